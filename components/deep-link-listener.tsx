@@ -0,0 +1,41 @@
+"use client";
+
+import { useEffect } from "react";
+import { useRouter } from "next/navigation";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { devError, isTauriContext } from "@/lib/env";
+
+/** Where a Spotify link leads, as resolved by the backend */
+interface LinkTarget {
+  kind: "track" | "album" | "artist" | "playlist" | "show" | "episode" | "user";
+  id: string;
+  uri: string;
+  /** Page to open, null for items without one */
+  route: string | null;
+}
+
+/**
+ * Follows Spotify links opened from outside the app:
+ * the link the app was launched with, then every link clicked while it runs.
+ */
+export function DeepLinkListener() {
+  const router = useRouter();
+
+  useEffect(() => {
+    if (!isTauriContext()) return;
+
+    const follow = (target: LinkTarget | null) => {
+      if (target?.route) router.push(target.route);
+    };
+
+    invoke<LinkTarget | null>("take_startup_link").then(follow).catch(devError);
+    const unlisten = listen<LinkTarget>("deeplink:navigate", (event) => follow(event.payload));
+
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, [router]);
+
+  return null;
+}
@@ -8,6 +8,7 @@ import { LyricsProvider } from '@/lib/lrclib';
 import { FullscreenProvider } from '@/lib/fullscreen';
 import { AppGate } from '@/components/app-gate';
 import { ContextMenuBlocker } from '@/components/context-menu-blocker';
+import { DeepLinkListener } from '@/components/deep-link-listener';
 
 interface ProvidersProps {
   children: ReactNode;
@@ -17,6 +18,7 @@ export const Providers: FC<ProvidersProps> = ({ children }) => {
   return (
     <ThemeProvider attribute="class" defaultTheme='dark'>
       <ContextMenuBlocker />
+      <DeepLinkListener />
       <AuthProvider>
         <FullscreenProvider>
           <SpotifyPlayerProvider>
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use tauri::State;
+
+use super::model::{
+    Annotation, AnnotationExport, AnnotationInput, Annotations, ImportMode, ImportSummary,
+    SearchHit, TagCount, TagQuery,
+};
+use crate::auth::AppAuthState;
+use crate::error::AppError;
+use crate::store;
+
+const ANNOTATIONS_FILE: &str = "annotations.json";
+
+/// Load the annotations of the signed-in account
+pub fn load_annotations(state: &AppAuthState) -> Result<Annotations, AppError> {
+    Ok(store::load_json(&store::account_file(state, ANNOTATIONS_FILE)?)?.unwrap_or_default())
+}
+
+/// Load, modify and save the annotations in one locked step
+fn update<T>(
+    state: &AppAuthState,
+    f: impl FnOnce(&mut Annotations) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    store::update_json(&store::account_file(state, ANNOTATIONS_FILE)?, f)
+}
+
+/// Get the annotations of the given URIs (unannotated URIs are omitted)
+#[tauri::command]
+pub fn get_annotations(
+    uris: Vec<String>,
+    state: State<AppAuthState>,
+) -> Result<HashMap<String, Annotation>, AppError> {
+    let annotations = load_annotations(&state)?;
+    Ok(uris
+        .into_iter()
+        .filter_map(|uri| annotations.get(&uri).cloned().map(|a| (uri, a)))
+        .collect())
+}
+
+/// Replace the rating, note and tags of a URI; an empty annotation is removed
+#[tauri::command]
+pub fn set_annotation(
+    uri: String,
+    annotation: AnnotationInput,
+    state: State<AppAuthState>,
+) -> Result<Option<Annotation>, AppError> {
+    update(&state, |a| a.set(&uri, annotation))
+}
+
+/// Set or clear the star rating of a URI
+#[tauri::command]
+pub fn set_rating(
+    uri: String,
+    rating: Option<u8>,
+    state: State<AppAuthState>,
+) -> Result<Option<Annotation>, AppError> {
+    update(&state, |a| a.set_rating(&uri, rating))
+}
+
+/// Add and remove tags on several URIs, e.g. from a multi-selection context menu
+#[tauri::command]
+pub fn edit_tags(
+    uris: Vec<String>,
+    add: Option<Vec<String>>,
+    remove: Option<Vec<String>>,
+    state: State<AppAuthState>,
+) -> Result<(), AppError> {
+    update(&state, |a| {
+        a.edit_tags(&uris, &add.unwrap_or_default(), &remove.unwrap_or_default());
+        Ok(())
+    })
+}
+
+/// List every tag in use, most used first
+#[tauri::command]
+pub fn list_tags(state: State<AppAuthState>) -> Result<Vec<TagCount>, AppError> {
+    Ok(load_annotations(&state)?.tags())
+}
+
+/// Get the URIs whose tags satisfy a query
+#[tauri::command]
+pub fn query_tags(query: TagQuery, state: State<AppAuthState>) -> Result<Vec<String>, AppError> {
+    Ok(load_annotations(&state)?.query_tags(&query))
+}
+
+/// Full-text search over notes and tags
+#[tauri::command]
+pub fn search_annotations(
+    query: String,
+    state: State<AppAuthState>,
+) -> Result<Vec<SearchHit>, AppError> {
+    Ok(load_annotations(&state)?.search(&query))
+}
+
+/// Export every annotation as a JSON document
+#[tauri::command]
+pub fn export_annotations(state: State<AppAuthState>) -> Result<String, AppError> {
+    serde_json::to_string_pretty(&load_annotations(&state)?.export())
+        .map_err(|e| AppError::Storage(format!("Failed to serialize: {}", e)))
+}
+
+/// Import a document produced by `export_annotations`
+#[tauri::command]
+pub fn import_annotations(
+    content: String,
+    mode: Option<ImportMode>,
+    state: State<AppAuthState>,
+) -> Result<ImportSummary, AppError> {
+    let export: AnnotationExport = serde_json::from_str(&content)
+        .map_err(|e| AppError::InvalidInput(format!("Not an annotations export: {}", e)))?;
+    update(&state, |a| {
+        Ok(a.import(export, mode.unwrap_or(ImportMode::Merge)))
+    })
+}
@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod model;
+
+pub use commands::*;
+pub use model::{Annotation, Annotations};
@@ -0,0 +1,432 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::error::AppError;
+use crate::playlists::matching::normalize;
+
+/// Highest star rating
+pub const MAX_RATING: u8 = 5;
+
+/// Local annotation of a track, album or artist
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    /// Star rating from 1 to 5
+    pub rating: Option<u8>,
+    pub note: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Annotation {
+    fn is_empty(&self) -> bool {
+        self.rating.is_none() && self.note.is_none() && self.tags.is_empty()
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| *t == normalize_tag(tag))
+    }
+}
+
+/// Fields the frontend sends when editing an annotation
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AnnotationInput {
+    pub rating: Option<u8>,
+    pub note: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Tag filter: every tag in `all`, at least one in `any` (when given), none in `none`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TagQuery {
+    #[serde(default)]
+    pub all: Vec<String>,
+    #[serde(default)]
+    pub any: Vec<String>,
+    #[serde(default)]
+    pub none: Vec<String>,
+}
+
+impl TagQuery {
+    pub fn matches(&self, annotation: &Annotation) -> bool {
+        self.all.iter().all(|t| annotation.has_tag(t))
+            && (self.any.is_empty() || self.any.iter().any(|t| annotation.has_tag(t)))
+            && !self.none.iter().any(|t| annotation.has_tag(t))
+    }
+}
+
+/// A full-text search hit
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub uri: String,
+    pub annotation: Annotation,
+    pub score: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+/// How imported annotations are combined with the existing ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Keep whichever version of each annotation was updated last
+    Merge,
+    /// Drop every existing annotation first
+    Replace,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ImportSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub skipped: usize,
+}
+
+/// Portable export document
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AnnotationExport {
+    pub exported_at: DateTime<Utc>,
+    pub annotations: BTreeMap<String, Annotation>,
+}
+
+/// Every annotation of an account, keyed by Spotify URI
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Annotations {
+    pub items: HashMap<String, Annotation>,
+}
+
+/// Tags are compared case-insensitively and without surrounding whitespace
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+fn normalize_tags(tags: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    tags.into_iter()
+        .map(|t| normalize_tag(&t))
+        .filter(|t| !t.is_empty() && seen.insert(t.clone()))
+        .collect()
+}
+
+fn validate_rating(rating: Option<u8>) -> Result<(), AppError> {
+    match rating {
+        Some(r) if r == 0 || r > MAX_RATING => Err(AppError::InvalidInput(format!(
+            "Rating must be between 1 and {}",
+            MAX_RATING
+        ))),
+        _ => Ok(()),
+    }
+}
+
+impl Annotations {
+    pub fn get(&self, uri: &str) -> Option<&Annotation> {
+        self.items.get(uri)
+    }
+
+    /// Replace the annotation of a URI; an empty input removes it
+    pub fn set(
+        &mut self,
+        uri: &str,
+        input: AnnotationInput,
+    ) -> Result<Option<Annotation>, AppError> {
+        validate_rating(input.rating)?;
+        let annotation = Annotation {
+            rating: input.rating,
+            note: input
+                .note
+                .map(|n| n.trim().to_string())
+                .filter(|n| !n.is_empty()),
+            tags: normalize_tags(input.tags),
+            updated_at: Utc::now(),
+        };
+        Ok(self.store(uri, annotation))
+    }
+
+    pub fn set_rating(
+        &mut self,
+        uri: &str,
+        rating: Option<u8>,
+    ) -> Result<Option<Annotation>, AppError> {
+        validate_rating(rating)?;
+        let mut annotation = self.current(uri);
+        annotation.rating = rating;
+        Ok(self.store(uri, annotation))
+    }
+
+    /// Add and remove tags on several URIs at once
+    pub fn edit_tags(&mut self, uris: &[String], add: &[String], remove: &[String]) {
+        let add = normalize_tags(add.iter().cloned());
+        let remove = normalize_tags(remove.iter().cloned());
+
+        for uri in uris {
+            let mut annotation = self.current(uri);
+            annotation.tags.retain(|t| !remove.contains(t));
+            let tags = annotation
+                .tags
+                .iter()
+                .chain(&add)
+                .cloned()
+                .collect::<Vec<_>>();
+            annotation.tags = normalize_tags(tags);
+            self.store(uri, annotation);
+        }
+    }
+
+    fn current(&self, uri: &str) -> Annotation {
+        self.items.get(uri).cloned().unwrap_or(Annotation {
+            rating: None,
+            note: None,
+            tags: Vec::new(),
+            updated_at: Utc::now(),
+        })
+    }
+
+    fn store(&mut self, uri: &str, mut annotation: Annotation) -> Option<Annotation> {
+        if annotation.is_empty() {
+            self.items.remove(uri);
+            return None;
+        }
+        annotation.updated_at = Utc::now();
+        self.items.insert(uri.to_string(), annotation.clone());
+        Some(annotation)
+    }
+
+    /// Every tag in use with the number of annotations carrying it
+    pub fn tags(&self) -> Vec<TagCount> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for tag in self.items.values().flat_map(|a| &a.tags) {
+            *counts.entry(tag).or_default() += 1;
+        }
+
+        let mut tags: Vec<TagCount> = counts
+            .into_iter()
+            .map(|(tag, count)| TagCount {
+                tag: tag.to_string(),
+                count,
+            })
+            .collect();
+        tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+        tags
+    }
+
+    /// URIs whose tags satisfy a query
+    pub fn query_tags(&self, query: &TagQuery) -> Vec<String> {
+        let mut uris: Vec<String> = self
+            .items
+            .iter()
+            .filter(|(_, a)| query.matches(a))
+            .map(|(uri, _)| uri.clone())
+            .collect();
+        uris.sort();
+        uris
+    }
+
+    /// Full-text search over notes and tags
+    ///
+    /// Every query word must prefix some word of the note or a tag; hits are
+    /// ranked by how many words they match, most recently edited first on ties.
+    pub fn search(&self, query: &str) -> Vec<SearchHit> {
+        let terms: Vec<String> = normalize(query)
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        let mut hits: Vec<SearchHit> = self
+            .items
+            .iter()
+            .filter_map(|(uri, annotation)| {
+                let text = format!(
+                    "{} {}",
+                    annotation.note.as_deref().unwrap_or_default(),
+                    annotation.tags.join(" ")
+                );
+                let words: Vec<String> = normalize(&text)
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .collect();
+
+                let mut score = 0;
+                for term in &terms {
+                    let count = words
+                        .iter()
+                        .filter(|w| w.starts_with(term.as_str()))
+                        .count();
+                    if count == 0 {
+                        return None;
+                    }
+                    score += count;
+                }
+                Some(SearchHit {
+                    uri: uri.clone(),
+                    annotation: annotation.clone(),
+                    score,
+                })
+            })
+            .collect();
+
+        hits.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| b.annotation.updated_at.cmp(&a.annotation.updated_at))
+        });
+        hits
+    }
+
+    pub fn export(&self) -> AnnotationExport {
+        AnnotationExport {
+            exported_at: Utc::now(),
+            annotations: self
+                .items
+                .iter()
+                .map(|(uri, a)| (uri.clone(), a.clone()))
+                .collect(),
+        }
+    }
+
+    pub fn import(&mut self, export: AnnotationExport, mode: ImportMode) -> ImportSummary {
+        if mode == ImportMode::Replace {
+            self.items.clear();
+        }
+
+        let mut summary = ImportSummary::default();
+        for (uri, mut annotation) in export.annotations {
+            annotation.tags = normalize_tags(annotation.tags);
+            if validate_rating(annotation.rating).is_err() || annotation.is_empty() {
+                summary.skipped += 1;
+                continue;
+            }
+
+            match self.items.get(&uri) {
+                None => summary.added += 1,
+                Some(existing) if existing.updated_at < annotation.updated_at => {
+                    summary.updated += 1
+                }
+                Some(_) => {
+                    summary.skipped += 1;
+                    continue;
+                }
+            }
+            self.items.insert(uri, annotation);
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(rating: Option<u8>, note: &str, tags: &[&str]) -> AnnotationInput {
+        AnnotationInput {
+            rating,
+            note: Some(note.into()),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_set_normalizes_and_removes_empty() {
+        let mut annotations = Annotations::default();
+        let saved = annotations
+            .set(
+                "spotify:track:a",
+                input(Some(4), "  ", &["Warmup", "warmup ", ""]),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.note, None);
+        assert_eq!(saved.tags, vec!["warmup".to_string()]);
+
+        assert!(annotations
+            .set("spotify:track:a", AnnotationInput::default())
+            .unwrap()
+            .is_none());
+        assert!(annotations.items.is_empty());
+        assert!(annotations.set_rating("spotify:track:a", Some(6)).is_err());
+    }
+
+    #[test]
+    fn test_tag_query_and_bulk_edit() {
+        let mut annotations = Annotations::default();
+        let uris = vec!["spotify:track:a".to_string(), "spotify:track:b".to_string()];
+        annotations.edit_tags(&uris, &["warmup".into(), "needs-review".into()], &[]);
+        annotations.edit_tags(&uris[..1], &[], &["Needs-Review".into()]);
+
+        let query = TagQuery {
+            all: vec!["warmup".into()],
+            none: vec!["needs-review".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            annotations.query_tags(&query),
+            vec!["spotify:track:a".to_string()]
+        );
+        assert_eq!(annotations.tags()[0].tag, "warmup");
+    }
+
+    #[test]
+    fn test_search_notes() {
+        let mut annotations = Annotations::default();
+        annotations
+            .set(
+                "spotify:track:a",
+                input(None, "Great bassline, great drums", &[]),
+            )
+            .unwrap();
+        annotations
+            .set(
+                "spotify:track:b",
+                input(None, "Bassline is fine", &["live"]),
+            )
+            .unwrap();
+
+        let hits = annotations.search("great bass");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].uri, "spotify:track:a");
+        assert_eq!(annotations.search("bassline").len(), 2);
+        assert_eq!(annotations.search("live")[0].uri, "spotify:track:b");
+    }
+
+    #[test]
+    fn test_import_merge_keeps_newest() {
+        let mut annotations = Annotations::default();
+        annotations
+            .set("spotify:track:a", input(Some(2), "old", &[]))
+            .unwrap();
+        let mut export = annotations.export();
+
+        annotations
+            .set("spotify:track:a", input(Some(5), "newer", &[]))
+            .unwrap();
+        export.annotations.insert(
+            "spotify:track:b".into(),
+            Annotation {
+                rating: Some(3),
+                note: None,
+                tags: vec!["Chill".into()],
+                updated_at: Utc::now(),
+            },
+        );
+
+        let summary = annotations.import(export, ImportMode::Merge);
+        assert_eq!(
+            summary,
+            ImportSummary {
+                added: 1,
+                updated: 0,
+                skipped: 1
+            }
+        );
+        assert_eq!(annotations.items["spotify:track:a"].rating, Some(5));
+        assert!(annotations.items["spotify:track:b"].has_tag("chill"));
+    }
+}
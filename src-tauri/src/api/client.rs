@@ -0,0 +1,221 @@
+use reqwest::{header, Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::time::Duration;
+
+use super::types::Paging;
+use crate::auth::{self, AppAuthState};
+use crate::error::AppError;
+
+const API_BASE: &str = "https://api.spotify.com/v1";
+
+/// How many times a rate-limited request is retried before giving up
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+/// Longest `Retry-After` we are willing to wait for inside a single call
+const MAX_RETRY_AFTER_SECS: u64 = 30;
+
+const IMAGE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Thin Spotify Web API client built on the shared auth state
+///
+/// Tokens are fetched (and refreshed) per request through the auth layer, so a
+/// client can be created cheaply wherever an `AppAuthState` is reachable.
+pub struct SpotifyApi<'a> {
+    auth: &'a AppAuthState,
+}
+
+impl<'a> SpotifyApi<'a> {
+    pub fn new(auth: &'a AppAuthState) -> Self {
+        Self { auth }
+    }
+
+    /// Build a full URL from an API path, leaving absolute URLs (`next` links) untouched
+    fn url(path: &str) -> String {
+        if path.starts_with("http") {
+            path.to_string()
+        } else {
+            format!("{}{}", API_BASE, path)
+        }
+    }
+
+    /// Send a request and return the raw response body
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<&Value>,
+    ) -> Result<String, AppError> {
+        let url = Self::url(path);
+        let mut retries = 0;
+
+        loop {
+            let token = auth::access_token(self.auth).await?;
+
+            let mut request = self
+                .auth
+                .http_client
+                .request(method.clone(), &url)
+                .bearer_auth(&token)
+                .query(query);
+
+            request = match body {
+                Some(body) => request.json(body),
+                // Spotify rejects body-less PUT/POST requests without a length
+                None if method != Method::GET => request.header(header::CONTENT_LENGTH, 0),
+                None => request,
+            };
+
+            let response = request
+                .send()
+                .await
+                .map_err(|e| AppError::Http(e.to_string()))?;
+
+            let status = response.status();
+
+            if status == StatusCode::TOO_MANY_REQUESTS {
+                let retry_after = response
+                    .headers()
+                    .get(header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(1);
+
+                if retries >= MAX_RATE_LIMIT_RETRIES || retry_after > MAX_RETRY_AFTER_SECS {
+                    return Err(AppError::RateLimited(retry_after));
+                }
+
+                retries += 1;
+                log::warn!("Rate limited on {}, retrying in {}s", path, retry_after);
+                tokio::time::sleep(Duration::from_secs(retry_after)).await;
+                continue;
+            }
+
+            let text = response
+                .text()
+                .await
+                .map_err(|e| AppError::Http(e.to_string()))?;
+
+            if !status.is_success() {
+                return Err(AppError::Api {
+                    status: status.as_u16(),
+                    message: error_message(&text),
+                });
+            }
+
+            return Ok(text);
+        }
+    }
+
+    /// Send a request and deserialize the JSON response
+    async fn send_json<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<&Value>,
+    ) -> Result<T, AppError> {
+        let text = self.send(method, path, query, body).await?;
+        serde_json::from_str(&text).map_err(|e| AppError::Parse(format!("{}: {}", path, e)))
+    }
+
+    /// GET a JSON resource
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, AppError> {
+        self.send_json(Method::GET, path, query, None).await
+    }
+
+    /// GET a JSON resource that may legitimately be empty (204 No Content)
+    pub async fn get_optional<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<Option<T>, AppError> {
+        let text = self.send(Method::GET, path, query, None).await?;
+        if text.trim().is_empty() {
+            return Ok(None);
+        }
+        serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|e| AppError::Parse(format!("{}: {}", path, e)))
+    }
+
+    /// POST a JSON body and deserialize the response
+    pub async fn post<T: DeserializeOwned>(&self, path: &str, body: &Value) -> Result<T, AppError> {
+        self.send_json(Method::POST, path, &[], Some(body)).await
+    }
+
+    /// PUT a JSON body and deserialize the response
+    pub async fn put<T: DeserializeOwned>(&self, path: &str, body: &Value) -> Result<T, AppError> {
+        self.send_json(Method::PUT, path, &[], Some(body)).await
+    }
+
+    /// DELETE with a JSON body and deserialize the response
+    pub async fn delete<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &Value,
+    ) -> Result<T, AppError> {
+        self.send_json(Method::DELETE, path, &[], Some(body)).await
+    }
+
+    /// Send a request whose response body is ignored
+    pub async fn execute(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<&Value>,
+    ) -> Result<(), AppError> {
+        self.send(method, path, query, body).await.map(|_| ())
+    }
+
+    /// Fetch every page of a paginated resource
+    pub async fn get_all<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<Vec<T>, AppError> {
+        let mut page: Paging<T> = self.get(path, query).await?;
+        let mut items = std::mem::take(&mut page.items);
+
+        while let Some(next) = page.next.take() {
+            page = self.get(&next, &[]).await?;
+            items.append(&mut page.items);
+        }
+
+        Ok(items)
+    }
+}
+
+/// Download an image from Spotify's CDN, which needs no token
+pub async fn download_image(url: &str) -> Result<Vec<u8>, AppError> {
+    let bytes = reqwest::Client::new()
+        .get(url)
+        .timeout(IMAGE_TIMEOUT)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| AppError::Http(e.to_string()))?
+        .bytes()
+        .await
+        .map_err(|e| AppError::Http(e.to_string()))?;
+    Ok(bytes.to_vec())
+}
+
+/// Extract the human readable message from a Spotify error body
+fn error_message(body: &str) -> String {
+    serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|v| {
+            v.pointer("/error/message")
+                .or_else(|| v.pointer("/error_description"))
+                .and_then(|m| m.as_str())
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| body.to_string())
+}
@@ -0,0 +1,64 @@
+use reqwest::Method;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{
+    client::SpotifyApi,
+    types::{Artist, PlayHistory, SavedTrack},
+};
+use crate::error::AppError;
+
+#[derive(Debug, Deserialize)]
+struct ArtistsResponse {
+    artists: Vec<Option<Artist>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RecentlyPlayedResponse {
+    items: Vec<PlayHistory>,
+}
+
+impl SpotifyApi<'_> {
+    /// Fetch every track in the user's library
+    pub async fn saved_tracks(&self) -> Result<Vec<SavedTrack>, AppError> {
+        self.get_all(
+            "/me/tracks",
+            &[
+                ("limit", "50".to_string()),
+                ("market", "from_token".to_string()),
+            ],
+        )
+        .await
+    }
+
+    /// Save tracks to the user's library, 50 per request
+    pub async fn save_tracks(&self, ids: &[String]) -> Result<(), AppError> {
+        for chunk in ids.chunks(50) {
+            let body = json!({ "ids": chunk });
+            self.execute(Method::PUT, "/me/tracks", &[], Some(&body))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Fetch several artists by id, 50 per request
+    pub async fn artists(&self, ids: &[String]) -> Result<Vec<Artist>, AppError> {
+        let mut artists = Vec::with_capacity(ids.len());
+
+        for chunk in ids.chunks(50) {
+            let response: ArtistsResponse =
+                self.get("/artists", &[("ids", chunk.join(","))]).await?;
+            artists.extend(response.artists.into_iter().flatten());
+        }
+
+        Ok(artists)
+    }
+
+    /// Fetch the last 50 played tracks
+    pub async fn recently_played(&self) -> Result<Vec<PlayHistory>, AppError> {
+        let response: RecentlyPlayedResponse = self
+            .get("/me/player/recently-played", &[("limit", "50".to_string())])
+            .await?;
+        Ok(response.items)
+    }
+}
@@ -0,0 +1,10 @@
+pub mod client;
+pub mod library;
+pub mod player;
+pub mod playlists;
+pub mod search;
+pub mod tracks;
+pub mod types;
+
+pub use client::*;
+pub use types::*;
@@ -0,0 +1,173 @@
+use reqwest::Method;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{
+    client::SpotifyApi,
+    types::{CurrentPlayback, Device, RepeatState},
+};
+use crate::error::AppError;
+
+#[derive(Debug, Deserialize)]
+struct DevicesResponse {
+    devices: Vec<Device>,
+}
+
+fn device_query(device_id: Option<&str>) -> Vec<(&'static str, String)> {
+    device_id
+        .map(|id| vec![("device_id", id.to_string())])
+        .unwrap_or_default()
+}
+
+impl SpotifyApi<'_> {
+    /// Get the current playback state, `None` when nothing is playing anywhere
+    pub async fn playback_state(&self) -> Result<Option<CurrentPlayback>, AppError> {
+        self.get_optional("/me/player", &[("market", "from_token".to_string())])
+            .await
+    }
+
+    /// List the user's available Connect devices
+    pub async fn devices(&self) -> Result<Vec<Device>, AppError> {
+        let response: DevicesResponse = self.get("/me/player/devices", &[]).await?;
+        Ok(response.devices)
+    }
+
+    /// Start playing a list of URIs, on the active device unless one is given
+    pub async fn play_uris(
+        &self,
+        uris: &[String],
+        device_id: Option<&str>,
+    ) -> Result<(), AppError> {
+        let body = json!({ "uris": uris });
+        self.execute(
+            Method::PUT,
+            "/me/player/play",
+            &device_query(device_id),
+            Some(&body),
+        )
+        .await
+    }
+
+    /// Start playing an album, playlist or artist context
+    pub async fn play_context(
+        &self,
+        context_uri: &str,
+        device_id: Option<&str>,
+    ) -> Result<(), AppError> {
+        let body = json!({ "context_uri": context_uri });
+        self.execute(
+            Method::PUT,
+            "/me/player/play",
+            &device_query(device_id),
+            Some(&body),
+        )
+        .await
+    }
+
+    /// Append an item to the end of Spotify's playback queue
+    pub async fn add_to_queue(&self, uri: &str) -> Result<(), AppError> {
+        self.execute(
+            Method::POST,
+            "/me/player/queue",
+            &[("uri", uri.to_string())],
+            None,
+        )
+        .await
+    }
+
+    /// Resume playback on the active device (or the given one)
+    pub async fn resume(&self, device_id: Option<&str>) -> Result<(), AppError> {
+        self.execute(
+            Method::PUT,
+            "/me/player/play",
+            &device_query(device_id),
+            None,
+        )
+        .await
+    }
+
+    /// Pause playback on the active device (or the given one)
+    pub async fn pause(&self, device_id: Option<&str>) -> Result<(), AppError> {
+        self.execute(
+            Method::PUT,
+            "/me/player/pause",
+            &device_query(device_id),
+            None,
+        )
+        .await
+    }
+
+    /// Skip to the next item on the active device (or the given one)
+    pub async fn skip_next(&self, device_id: Option<&str>) -> Result<(), AppError> {
+        self.execute(
+            Method::POST,
+            "/me/player/next",
+            &device_query(device_id),
+            None,
+        )
+        .await
+    }
+
+    /// Go back to the previous item on the active device (or the given one)
+    pub async fn skip_previous(&self, device_id: Option<&str>) -> Result<(), AppError> {
+        self.execute(
+            Method::POST,
+            "/me/player/previous",
+            &device_query(device_id),
+            None,
+        )
+        .await
+    }
+
+    /// Seek to a position in the current item
+    pub async fn seek(&self, position_ms: u64, device_id: Option<&str>) -> Result<(), AppError> {
+        let mut query = device_query(device_id);
+        query.push(("position_ms", position_ms.to_string()));
+        self.execute(Method::PUT, "/me/player/seek", &query, None)
+            .await
+    }
+
+    /// Turn shuffle on or off
+    pub async fn set_shuffle(
+        &self,
+        shuffle: bool,
+        device_id: Option<&str>,
+    ) -> Result<(), AppError> {
+        let mut query = device_query(device_id);
+        query.push(("state", shuffle.to_string()));
+        self.execute(Method::PUT, "/me/player/shuffle", &query, None)
+            .await
+    }
+
+    /// Set the repeat mode
+    pub async fn set_repeat(
+        &self,
+        repeat: RepeatState,
+        device_id: Option<&str>,
+    ) -> Result<(), AppError> {
+        let state = match repeat {
+            RepeatState::Off => "off",
+            RepeatState::Track => "track",
+            RepeatState::Context => "context",
+        };
+        let mut query = device_query(device_id);
+        query.push(("state", state.to_string()));
+        self.execute(Method::PUT, "/me/player/repeat", &query, None)
+            .await
+    }
+
+    /// Set the volume of the active device (or the given one)
+    pub async fn set_volume(&self, percent: u32, device_id: Option<&str>) -> Result<(), AppError> {
+        let mut query = device_query(device_id);
+        query.push(("volume_percent", percent.min(100).to_string()));
+        self.execute(Method::PUT, "/me/player/volume", &query, None)
+            .await
+    }
+
+    /// Move playback to another device, optionally starting it
+    pub async fn transfer_playback(&self, device_id: &str, play: bool) -> Result<(), AppError> {
+        let body = json!({ "device_ids": [device_id], "play": play });
+        self.execute(Method::PUT, "/me/player", &[], Some(&body))
+            .await
+    }
+}
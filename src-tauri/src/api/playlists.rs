@@ -0,0 +1,158 @@
+use serde_json::json;
+
+use super::{
+    client::SpotifyApi,
+    types::{Playlist, PlaylistItem, SnapshotResponse},
+};
+use crate::error::AppError;
+
+/// Maximum number of items Spotify accepts per playlist mutation
+pub const PLAYLIST_BATCH_SIZE: usize = 100;
+
+impl SpotifyApi<'_> {
+    /// Create a playlist owned by the given user
+    pub async fn create_playlist(
+        &self,
+        user_id: &str,
+        name: &str,
+        description: Option<&str>,
+        public: bool,
+    ) -> Result<Playlist, AppError> {
+        let mut body = json!({ "name": name, "public": public });
+        if let Some(description) = description {
+            body["description"] = json!(description);
+        }
+
+        self.post(
+            &format!("/users/{}/playlists", urlencoding::encode(user_id)),
+            &body,
+        )
+        .await
+    }
+
+    /// Fetch playlist metadata
+    pub async fn playlist(&self, playlist_id: &str) -> Result<Playlist, AppError> {
+        self.get(
+            &format!("/playlists/{}", playlist_id),
+            &[(
+                "fields",
+                "id,uri,name,description,snapshot_id,owner(id,display_name),collaborative,public,images"
+                    .to_string(),
+            )],
+        )
+        .await
+    }
+
+    /// Fetch every item of a playlist
+    pub async fn playlist_items(&self, playlist_id: &str) -> Result<Vec<PlaylistItem>, AppError> {
+        self.get_all(
+            &format!("/playlists/{}/tracks", playlist_id),
+            &[
+                ("limit", "100".to_string()),
+                ("market", "from_token".to_string()),
+            ],
+        )
+        .await
+    }
+
+    /// Append items to a playlist in batches of 100, returning the final snapshot id
+    pub async fn add_playlist_items(
+        &self,
+        playlist_id: &str,
+        uris: &[String],
+    ) -> Result<Option<String>, AppError> {
+        let mut snapshot = None;
+
+        for chunk in uris.chunks(PLAYLIST_BATCH_SIZE) {
+            let response: SnapshotResponse = self
+                .post(
+                    &format!("/playlists/{}/tracks", playlist_id),
+                    &json!({ "uris": chunk }),
+                )
+                .await?;
+            snapshot = Some(response.snapshot_id);
+        }
+
+        Ok(snapshot)
+    }
+
+    /// Replace the whole content of a playlist, returning the final snapshot id
+    ///
+    /// The first batch replaces the items, later batches are appended.
+    pub async fn replace_playlist_items(
+        &self,
+        playlist_id: &str,
+        uris: &[String],
+    ) -> Result<String, AppError> {
+        let (first, rest) = uris.split_at(uris.len().min(PLAYLIST_BATCH_SIZE));
+
+        let response: SnapshotResponse = self
+            .put(
+                &format!("/playlists/{}/tracks", playlist_id),
+                &json!({ "uris": first }),
+            )
+            .await?;
+
+        Ok(self
+            .add_playlist_items(playlist_id, rest)
+            .await?
+            .unwrap_or(response.snapshot_id))
+    }
+
+    /// Remove items at specific positions, one batch of 100 at a time
+    ///
+    /// `removals` pairs a URI with the positions it occupies. Batches are sent from
+    /// the end of the playlist backwards so earlier positions stay valid while the
+    /// snapshot advances between requests.
+    pub async fn remove_playlist_positions(
+        &self,
+        playlist_id: &str,
+        snapshot_id: &str,
+        removals: &[(String, usize)],
+    ) -> Result<String, AppError> {
+        let mut sorted = removals.to_vec();
+        sorted.sort_by_key(|r| std::cmp::Reverse(r.1));
+        sorted.dedup_by_key(|r| r.1);
+
+        let mut snapshot = snapshot_id.to_string();
+        for chunk in sorted.chunks(PLAYLIST_BATCH_SIZE) {
+            let tracks: Vec<_> = chunk
+                .iter()
+                .map(|(uri, position)| json!({ "uri": uri, "positions": [position] }))
+                .collect();
+
+            let response: SnapshotResponse = self
+                .delete(
+                    &format!("/playlists/{}/tracks", playlist_id),
+                    &json!({ "tracks": tracks, "snapshot_id": snapshot }),
+                )
+                .await?;
+            snapshot = response.snapshot_id;
+        }
+
+        Ok(snapshot)
+    }
+
+    /// Move a range of items, returning the new snapshot id
+    pub async fn reorder_playlist_items(
+        &self,
+        playlist_id: &str,
+        range_start: usize,
+        range_length: usize,
+        insert_before: usize,
+        snapshot_id: &str,
+    ) -> Result<String, AppError> {
+        let response: SnapshotResponse = self
+            .put(
+                &format!("/playlists/{}/tracks", playlist_id),
+                &json!({
+                    "range_start": range_start,
+                    "range_length": range_length,
+                    "insert_before": insert_before,
+                    "snapshot_id": snapshot_id,
+                }),
+            )
+            .await?;
+        Ok(response.snapshot_id)
+    }
+}
@@ -0,0 +1,35 @@
+use serde::Deserialize;
+
+use super::{
+    client::SpotifyApi,
+    types::{Paging, Track},
+};
+use crate::error::AppError;
+
+#[derive(Debug, Deserialize)]
+struct TrackSearchResponse {
+    tracks: Paging<Track>,
+}
+
+impl SpotifyApi<'_> {
+    /// Search the catalogue for tracks
+    pub async fn search_tracks(&self, query: &str, limit: u32) -> Result<Vec<Track>, AppError> {
+        let response: TrackSearchResponse = self
+            .get(
+                "/search",
+                &[
+                    ("q", query.to_string()),
+                    ("type", "track".to_string()),
+                    ("limit", limit.min(50).to_string()),
+                ],
+            )
+            .await?;
+
+        Ok(response.tracks.items)
+    }
+
+    /// Look up tracks by ISRC
+    pub async fn tracks_by_isrc(&self, isrc: &str) -> Result<Vec<Track>, AppError> {
+        self.search_tracks(&format!("isrc:{}", isrc), 5).await
+    }
+}
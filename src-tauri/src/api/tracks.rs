@@ -0,0 +1,93 @@
+use serde::Deserialize;
+
+use super::{
+    client::SpotifyApi,
+    types::{Album, AudioFeatures, Track},
+};
+use crate::error::AppError;
+
+#[derive(Debug, Deserialize)]
+struct TracksResponse {
+    tracks: Vec<Option<Track>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RecommendationsResponse {
+    tracks: Vec<Track>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AudioFeaturesResponse {
+    audio_features: Vec<Option<AudioFeatures>>,
+}
+
+impl SpotifyApi<'_> {
+    /// Fetch several tracks by id, 50 per request; unknown ids are skipped
+    pub async fn tracks(&self, ids: &[String]) -> Result<Vec<Track>, AppError> {
+        let mut tracks = Vec::with_capacity(ids.len());
+
+        for chunk in ids.chunks(50) {
+            let response: TracksResponse = self
+                .get(
+                    "/tracks",
+                    &[
+                        ("ids", chunk.join(",")),
+                        ("market", "from_token".to_string()),
+                    ],
+                )
+                .await?;
+            tracks.extend(response.tracks.into_iter().flatten());
+        }
+
+        Ok(tracks)
+    }
+
+    /// Fetch an album
+    pub async fn album(&self, album_id: &str) -> Result<Album, AppError> {
+        self.get(
+            &format!("/albums/{}", album_id),
+            &[("market", "from_token".to_string())],
+        )
+        .await
+    }
+
+    /// Fetch audio features for several tracks, 100 per request
+    pub async fn audio_features(&self, ids: &[String]) -> Result<Vec<AudioFeatures>, AppError> {
+        let mut features = Vec::with_capacity(ids.len());
+
+        for chunk in ids.chunks(100) {
+            let response: AudioFeaturesResponse = self
+                .get("/audio-features", &[("ids", chunk.join(","))])
+                .await?;
+            features.extend(response.audio_features.into_iter().flatten());
+        }
+
+        Ok(features)
+    }
+
+    /// Get recommendations from up to five seed tracks, artists and genres in total
+    pub async fn recommendations(
+        &self,
+        seed_tracks: &[String],
+        seed_artists: &[String],
+        seed_genres: &[String],
+        limit: u32,
+    ) -> Result<Vec<Track>, AppError> {
+        let mut query = vec![
+            ("limit", limit.clamp(1, 100).to_string()),
+            ("market", "from_token".to_string()),
+        ];
+        for (name, seeds) in [
+            ("seed_tracks", seed_tracks),
+            ("seed_artists", seed_artists),
+            ("seed_genres", seed_genres),
+        ] {
+            if !seeds.is_empty() {
+                query.push((name, seeds.join(",")));
+            }
+        }
+
+        let response: RecommendationsResponse = self.get("/recommendations", &query).await?;
+        Ok(response.tracks)
+    }
+}
@@ -0,0 +1,228 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::SpotifyImage;
+
+/// Generic Spotify paging object
+#[derive(Debug, Clone, Deserialize)]
+pub struct Paging<T> {
+    pub items: Vec<T>,
+    pub next: Option<String>,
+}
+
+/// Artist as embedded in tracks and albums
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SimplifiedArtist {
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub uri: String,
+}
+
+/// Album as embedded in tracks
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SimplifiedAlbum {
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub uri: String,
+    #[serde(default)]
+    pub release_date: Option<String>,
+    #[serde(default)]
+    pub images: Vec<SpotifyImage>,
+}
+
+/// Track originally requested when Spotify relinked it for the user's market
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkedTrack {
+    pub id: Option<String>,
+    pub uri: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExternalIds {
+    pub isrc: Option<String>,
+}
+
+/// Full track object
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Track {
+    pub id: Option<String>,
+    pub uri: String,
+    pub name: String,
+    #[serde(default)]
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub explicit: bool,
+    #[serde(default)]
+    pub artists: Vec<SimplifiedArtist>,
+    #[serde(default)]
+    pub album: SimplifiedAlbum,
+    #[serde(default)]
+    pub external_ids: ExternalIds,
+    /// Only present when a market was sent with the request
+    #[serde(default)]
+    pub is_playable: Option<bool>,
+    #[serde(default)]
+    pub is_local: bool,
+    #[serde(default)]
+    pub popularity: Option<u32>,
+    /// Set when `uri` is a relinked substitute, only with a market on the request
+    #[serde(default)]
+    pub linked_from: Option<LinkedTrack>,
+}
+
+impl Track {
+    /// Artist names joined for display
+    pub fn artist_names(&self) -> String {
+        self.artists
+            .iter()
+            .map(|a| a.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn isrc(&self) -> Option<&str> {
+        self.external_ids.isrc.as_deref()
+    }
+
+    /// URI as stored in playlists and queues, before relinking
+    pub fn original_uri(&self) -> &str {
+        self.linked_from
+            .as_ref()
+            .map_or(self.uri.as_str(), |linked| linked.uri.as_str())
+    }
+}
+
+/// Audio features of a track (only the fields the backend uses)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioFeatures {
+    pub id: String,
+    pub tempo: f64,
+}
+
+/// Minimal user object (e.g. `added_by` on playlist items)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublicUser {
+    pub id: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+}
+
+/// Item of a playlist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaylistItem {
+    pub added_at: Option<DateTime<Utc>>,
+    pub added_by: Option<PublicUser>,
+    #[serde(default)]
+    pub is_local: bool,
+    pub track: Option<Track>,
+}
+
+/// Item of the user's saved tracks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedTrack {
+    pub added_at: DateTime<Utc>,
+    pub track: Track,
+}
+
+/// Full artist object
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Artist {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub genres: Vec<String>,
+    #[serde(default)]
+    pub images: Vec<SpotifyImage>,
+}
+
+/// Full album object
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Album {
+    pub id: String,
+    pub uri: String,
+    pub name: String,
+    #[serde(default)]
+    pub artists: Vec<SimplifiedArtist>,
+    #[serde(default)]
+    pub release_date: Option<String>,
+    #[serde(default)]
+    pub images: Vec<SpotifyImage>,
+}
+
+/// Playback context reference (album, playlist, artist...)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Context {
+    pub uri: String,
+}
+
+/// Item of the recently played history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayHistory {
+    pub track: Track,
+    pub played_at: DateTime<Utc>,
+    pub context: Option<Context>,
+}
+
+/// Playlist metadata (without items)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Playlist {
+    pub id: String,
+    pub uri: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub snapshot_id: String,
+    pub owner: PublicUser,
+    #[serde(default)]
+    pub collaborative: bool,
+    #[serde(default)]
+    pub public: Option<bool>,
+    #[serde(default)]
+    pub images: Option<Vec<SpotifyImage>>,
+}
+
+/// Response of playlist item mutations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotResponse {
+    pub snapshot_id: String,
+}
+
+/// Spotify Connect device
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Device {
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub device_type: String,
+    #[serde(default)]
+    pub is_active: bool,
+    #[serde(default)]
+    pub is_restricted: bool,
+    pub volume_percent: Option<u32>,
+    #[serde(default)]
+    pub supports_volume: bool,
+}
+
+/// Repeat mode of the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RepeatState {
+    Off,
+    Track,
+    Context,
+}
+
+/// Response of `/me/player`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrentPlayback {
+    pub device: Device,
+    pub repeat_state: RepeatState,
+    pub shuffle_state: bool,
+    pub context: Option<Context>,
+    pub progress_ms: Option<u64>,
+    pub is_playing: bool,
+    /// `None` for ads, episodes and unknown items
+    pub item: Option<Track>,
+}
@@ -58,6 +58,8 @@ pub struct AppAuthState {
     pub pending_pkce: Mutex<Option<PkceData>>,
     pub current_auth: Mutex<Option<AuthState>>,
     pub http_client: Client,
+    /// Held while refreshing, so the refresh token is never sent twice
+    refreshing: tokio::sync::Mutex<()>,
 }
 
 impl AppAuthState {
@@ -67,6 +69,7 @@ impl AppAuthState {
             pending_pkce: Mutex::new(None),
             current_auth: Mutex::new(None),
             http_client: Client::new(),
+            refreshing: tokio::sync::Mutex::new(()),
         }
     }
 }
@@ -240,6 +243,20 @@ async fn fetch_user_profile(client: &Client, access_token: &str) -> Result<Spoti
 /// Refresh the access token
 #[tauri::command]
 pub async fn refresh_token(state: State<'_, AppAuthState>) -> Result<AuthSession, AuthError> {
+    refresh_session(&state).await
+}
+
+/// Refresh the stored tokens without going through the command layer
+pub async fn refresh_session(state: &AppAuthState) -> Result<AuthSession, AuthError> {
+    let _refreshing = state.refreshing.lock().await;
+    request_refresh(state).await
+}
+
+/// Exchange the refresh token for new tokens; callers hold `refreshing`
+///
+/// Spotify may rotate the refresh token, making the one a concurrent refresh
+/// sent invalid.
+async fn request_refresh(state: &AppAuthState) -> Result<AuthSession, AuthError> {
     let auth_state = state
         .current_auth
         .lock()
@@ -305,6 +322,11 @@ pub async fn refresh_token(state: State<'_, AppAuthState>) -> Result<AuthSession
 /// Get current session (checks and refreshes if needed)
 #[tauri::command]
 pub async fn get_session(state: State<'_, AppAuthState>) -> Result<Option<AuthSession>, AuthError> {
+    load_session(&state).await
+}
+
+/// Load the current session, refreshing it when close to expiry
+pub async fn load_session(state: &AppAuthState) -> Result<Option<AuthSession>, AuthError> {
     // Try memory first, then storage
     let auth_state = {
         let guard = state.current_auth.lock().unwrap();
@@ -327,8 +349,15 @@ pub async fn get_session(state: State<'_, AppAuthState>) -> Result<Option<AuthSe
 
     // Check if token needs refresh (within 5 minutes of expiry)
     if auth_state.tokens.expires_within(300) {
+        let _refreshing = state.refreshing.lock().await;
+        // Another caller may have refreshed while we waited
+        let current = state.current_auth.lock().unwrap().clone();
+        if let Some(current) = current.filter(|s| !s.tokens.expires_within(300)) {
+            return Ok(Some(AuthSession::from(&current)));
+        }
+
         log::info!("Token expiring soon, refreshing...");
-        match refresh_token(state).await {
+        match request_refresh(state).await {
             Ok(session) => return Ok(Some(session)),
             Err(e) => {
                 log::error!("Failed to refresh token: {}", e);
@@ -348,13 +377,27 @@ pub async fn get_session(state: State<'_, AppAuthState>) -> Result<Option<AuthSe
 pub async fn get_access_token(
     state: State<'_, AppAuthState>,
 ) -> Result<String, AuthError> {
-    let session = get_session(state)
+    access_token(&state).await
+}
+
+/// Get a valid access token for backend API calls
+pub async fn access_token(state: &AppAuthState) -> Result<String, AuthError> {
+    let session = load_session(state)
         .await?
         .ok_or(AuthError::NotAuthenticated)?;
 
     Ok(session.access_token)
 }
 
+/// Get the signed-in user's profile, if any
+pub fn current_user(state: &AppAuthState) -> Option<SpotifyUser> {
+    if let Some(auth) = state.current_auth.lock().unwrap().as_ref() {
+        return Some(auth.user.clone());
+    }
+
+    storage::load_auth_state().ok().flatten().map(|s| s.user)
+}
+
 /// Logout - clear all stored auth data
 #[tauri::command]
 pub fn logout(state: State<AppAuthState>) -> Result<(), AuthError> {
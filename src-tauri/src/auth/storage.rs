@@ -10,7 +10,7 @@ const APP_NAME: &str = "spotify-rework";
 const AUTH_FILE: &str = "auth.enc";
 
 /// Get the application data directory
-fn get_data_dir() -> Result<PathBuf, AuthError> {
+pub(crate) fn get_data_dir() -> Result<PathBuf, AuthError> {
     ProjectDirs::from(APP_QUALIFIER, APP_ORGANIZATION, APP_NAME)
         .map(|dirs| dirs.data_local_dir().to_path_buf())
         .ok_or_else(|| AuthError::StorageError("Could not determine data directory".into()))
@@ -0,0 +1,291 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::broadcast::error::RecvError;
+
+use super::rules::{ContextOverride, SkipCondition, SkipInput, SkipReason, SkipRule, SkipSettings};
+use crate::api::{RepeatState, SpotifyApi};
+use crate::auth::{self, AppAuthState};
+use crate::blocklist;
+use crate::error::AppError;
+use crate::library::history;
+use crate::playback::events::{PlaybackEvent, PlaybackSnapshot};
+use crate::playback::PlaybackHub;
+use crate::store;
+
+const SETTINGS_FILE: &str = "autoskip.json";
+const LOG_FILE: &str = "autoskip_log.json";
+
+/// Entries kept in the action log
+const MAX_LOG_ENTRIES: usize = 500;
+
+/// Automatic skips in a row after which enforcement pauses until a track plays,
+/// so a context where everything matches does not skip forever
+const MAX_CONSECUTIVE_SKIPS: u32 = 15;
+
+/// A skip performed by the rules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkipLogEntry {
+    pub at: DateTime<Utc>,
+    pub track_uri: String,
+    pub track_name: String,
+    pub context_uri: Option<String>,
+    pub reason: SkipReason,
+}
+
+fn load_settings(state: &AppAuthState) -> Result<SkipSettings, AppError> {
+    Ok(store::load_json(&store::account_file(state, SETTINGS_FILE)?)?.unwrap_or_default())
+}
+
+fn update<T>(
+    state: &AppAuthState,
+    f: impl FnOnce(&mut SkipSettings) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    store::update_json(&store::account_file(state, SETTINGS_FILE)?, f)
+}
+
+fn append_log(state: &AppAuthState, entry: SkipLogEntry) -> Result<(), AppError> {
+    store::update_json(
+        &store::account_file(state, LOG_FILE)?,
+        |log: &mut Vec<SkipLogEntry>| {
+            log.push(entry);
+            if log.len() > MAX_LOG_ENTRIES {
+                log.drain(..log.len() - MAX_LOG_ENTRIES);
+            }
+            Ok(())
+        },
+    )
+}
+
+/// Rule id reported for skips caused by the blocklist
+const BLOCKLIST_RULE_ID: &str = "blocklist";
+
+/// Decide whether the track that just started must be skipped
+///
+/// The blocklist is enforced even when the skip rules are turned off.
+fn check(
+    state: &AppAuthState,
+    snapshot: &PlaybackSnapshot,
+) -> Result<Option<SkipReason>, AppError> {
+    let Some(track) = snapshot.track.as_ref() else {
+        return Ok(None);
+    };
+
+    let blocklist = blocklist::load_blocklist(state)?;
+    if let Some(blocked) = blocklist.blocking_track(track) {
+        return Ok(Some(SkipReason {
+            rule_id: BLOCKLIST_RULE_ID.to_string(),
+            description: format!("{} is in the blocklist", blocked.name),
+        }));
+    }
+
+    let settings = load_settings(state)?;
+    if !settings.enabled || settings.rules.iter().all(|r| !r.enabled) {
+        return Ok(None);
+    }
+
+    // Repeating one track is deliberate, whatever the history says
+    let last_played = if snapshot.repeat == RepeatState::Track {
+        None
+    } else {
+        history::load_history(state)?
+            .stats()
+            .get(&track.uri)
+            .map(|s| s.last_played)
+    };
+
+    Ok(settings.check(&SkipInput {
+        track,
+        context_uri: snapshot.context_uri.as_deref(),
+        last_played,
+        now: Utc::now(),
+    }))
+}
+
+/// Skip the current track if a rule says so; returns whether it was skipped
+async fn enforce(
+    app: &AppHandle,
+    state: &AppAuthState,
+    snapshot: &PlaybackSnapshot,
+) -> Result<bool, AppError> {
+    let Some(reason) = check(state, snapshot)? else {
+        return Ok(false);
+    };
+    let Some(track) = snapshot.track.as_ref() else {
+        return Ok(false);
+    };
+
+    SpotifyApi::new(state)
+        .skip_next(snapshot.device.id.as_deref())
+        .await?;
+    app.state::<PlaybackHub>().refresh();
+
+    let entry = SkipLogEntry {
+        at: Utc::now(),
+        track_uri: track.uri.clone(),
+        track_name: track.name.clone(),
+        context_uri: snapshot.context_uri.clone(),
+        reason,
+    };
+    log::info!("Skipped {}: {}", entry.track_name, entry.reason.description);
+    let _ = app.emit("autoskip:skipped", &entry);
+    append_log(state, entry)?;
+    Ok(true)
+}
+
+/// Start the background job applying skip rules whenever the track changes
+pub fn spawn_autoskip_job(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut updates = app.state::<PlaybackHub>().subscribe();
+        let mut consecutive = 0;
+        loop {
+            let update = match updates.recv().await {
+                Ok(update) => update,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+            let started = update
+                .events
+                .iter()
+                .any(|e| matches!(e, PlaybackEvent::TrackChanged { track: Some(_), .. }));
+            let Some(snapshot) = update.snapshot.filter(|s| started && s.is_playing) else {
+                continue;
+            };
+
+            let state = app.state::<AppAuthState>();
+            if auth::current_user(&state).is_none() {
+                continue;
+            }
+            if consecutive >= MAX_CONSECUTIVE_SKIPS {
+                if check(&state, &snapshot).ok().flatten().is_none() {
+                    consecutive = 0;
+                }
+                continue;
+            }
+
+            match enforce(&app, &state, &snapshot).await {
+                Ok(true) => {
+                    consecutive += 1;
+                    if consecutive == MAX_CONSECUTIVE_SKIPS {
+                        log::warn!("Too many automatic skips in a row, pausing enforcement");
+                        let _ = app.emit("autoskip:suspended", consecutive);
+                    }
+                }
+                Ok(false) => consecutive = 0,
+                Err(e) => log::warn!("Failed to apply skip rules: {}", e),
+            }
+        }
+    });
+}
+
+/// Get the skip rules and per-context overrides
+#[tauri::command]
+pub fn get_skip_settings(state: State<AppAuthState>) -> Result<SkipSettings, AppError> {
+    load_settings(&state)
+}
+
+/// Turn automatic skipping on or off
+#[tauri::command]
+pub fn set_skip_enabled(
+    enabled: bool,
+    state: State<AppAuthState>,
+) -> Result<SkipSettings, AppError> {
+    update(&state, |settings| {
+        settings.enabled = enabled;
+        Ok(settings.clone())
+    })
+}
+
+/// Add a skip rule
+#[tauri::command]
+pub fn add_skip_rule(
+    condition: SkipCondition,
+    state: State<AppAuthState>,
+) -> Result<SkipRule, AppError> {
+    update(&state, |settings| {
+        if settings.rules.iter().any(|r| r.condition == condition) {
+            return Err(AppError::Conflict("This rule already exists".into()));
+        }
+        let rule = SkipRule {
+            id: store::new_id(),
+            enabled: true,
+            condition,
+        };
+        settings.rules.push(rule.clone());
+        Ok(rule)
+    })
+}
+
+/// Remove a skip rule
+#[tauri::command]
+pub fn remove_skip_rule(id: String, state: State<AppAuthState>) -> Result<(), AppError> {
+    update(&state, |settings| {
+        let before = settings.rules.len();
+        settings.rules.retain(|r| r.id != id);
+        if settings.rules.len() == before {
+            return Err(AppError::NotFound(format!("Skip rule {}", id)));
+        }
+        for overrides in settings.overrides.values_mut() {
+            overrides.disabled_rules.retain(|r| *r != id);
+        }
+        Ok(())
+    })
+}
+
+/// Enable or disable a skip rule
+#[tauri::command]
+pub fn set_skip_rule_enabled(
+    id: String,
+    enabled: bool,
+    state: State<AppAuthState>,
+) -> Result<(), AppError> {
+    update(&state, |settings| {
+        let rule = settings
+            .rules
+            .iter_mut()
+            .find(|r| r.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("Skip rule {}", id)))?;
+        rule.enabled = enabled;
+        Ok(())
+    })
+}
+
+/// Relax rules for a context, or remove its override with `None`
+#[tauri::command]
+pub fn set_skip_context_override(
+    context_uri: String,
+    context_override: Option<ContextOverride>,
+    state: State<AppAuthState>,
+) -> Result<(), AppError> {
+    update(&state, |settings| {
+        match context_override {
+            Some(o) => settings.overrides.insert(context_uri, o),
+            None => settings.overrides.remove(&context_uri),
+        };
+        Ok(())
+    })
+}
+
+/// Get the most recent automatic skips, newest first
+#[tauri::command]
+pub fn get_skip_log(
+    limit: Option<usize>,
+    state: State<AppAuthState>,
+) -> Result<Vec<SkipLogEntry>, AppError> {
+    let log: Vec<SkipLogEntry> =
+        store::load_json(&store::account_file(&state, LOG_FILE)?)?.unwrap_or_default();
+    Ok(log
+        .into_iter()
+        .rev()
+        .take(limit.unwrap_or(MAX_LOG_ENTRIES))
+        .collect())
+}
+
+/// Clear the skip log
+#[tauri::command]
+pub fn clear_skip_log(state: State<AppAuthState>) -> Result<(), AppError> {
+    store::save_json(
+        &store::account_file(&state, LOG_FILE)?,
+        &Vec::<SkipLogEntry>::new(),
+    )
+}
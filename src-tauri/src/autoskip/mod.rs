@@ -0,0 +1,4 @@
+pub mod commands;
+pub mod rules;
+
+pub use commands::*;
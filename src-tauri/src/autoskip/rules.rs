@@ -0,0 +1,222 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::api::Track;
+
+/// What makes a track skipped
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SkipCondition {
+    Explicit,
+    Artist { uri: String, name: String },
+    Track { uri: String, name: String },
+    ShorterThan { seconds: u64 },
+    PlayedWithinHours { hours: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkipRule {
+    pub id: String,
+    pub enabled: bool,
+    pub condition: SkipCondition,
+}
+
+/// Rules relaxed for one album, playlist or artist context
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContextOverride {
+    #[serde(default)]
+    pub disable_all: bool,
+    #[serde(default)]
+    pub disabled_rules: Vec<String>,
+}
+
+/// Auto-skip configuration of an account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkipSettings {
+    pub enabled: bool,
+    pub rules: Vec<SkipRule>,
+    #[serde(default)]
+    pub overrides: HashMap<String, ContextOverride>,
+}
+
+impl Default for SkipSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rules: Vec::new(),
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+/// Why a track was skipped
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkipReason {
+    pub rule_id: String,
+    pub description: String,
+}
+
+/// What a skip check needs to know about the track being played
+pub struct SkipInput<'a> {
+    pub track: &'a Track,
+    pub context_uri: Option<&'a str>,
+    pub last_played: Option<DateTime<Utc>>,
+    pub now: DateTime<Utc>,
+}
+
+impl SkipCondition {
+    /// Describe why the track matches, `None` when it does not
+    fn check(&self, input: &SkipInput) -> Option<String> {
+        let track = input.track;
+        match self {
+            SkipCondition::Explicit => track.explicit.then(|| "Explicit".to_string()),
+            SkipCondition::Artist { uri, name } => track
+                .artists
+                .iter()
+                .any(|a| a.uri == *uri)
+                .then(|| format!("Artist {} is blocked", name)),
+            SkipCondition::Track { uri, name } => {
+                (track.uri == *uri).then(|| format!("Track {} is blocked", name))
+            }
+            SkipCondition::ShorterThan { seconds } => (track.duration_ms > 0
+                && track.duration_ms < seconds * 1000)
+                .then(|| format!("Shorter than {}s", seconds)),
+            SkipCondition::PlayedWithinHours { hours } => input
+                .last_played
+                .filter(|at| input.now - *at < Duration::hours(*hours as i64))
+                .map(|_| format!("Played in the last {} hours", hours)),
+        }
+    }
+}
+
+impl SkipSettings {
+    /// First enabled rule the track matches, honouring the context override
+    pub fn check(&self, input: &SkipInput) -> Option<SkipReason> {
+        if !self.enabled {
+            return None;
+        }
+        let overrides = input.context_uri.and_then(|uri| self.overrides.get(uri));
+        if overrides.map(|o| o.disable_all).unwrap_or(false) {
+            return None;
+        }
+
+        self.rules
+            .iter()
+            .filter(|rule| rule.enabled)
+            .filter(|rule| {
+                !overrides
+                    .map(|o| o.disabled_rules.contains(&rule.id))
+                    .unwrap_or(false)
+            })
+            .find_map(|rule| {
+                rule.condition.check(input).map(|description| SkipReason {
+                    rule_id: rule.id.clone(),
+                    description,
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(uri: &str, artist: &str, explicit: bool, duration_ms: u64) -> Track {
+        serde_json::from_value(serde_json::json!({
+            "id": null,
+            "uri": uri,
+            "name": uri,
+            "explicit": explicit,
+            "duration_ms": duration_ms,
+            "artists": [{"id": null, "name": artist, "uri": artist}],
+        }))
+        .unwrap()
+    }
+
+    fn rule(id: &str, condition: SkipCondition) -> SkipRule {
+        SkipRule {
+            id: id.into(),
+            enabled: true,
+            condition,
+        }
+    }
+
+    fn input<'a>(track: &'a Track, context_uri: Option<&'a str>) -> SkipInput<'a> {
+        SkipInput {
+            track,
+            context_uri,
+            last_played: None,
+            now: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let settings = SkipSettings {
+            rules: vec![
+                rule("short", SkipCondition::ShorterThan { seconds: 60 }),
+                rule(
+                    "artist",
+                    SkipCondition::Artist {
+                        uri: "spotify:artist:x".into(),
+                        name: "X".into(),
+                    },
+                ),
+                rule("explicit", SkipCondition::Explicit),
+            ],
+            ..Default::default()
+        };
+
+        let blocked = track("a", "spotify:artist:x", true, 200_000);
+        assert_eq!(
+            settings.check(&input(&blocked, None)).unwrap().rule_id,
+            "artist"
+        );
+
+        let interlude = track("b", "spotify:artist:y", false, 30_000);
+        assert_eq!(
+            settings.check(&input(&interlude, None)).unwrap().rule_id,
+            "short"
+        );
+
+        let fine = track("c", "spotify:artist:y", false, 200_000);
+        assert!(settings.check(&input(&fine, None)).is_none());
+    }
+
+    #[test]
+    fn test_recently_played_and_overrides() {
+        let mut settings = SkipSettings {
+            rules: vec![
+                rule("recent", SkipCondition::PlayedWithinHours { hours: 6 }),
+                rule("explicit", SkipCondition::Explicit),
+            ],
+            ..Default::default()
+        };
+        let song = track("a", "spotify:artist:y", true, 200_000);
+        let now = Utc::now();
+
+        let recent = SkipInput {
+            last_played: Some(now - Duration::hours(2)),
+            ..input(&song, Some("spotify:playlist:party"))
+        };
+        assert_eq!(settings.check(&recent).unwrap().rule_id, "recent");
+
+        settings.overrides.insert(
+            "spotify:playlist:party".into(),
+            ContextOverride {
+                disable_all: false,
+                disabled_rules: vec!["recent".into()],
+            },
+        );
+        assert_eq!(settings.check(&recent).unwrap().rule_id, "explicit");
+
+        settings
+            .overrides
+            .get_mut("spotify:playlist:party")
+            .unwrap()
+            .disable_all = true;
+        assert!(settings.check(&recent).is_none());
+        assert!(settings.check(&input(&song, None)).is_some());
+    }
+}
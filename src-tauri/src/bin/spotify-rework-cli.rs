@@ -0,0 +1,32 @@
+use std::process::ExitCode;
+
+use app_lib::ipc::{cli, client};
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let (request, json) = match cli::parse(std::env::args().skip(1)) {
+        Ok(cli::Invocation::Run { request, json }) => (request, json),
+        Ok(cli::Invocation::Help) => {
+            println!("{}", cli::USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match client::send(&request).await {
+        Ok(reply) => {
+            let text = cli::render(&request, &reply, json);
+            if !text.is_empty() {
+                println!("{}", text);
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, State};
+
+use super::model::Blocklist;
+use crate::api::{SpotifyApi, Track};
+use crate::auth::AppAuthState;
+use crate::error::AppError;
+use crate::store;
+
+const BLOCKLIST_FILE: &str = "blocklist.json";
+
+/// Tracks left after applying the blocklist
+#[derive(Debug, Clone, Serialize)]
+pub struct FilteredTracks {
+    pub tracks: Vec<Track>,
+    /// How many results were hidden because they are blocked
+    pub hidden: usize,
+}
+
+impl FilteredTracks {
+    fn new(tracks: Vec<Track>, blocklist: &Blocklist) -> Self {
+        let total = tracks.len();
+        let tracks: Vec<Track> = tracks
+            .into_iter()
+            .filter(|t| !blocklist.blocks(t))
+            .collect();
+        Self {
+            hidden: total - tracks.len(),
+            tracks,
+        }
+    }
+}
+
+/// Load the blocklist of the signed-in account
+pub fn load_blocklist(state: &AppAuthState) -> Result<Blocklist, AppError> {
+    Ok(store::load_json(&store::account_file(state, BLOCKLIST_FILE)?)?.unwrap_or_default())
+}
+
+/// Modify the blocklist and notify the windows when it changed
+fn change(
+    app: &AppHandle,
+    state: &AppAuthState,
+    f: impl FnOnce(&mut Blocklist) -> bool,
+) -> Result<Blocklist, AppError> {
+    let (blocklist, changed) = store::update_json(
+        &store::account_file(state, BLOCKLIST_FILE)?,
+        |blocklist: &mut Blocklist| {
+            let changed = f(blocklist);
+            Ok((blocklist.clone(), changed))
+        },
+    )?;
+    if changed {
+        let _ = app.emit("blocklist:changed", &blocklist);
+    }
+    Ok(blocklist)
+}
+
+fn validate(uri: &str, kind: &str) -> Result<(), AppError> {
+    if uri.starts_with(&format!("spotify:{}:", kind)) {
+        Ok(())
+    } else {
+        Err(AppError::InvalidInput(format!(
+            "Not a Spotify {} URI: {}",
+            kind, uri
+        )))
+    }
+}
+
+/// Search tracks, leaving out blocked artists and tracks
+pub async fn search(
+    state: &AppAuthState,
+    query: &str,
+    limit: Option<u32>,
+) -> Result<FilteredTracks, AppError> {
+    if query.trim().is_empty() {
+        return Err(AppError::InvalidInput("Search query is required".into()));
+    }
+    let tracks = SpotifyApi::new(state)
+        .search_tracks(query.trim(), limit.unwrap_or(20))
+        .await?;
+    Ok(FilteredTracks::new(tracks, &load_blocklist(state)?))
+}
+
+/// Get the blocked artists and tracks
+#[tauri::command]
+pub fn get_blocklist(state: State<AppAuthState>) -> Result<Blocklist, AppError> {
+    load_blocklist(&state)
+}
+
+/// Never play an artist again, including tracks they feature on
+#[tauri::command]
+pub fn block_artist(
+    app: AppHandle,
+    uri: String,
+    name: String,
+    state: State<AppAuthState>,
+) -> Result<Blocklist, AppError> {
+    validate(&uri, "artist")?;
+    change(&app, &state, |b| {
+        Blocklist::add(&mut b.artists, &uri, &name)
+    })
+}
+
+#[tauri::command]
+pub fn unblock_artist(
+    app: AppHandle,
+    uri: String,
+    state: State<AppAuthState>,
+) -> Result<Blocklist, AppError> {
+    change(&app, &state, |b| Blocklist::remove(&mut b.artists, &uri))
+}
+
+/// Never play a track again
+#[tauri::command]
+pub fn block_track(
+    app: AppHandle,
+    uri: String,
+    name: String,
+    state: State<AppAuthState>,
+) -> Result<Blocklist, AppError> {
+    validate(&uri, "track")?;
+    change(&app, &state, |b| Blocklist::add(&mut b.tracks, &uri, &name))
+}
+
+#[tauri::command]
+pub fn unblock_track(
+    app: AppHandle,
+    uri: String,
+    state: State<AppAuthState>,
+) -> Result<Blocklist, AppError> {
+    change(&app, &state, |b| Blocklist::remove(&mut b.tracks, &uri))
+}
+
+/// Search tracks, leaving out blocked artists and tracks
+#[tauri::command]
+pub async fn search_tracks(
+    query: String,
+    limit: Option<u32>,
+    state: State<'_, AppAuthState>,
+) -> Result<FilteredTracks, AppError> {
+    search(&state, &query, limit).await
+}
+
+/// Get recommendations, leaving out blocked artists and tracks
+#[tauri::command]
+pub async fn get_recommendations(
+    seed_tracks: Option<Vec<String>>,
+    seed_artists: Option<Vec<String>>,
+    seed_genres: Option<Vec<String>>,
+    limit: Option<u32>,
+    state: State<'_, AppAuthState>,
+) -> Result<FilteredTracks, AppError> {
+    let (tracks, artists, genres) = (
+        seed_tracks.unwrap_or_default(),
+        seed_artists.unwrap_or_default(),
+        seed_genres.unwrap_or_default(),
+    );
+    let seeds = tracks.len() + artists.len() + genres.len();
+    if seeds == 0 || seeds > 5 {
+        return Err(AppError::InvalidInput(
+            "Between 1 and 5 seeds are required".into(),
+        ));
+    }
+
+    let blocklist = load_blocklist(&state)?;
+    // Blocked seeds would only bring back what the user asked not to hear
+    let tracks: Vec<String> = tracks
+        .into_iter()
+        .filter(|id| {
+            !blocklist
+                .tracks
+                .iter()
+                .any(|b| b.uri.ends_with(&format!(":{}", id)))
+        })
+        .collect();
+    let artists: Vec<String> = artists
+        .into_iter()
+        .filter(|id| {
+            !blocklist
+                .artists
+                .iter()
+                .any(|b| b.uri.ends_with(&format!(":{}", id)))
+        })
+        .collect();
+    if tracks.is_empty() && artists.is_empty() && genres.is_empty() {
+        return Ok(FilteredTracks {
+            tracks: Vec::new(),
+            hidden: 0,
+        });
+    }
+
+    let recommended = SpotifyApi::new(&state)
+        .recommendations(&tracks, &artists, &genres, limit.unwrap_or(20))
+        .await?;
+    Ok(FilteredTracks::new(recommended, &blocklist))
+}
@@ -0,0 +1,135 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::api::Track;
+use crate::library::LibraryTrack;
+
+/// An artist or track the user never wants to hear
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockedItem {
+    pub uri: String,
+    pub name: String,
+    pub added_at: DateTime<Utc>,
+}
+
+/// The "don't play this" list of an account
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Blocklist {
+    pub artists: Vec<BlockedItem>,
+    pub tracks: Vec<BlockedItem>,
+}
+
+impl Blocklist {
+    /// The blocked entry a track, known under any of `track_uris`, falls under
+    pub fn blocking<'a, 'b>(
+        &'a self,
+        track_uris: &[&str],
+        mut artist_uris: impl Iterator<Item = &'b str>,
+    ) -> Option<&'a BlockedItem> {
+        self.tracks
+            .iter()
+            .find(|t| track_uris.contains(&t.uri.as_str()))
+            .or_else(|| artist_uris.find_map(|uri| self.artists.iter().find(|a| a.uri == uri)))
+    }
+
+    /// The blocked entry a track falls under, relinked or not
+    pub fn blocking_track(&self, track: &Track) -> Option<&BlockedItem> {
+        self.blocking(
+            &[&track.uri, track.original_uri()],
+            track.artists.iter().map(|a| a.uri.as_str()),
+        )
+    }
+
+    pub fn blocks(&self, track: &Track) -> bool {
+        self.blocking_track(track).is_some()
+    }
+
+    pub fn blocks_library_track(&self, track: &LibraryTrack) -> bool {
+        self.blocking(&[&track.uri], track.artists.iter().map(|a| a.uri.as_str()))
+            .is_some()
+    }
+
+    /// Add an entry; returns false when it was already blocked
+    pub fn add(list: &mut Vec<BlockedItem>, uri: &str, name: &str) -> bool {
+        if list.iter().any(|i| i.uri == uri) {
+            return false;
+        }
+        list.push(BlockedItem {
+            uri: uri.to_string(),
+            name: name.to_string(),
+            added_at: Utc::now(),
+        });
+        true
+    }
+
+    /// Remove an entry; returns false when it was not blocked
+    pub fn remove(list: &mut Vec<BlockedItem>, uri: &str) -> bool {
+        let before = list.len();
+        list.retain(|i| i.uri != uri);
+        list.len() != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::LinkedTrack;
+
+    fn track(uri: &str, artists: &[&str]) -> Track {
+        let artists: Vec<_> = artists
+            .iter()
+            .map(|a| serde_json::json!({"id": null, "name": a, "uri": a}))
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "id": null, "uri": uri, "name": uri, "artists": artists
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_blocks_tracks_and_featured_artists() {
+        let mut blocklist = Blocklist::default();
+        assert!(Blocklist::add(
+            &mut blocklist.artists,
+            "spotify:artist:x",
+            "X"
+        ));
+        assert!(!Blocklist::add(
+            &mut blocklist.artists,
+            "spotify:artist:x",
+            "X"
+        ));
+        Blocklist::add(&mut blocklist.tracks, "spotify:track:b", "B");
+
+        assert!(blocklist.blocks(&track(
+            "spotify:track:a",
+            &["spotify:artist:y", "spotify:artist:x"]
+        )));
+        assert!(blocklist.blocks(&track("spotify:track:b", &["spotify:artist:y"])));
+        assert!(!blocklist.blocks(&track("spotify:track:c", &["spotify:artist:y"])));
+
+        assert!(Blocklist::remove(
+            &mut blocklist.artists,
+            "spotify:artist:x"
+        ));
+        assert!(!blocklist.blocks(&track("spotify:track:a", &["spotify:artist:x"])));
+    }
+
+    #[test]
+    fn test_blocks_relinked_tracks() {
+        let mut blocklist = Blocklist::default();
+        Blocklist::add(&mut blocklist.tracks, "spotify:track:a", "A");
+
+        let mut relinked = track("spotify:track:x", &["spotify:artist:y"]);
+        assert!(!blocklist.blocks(&relinked));
+        relinked.linked_from = Some(LinkedTrack {
+            id: Some("a".into()),
+            uri: "spotify:track:a".into(),
+        });
+        assert!(blocklist.blocks(&relinked));
+
+        Blocklist::remove(&mut blocklist.tracks, "spotify:track:a");
+        Blocklist::add(&mut blocklist.tracks, "spotify:track:x", "X");
+        assert!(blocklist.blocks(&relinked));
+    }
+}
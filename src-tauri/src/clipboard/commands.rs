@@ -0,0 +1,167 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use super::model::{self, ClipboardAction, ClipboardLink, ClipboardSettings};
+use crate::api::SpotifyApi;
+use crate::auth::{self, AppAuthState};
+use crate::error::AppError;
+use crate::links::{self, parse::LinkKind, SpotifyLink};
+use crate::playback::{self, PlaybackHub, PlayerCommand};
+use crate::queue;
+use crate::store;
+
+const CLIPBOARD_FILE: &str = "clipboard.json";
+
+/// How often the clipboard is read while watching
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Whether the watcher is on, mirrored from the settings file so polling
+/// does not read it every second
+#[derive(Default)]
+pub struct ClipboardHub {
+    enabled: AtomicBool,
+}
+
+fn load() -> Result<ClipboardSettings, AppError> {
+    Ok(store::load_json(&store::app_file(CLIPBOARD_FILE)?)?.unwrap_or_default())
+}
+
+fn save(settings: &ClipboardSettings) -> Result<(), AppError> {
+    store::save_json(&store::app_file(CLIPBOARD_FILE)?, settings)
+}
+
+/// Look up what a link points to
+async fn describe(state: &AppAuthState, link: SpotifyLink) -> Result<ClipboardLink, AppError> {
+    let api = SpotifyApi::new(state);
+    let not_found = || AppError::NotFound(link.uri());
+    Ok(match link.kind {
+        LinkKind::Track => {
+            let track = api
+                .tracks(std::slice::from_ref(&link.id))
+                .await?
+                .into_iter()
+                .next()
+                .ok_or_else(not_found)?;
+            ClipboardLink::track(link, &track)
+        }
+        LinkKind::Album => {
+            let album = api.album(&link.id).await?;
+            ClipboardLink::album(link, &album)
+        }
+        LinkKind::Artist => {
+            let artist = api
+                .artists(std::slice::from_ref(&link.id))
+                .await?
+                .into_iter()
+                .next()
+                .ok_or_else(not_found)?;
+            ClipboardLink::artist(link, &artist)
+        }
+        LinkKind::Playlist => {
+            let playlist = api.playlist(&link.id).await?;
+            ClipboardLink::playlist(link, &playlist)
+        }
+        LinkKind::Show | LinkKind::Episode | LinkKind::User => {
+            return Err(AppError::InvalidInput(format!(
+                "Unsupported link: {}",
+                link.uri()
+            )))
+        }
+    })
+}
+
+/// Start the background job that announces copied Spotify links
+///
+/// Only text copied while the watcher is on is considered; whatever was on
+/// the clipboard before is left alone.
+pub fn spawn_clipboard_watcher(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let settings = load().unwrap_or_else(|e| {
+            log::warn!("Failed to load clipboard settings: {}", e);
+            ClipboardSettings::default()
+        });
+        let hub = app.state::<ClipboardHub>();
+        hub.enabled.store(settings.enabled, Ordering::SeqCst);
+
+        let mut last: Option<String> = None;
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            if !hub.enabled.load(Ordering::SeqCst) {
+                last = None;
+                continue;
+            }
+            // Fails when the clipboard holds something other than text
+            let Ok(text) = app.clipboard().read_text() else {
+                continue;
+            };
+            match last.replace(text.clone()) {
+                Some(previous) if previous != text => {}
+                _ => continue,
+            }
+
+            let Some(link) = model::find_link(&text) else {
+                continue;
+            };
+            let state = app.state::<AppAuthState>();
+            if auth::current_user(&state).is_none() {
+                continue;
+            }
+            match describe(&state, link).await {
+                Ok(found) => {
+                    let _ = app.emit("clipboard:link-detected", &found);
+                }
+                Err(e) => log::warn!("Failed to look up a copied link: {}", e),
+            }
+        }
+    });
+}
+
+/// Get the clipboard watcher settings
+#[tauri::command]
+pub fn get_clipboard_settings() -> Result<ClipboardSettings, AppError> {
+    load()
+}
+
+/// Turn the clipboard watcher on or off
+#[tauri::command]
+pub fn set_clipboard_watcher(enabled: bool, hub: State<ClipboardHub>) -> Result<(), AppError> {
+    save(&ClipboardSettings { enabled })?;
+    hub.enabled.store(enabled, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Play, queue or open a link offered by the watcher
+#[tauri::command]
+pub async fn run_clipboard_action(
+    app: AppHandle,
+    uri: String,
+    action: ClipboardAction,
+    state: State<'_, AppAuthState>,
+    hub: State<'_, PlaybackHub>,
+) -> Result<(), AppError> {
+    let link = SpotifyLink::parse(&uri)
+        .ok_or_else(|| AppError::InvalidInput(format!("Not a Spotify link: {}", uri)))?;
+    if !ClipboardAction::for_kind(link.kind).contains(&action) {
+        return Err(AppError::InvalidInput(format!(
+            "Cannot {:?} {}",
+            action,
+            link.uri()
+        )));
+    }
+
+    match action {
+        ClipboardAction::Play => {
+            playback::execute(&state, &hub, PlayerCommand::PlayUri { uri: link.uri() }).await
+        }
+        ClipboardAction::Queue => {
+            queue::enqueue(&app, &state, &[link.uri()], None)?;
+            Ok(())
+        }
+        ClipboardAction::Open => {
+            links::open_link(&app, link);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+
+use crate::api::{Album, Artist, Playlist, SimplifiedArtist, Track};
+use crate::auth::SpotifyImage;
+use crate::links::parse::{LinkKind, SpotifyLink};
+
+/// Longest clipboard text searched for links; bigger copies are not chat messages
+const MAX_TEXT_LEN: usize = 4096;
+
+/// Clipboard watcher preferences
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClipboardSettings {
+    /// Off until the user opts in, since it reads everything they copy
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// What can be done with a copied link
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardAction {
+    Play,
+    /// Add to the local queue, tracks only
+    Queue,
+    /// Show the item's page
+    Open,
+}
+
+impl ClipboardAction {
+    pub fn for_kind(kind: LinkKind) -> Vec<Self> {
+        match kind {
+            LinkKind::Track => vec![Self::Play, Self::Queue, Self::Open],
+            LinkKind::Album | LinkKind::Artist | LinkKind::Playlist => {
+                vec![Self::Play, Self::Open]
+            }
+            LinkKind::Show | LinkKind::Episode | LinkKind::User => Vec::new(),
+        }
+    }
+}
+
+/// First supported Spotify link in copied text
+///
+/// Links pasted in chat come wrapped in text and punctuation, so every word
+/// is tried.
+pub fn find_link(text: &str) -> Option<SpotifyLink> {
+    if text.len() > MAX_TEXT_LEN {
+        return None;
+    }
+    text.split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c| matches!(c, '<' | '>' | '(' | ')' | '"' | '\'' | ',' | '.' | '!'))
+        })
+        .filter_map(SpotifyLink::parse)
+        .find(|link| !ClipboardAction::for_kind(link.kind).is_empty())
+}
+
+/// A copied link with what it points to, as offered to the user
+#[derive(Debug, Clone, Serialize)]
+pub struct ClipboardLink {
+    #[serde(flatten)]
+    pub link: SpotifyLink,
+    pub uri: String,
+    /// Page showing the item
+    pub route: Option<String>,
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub image_url: Option<String>,
+    pub actions: Vec<ClipboardAction>,
+}
+
+fn artist_names(artists: &[SimplifiedArtist]) -> Option<String> {
+    let names: Vec<&str> = artists.iter().map(|a| a.name.as_str()).collect();
+    (!names.is_empty()).then(|| names.join(", "))
+}
+
+/// Spotify lists images largest first
+fn image_url(images: &[SpotifyImage]) -> Option<String> {
+    images.first().map(|image| image.url.clone())
+}
+
+impl ClipboardLink {
+    fn new(
+        link: SpotifyLink,
+        route: Option<String>,
+        title: &str,
+        subtitle: Option<String>,
+        image_url: Option<String>,
+    ) -> Self {
+        Self {
+            uri: link.uri(),
+            actions: ClipboardAction::for_kind(link.kind),
+            link,
+            route,
+            title: title.to_string(),
+            subtitle,
+            image_url,
+        }
+    }
+
+    /// A track opens on its album's page
+    pub fn track(link: SpotifyLink, track: &Track) -> Self {
+        let route = track.album.id.as_ref().map(|id| format!("/album/{}", id));
+        Self::new(
+            link,
+            route,
+            &track.name,
+            artist_names(&track.artists),
+            image_url(&track.album.images),
+        )
+    }
+
+    pub fn album(link: SpotifyLink, album: &Album) -> Self {
+        let route = link.route();
+        Self::new(
+            link,
+            route,
+            &album.name,
+            artist_names(&album.artists),
+            image_url(&album.images),
+        )
+    }
+
+    pub fn artist(link: SpotifyLink, artist: &Artist) -> Self {
+        let route = link.route();
+        Self::new(link, route, &artist.name, None, image_url(&artist.images))
+    }
+
+    pub fn playlist(link: SpotifyLink, playlist: &Playlist) -> Self {
+        let route = link.route();
+        let owner = playlist.owner.display_name.clone();
+        let images = playlist.images.as_deref().unwrap_or_default();
+        Self::new(link, route, &playlist.name, owner, image_url(images))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const ID: &str = "4uLU6hMCjMI75M1A2tKUQC";
+
+    #[test]
+    fn test_finds_links_in_chat_messages() {
+        let link = find_link(&format!(
+            "have you heard this? (https://open.spotify.com/track/{}?si=1).",
+            ID
+        ))
+        .unwrap();
+        assert_eq!(link.kind, LinkKind::Track);
+        assert_eq!(link.id, ID);
+
+        assert_eq!(
+            find_link(&format!("spotify:album:{}", ID)).map(|l| l.kind),
+            Some(LinkKind::Album)
+        );
+        // Kinds without actions are skipped in favour of later links
+        assert_eq!(
+            find_link(&format!(
+                "spotify:episode:{} and spotify:playlist:{}",
+                ID, ID
+            ))
+            .map(|l| l.kind),
+            Some(LinkKind::Playlist)
+        );
+        assert_eq!(find_link("no links here"), None);
+        assert_eq!(
+            find_link(&format!("{} spotify:track:{}", "x".repeat(5000), ID)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_describes_tracks() {
+        let track: Track = serde_json::from_value(json!({
+            "id": ID, "uri": format!("spotify:track:{}", ID), "name": "Song",
+            "artists": [{ "name": "A" }, { "name": "B" }],
+            "album": {
+                "id": "album1", "name": "Record",
+                "images": [{ "url": "big", "width": 640, "height": 640 }]
+            }
+        }))
+        .unwrap();
+        let link = SpotifyLink::parse(&format!("spotify:track:{}", ID)).unwrap();
+
+        let found = ClipboardLink::track(link, &track);
+        assert_eq!(found.title, "Song");
+        assert_eq!(found.subtitle.as_deref(), Some("A, B"));
+        assert_eq!(found.route.as_deref(), Some("/album/album1"));
+        assert_eq!(found.image_url.as_deref(), Some("big"));
+        assert_eq!(
+            found.actions,
+            [
+                ClipboardAction::Play,
+                ClipboardAction::Queue,
+                ClipboardAction::Open
+            ]
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use chrono::Utc;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::preferences::{self, DevicePreferences, DeviceRef};
+use crate::api::{Device, SpotifyApi};
+use crate::auth::{self, AppAuthState};
+use crate::error::AppError;
+use crate::playback::PlaybackHub;
+use crate::store;
+
+const DEVICES_FILE: &str = "devices.json";
+
+/// How often the device list is checked for devices coming and going
+const WATCH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Last device listing, used to detect devices appearing and disappearing
+#[derive(Default)]
+pub struct DeviceHub {
+    known: Mutex<Option<Vec<Device>>>,
+}
+
+impl DeviceHub {
+    /// Devices seen by the last listing
+    pub fn known(&self) -> Vec<Device> {
+        self.known.lock().unwrap().clone().unwrap_or_default()
+    }
+}
+
+fn load(state: &AppAuthState) -> Result<DevicePreferences, AppError> {
+    Ok(store::load_json(&store::account_file(state, DEVICES_FILE)?)?.unwrap_or_default())
+}
+
+fn update<T>(
+    state: &AppAuthState,
+    f: impl FnOnce(&mut DevicePreferences) -> T,
+) -> Result<T, AppError> {
+    store::update_json(&store::account_file(state, DEVICES_FILE)?, |prefs| {
+        Ok(f(prefs))
+    })
+}
+
+/// Remember the device playback last happened on
+pub fn remember_device(state: &AppAuthState, device: &Device) -> Result<(), AppError> {
+    update(state, |prefs| {
+        prefs.last_device = Some(DeviceRef::from(device));
+        prefs.last_used_at = Some(Utc::now());
+    })
+}
+
+/// Store a fresh device listing and emit the devices that came and went
+///
+/// The first listing, made by the device job at startup, is emitted as a whole.
+fn publish(app: &AppHandle, hub: &DeviceHub, devices: &[Device]) {
+    let previous = hub.known.lock().unwrap().replace(devices.to_vec());
+    let Some(previous) = previous else {
+        // Nothing came or went yet, but listeners such as the tray need the list
+        let _ = app.emit("devices:changed", devices);
+        return;
+    };
+
+    let changes = preferences::diff_devices(&previous, devices);
+    if changes.is_empty() {
+        return;
+    }
+    for device in &changes.added {
+        let _ = app.emit("devices:added", device);
+    }
+    for device in &changes.removed {
+        let _ = app.emit("devices:removed", device);
+    }
+    let _ = app.emit("devices:changed", devices);
+}
+
+async fn list(
+    app: &AppHandle,
+    state: &AppAuthState,
+    hub: &DeviceHub,
+) -> Result<Vec<Device>, AppError> {
+    let devices = SpotifyApi::new(state).devices().await?;
+    publish(app, hub, &devices);
+    Ok(devices)
+}
+
+async fn transfer(
+    app: &AppHandle,
+    state: &AppAuthState,
+    device: &Device,
+    play: bool,
+) -> Result<(), AppError> {
+    let id = device.id.as_deref().ok_or_else(|| {
+        AppError::InvalidInput(format!("Device {} cannot be controlled", device.name))
+    })?;
+    SpotifyApi::new(state).transfer_playback(id, play).await?;
+    remember_device(state, device)?;
+    app.state::<PlaybackHub>().refresh();
+    Ok(())
+}
+
+/// Move playback to one of the listed devices by id
+pub async fn transfer_to_device(
+    app: &AppHandle,
+    state: &AppAuthState,
+    hub: &DeviceHub,
+    device_id: &str,
+    play: bool,
+) -> Result<(), AppError> {
+    let devices = list(app, state, hub).await?;
+    let device = devices
+        .iter()
+        .find(|d| d.id.as_deref() == Some(device_id))
+        .ok_or_else(|| AppError::NotFound(format!("Device {}", device_id)))?;
+    transfer(app, state, device, play).await
+}
+
+/// Resume on the last used device when nothing is playing anywhere
+async fn reconnect(
+    app: &AppHandle,
+    state: &AppAuthState,
+    hub: &DeviceHub,
+) -> Result<Option<Device>, AppError> {
+    let prefs = load(state)?;
+    let Some(last) = prefs.last_device.filter(|_| prefs.auto_reconnect) else {
+        return Ok(None);
+    };
+    if SpotifyApi::new(state).playback_state().await?.is_some() {
+        return Ok(None);
+    }
+
+    let devices = list(app, state, hub).await?;
+    let Some(device) = last.resolve(&devices) else {
+        return Ok(None);
+    };
+    transfer(app, state, device, false).await?;
+    Ok(Some(device.clone()))
+}
+
+/// Start the background job that reconnects on launch and watches the device list
+pub fn spawn_device_jobs(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut reconnect_attempted = false;
+        loop {
+            let state = app.state::<AppAuthState>();
+            let hub = app.state::<DeviceHub>();
+
+            if auth::current_user(&state).is_some() {
+                if !reconnect_attempted {
+                    reconnect_attempted = true;
+                    match reconnect(&app, &state, &hub).await {
+                        Ok(Some(device)) => {
+                            log::info!("Reconnected to {}", device.name);
+                            let _ = app.emit("devices:reconnected", &device);
+                        }
+                        Ok(None) => {}
+                        Err(e) => log::warn!("Failed to reconnect to the last device: {}", e),
+                    }
+                }
+                if let Err(e) = list(&app, &state, &hub).await {
+                    log::warn!("Failed to list devices: {}", e);
+                }
+            }
+            tokio::time::sleep(WATCH_INTERVAL).await;
+        }
+    });
+}
+
+/// List the available Connect devices
+#[tauri::command]
+pub async fn list_devices(
+    app: AppHandle,
+    state: State<'_, AppAuthState>,
+    hub: State<'_, DeviceHub>,
+) -> Result<Vec<Device>, AppError> {
+    list(&app, &state, &hub).await
+}
+
+/// Move playback to a device, starting it when `play` is set
+#[tauri::command]
+pub async fn transfer_playback(
+    app: AppHandle,
+    device_id: String,
+    play: Option<bool>,
+    state: State<'_, AppAuthState>,
+    hub: State<'_, DeviceHub>,
+) -> Result<(), AppError> {
+    transfer_to_device(&app, &state, &hub, &device_id, play.unwrap_or(false)).await
+}
+
+/// Get the remembered devices and reconnect setting
+#[tauri::command]
+pub fn get_device_preferences(state: State<AppAuthState>) -> Result<DevicePreferences, AppError> {
+    load(&state)
+}
+
+/// Enable or disable reconnecting to the last device on launch
+#[tauri::command]
+pub fn set_device_auto_reconnect(
+    enabled: bool,
+    state: State<AppAuthState>,
+) -> Result<(), AppError> {
+    update(&state, |prefs| prefs.auto_reconnect = enabled)
+}
+
+/// Remember (or forget, with no device) the preferred device of a context
+#[tauri::command]
+pub async fn set_preferred_device(
+    app: AppHandle,
+    context_uri: String,
+    device_id: Option<String>,
+    state: State<'_, AppAuthState>,
+    hub: State<'_, DeviceHub>,
+) -> Result<(), AppError> {
+    let device = match device_id {
+        Some(id) => {
+            let devices = list(&app, &state, &hub).await?;
+            let device = devices
+                .iter()
+                .find(|d| d.id.as_deref() == Some(id.as_str()))
+                .ok_or_else(|| AppError::NotFound(format!("Device {}", id)))?;
+            Some(DeviceRef::from(device))
+        }
+        None => None,
+    };
+
+    update(&state, |prefs| match device {
+        Some(device) => {
+            prefs.contexts.insert(context_uri, device);
+        }
+        None => {
+            prefs.contexts.remove(&context_uri);
+        }
+    })
+}
+
+/// Transfer playback to the preferred device of a context, falling back to the
+/// last used device; returns the device used, if any was available
+#[tauri::command]
+pub async fn transfer_to_preferred_device(
+    app: AppHandle,
+    context_uri: String,
+    play: Option<bool>,
+    state: State<'_, AppAuthState>,
+    hub: State<'_, DeviceHub>,
+) -> Result<Option<Device>, AppError> {
+    let prefs = load(&state)?;
+    let devices = list(&app, &state, &hub).await?;
+
+    let device = prefs
+        .contexts
+        .get(&context_uri)
+        .and_then(|d| d.resolve(&devices))
+        .or_else(|| prefs.last_device.as_ref().and_then(|d| d.resolve(&devices)));
+
+    match device {
+        Some(device) => {
+            transfer(&app, &state, device, play.unwrap_or(false)).await?;
+            Ok(Some(device.clone()))
+        }
+        None => Ok(None),
+    }
+}
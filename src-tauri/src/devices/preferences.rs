@@ -0,0 +1,145 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::api::Device;
+
+/// A device remembered across sessions
+///
+/// Connect device ids are not guaranteed to survive restarts of the device, so
+/// the name and type are kept to find it again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceRef {
+    pub id: Option<String>,
+    pub name: String,
+    pub device_type: String,
+}
+
+impl DeviceRef {
+    /// Find this device among the available ones, by id first and then by name
+    pub fn resolve<'a>(&self, devices: &'a [Device]) -> Option<&'a Device> {
+        devices
+            .iter()
+            .find(|d| d.id.is_some() && d.id == self.id)
+            .or_else(|| {
+                devices
+                    .iter()
+                    .find(|d| d.name == self.name && d.device_type == self.device_type)
+            })
+    }
+}
+
+impl From<&Device> for DeviceRef {
+    fn from(device: &Device) -> Self {
+        Self {
+            id: device.id.clone(),
+            name: device.name.clone(),
+            device_type: device.device_type.clone(),
+        }
+    }
+}
+
+/// Device choices persisted per account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DevicePreferences {
+    /// Reconnect to `last_device` on launch when nothing is playing
+    #[serde(default = "default_auto_reconnect")]
+    pub auto_reconnect: bool,
+    pub last_device: Option<DeviceRef>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// Preferred device per context URI (playlist, album...)
+    #[serde(default)]
+    pub contexts: HashMap<String, DeviceRef>,
+}
+
+fn default_auto_reconnect() -> bool {
+    true
+}
+
+impl Default for DevicePreferences {
+    fn default() -> Self {
+        Self {
+            auto_reconnect: default_auto_reconnect(),
+            last_device: None,
+            last_used_at: None,
+            contexts: HashMap::new(),
+        }
+    }
+}
+
+/// Devices that appeared and disappeared between two listings
+#[derive(Debug, Default)]
+pub struct DeviceChanges {
+    pub added: Vec<Device>,
+    pub removed: Vec<Device>,
+}
+
+impl DeviceChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+fn device_key(device: &Device) -> (Option<&str>, &str) {
+    match device.id.as_deref() {
+        Some(id) => (Some(id), ""),
+        None => (None, device.name.as_str()),
+    }
+}
+
+/// Compare two device listings
+pub fn diff_devices(previous: &[Device], current: &[Device]) -> DeviceChanges {
+    let missing = |list: &[Device], other: &[Device]| -> Vec<Device> {
+        list.iter()
+            .filter(|d| !other.iter().any(|o| device_key(o) == device_key(d)))
+            .cloned()
+            .collect()
+    };
+
+    DeviceChanges {
+        added: missing(current, previous),
+        removed: missing(previous, current),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(id: Option<&str>, name: &str) -> Device {
+        Device {
+            id: id.map(str::to_string),
+            name: name.into(),
+            device_type: "Speaker".into(),
+            is_active: false,
+            is_restricted: false,
+            volume_percent: None,
+            supports_volume: true,
+        }
+    }
+
+    #[test]
+    fn test_resolve_falls_back_to_name() {
+        let remembered = DeviceRef::from(&device(Some("old"), "Kitchen"));
+        let devices = vec![device(Some("x"), "Desk"), device(Some("new"), "Kitchen")];
+
+        assert_eq!(
+            remembered.resolve(&devices).and_then(|d| d.id.as_deref()),
+            Some("new")
+        );
+        assert!(DeviceRef::from(&device(Some("y"), "Car"))
+            .resolve(&devices)
+            .is_none());
+    }
+
+    #[test]
+    fn test_diff_devices() {
+        let previous = vec![device(Some("a"), "Desk"), device(Some("b"), "Phone")];
+        let current = vec![device(Some("b"), "Phone"), device(None, "TV")];
+
+        let changes = diff_devices(&previous, &current);
+        assert_eq!(changes.added[0].name, "TV");
+        assert_eq!(changes.removed[0].name, "Desk");
+        assert!(diff_devices(&current, &current).is_empty());
+    }
+}
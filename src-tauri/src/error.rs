@@ -0,0 +1,43 @@
+use serde::Serialize;
+
+use crate::auth::AuthError;
+
+/// Error type shared by the backend subsystems built on top of auth
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    #[error(transparent)]
+    Auth(#[from] AuthError),
+
+    #[error("HTTP error: {0}")]
+    Http(String),
+
+    #[error("Spotify API error ({status}): {message}")]
+    Api { status: u16, message: String },
+
+    #[error("Rate limited by Spotify, retry after {0}s")]
+    RateLimited(u64),
+
+    #[error("Failed to parse response: {0}")]
+    Parse(String),
+
+    #[error("Storage error: {0}")]
+    Storage(String),
+
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+}
+
+impl Serialize for AppError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
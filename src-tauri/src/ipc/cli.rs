@@ -0,0 +1,311 @@
+use serde_json::Value;
+
+use super::protocol::{PlayerCommand, Request};
+use crate::links::parse::{LinkKind, SpotifyLink};
+
+pub const USAGE: &str = "\
+Usage: spotify-rework-cli <command> [--json]
+
+Control the running app from the terminal.
+
+Commands:
+  now-playing                     Show the current track
+  play | pause | toggle           Start or stop playback
+  next | previous                 Skip forward or back
+  volume <0-100 | +N | -N>        Set or change the volume
+  queue <uri>...                  Add Spotify URIs to the local queue
+  search <query>... [--limit N]   Search for tracks
+  export-playlist <id | uri>      Print a playlist and its tracks as JSON
+
+Options:
+  --json                          Print the raw JSON reply
+  -h, --help                      Show this help";
+
+/// What the command line asks for
+#[derive(Debug, Clone, PartialEq)]
+pub enum Invocation {
+    Help,
+    Run { request: Request, json: bool },
+}
+
+/// Playlist id from a bare id, a `spotify:playlist:` URI or an open.spotify.com link
+pub fn playlist_id(text: &str) -> Option<String> {
+    let text = text.trim();
+    match SpotifyLink::parse(text) {
+        Some(link) => (link.kind == LinkKind::Playlist).then_some(link.id),
+        None => {
+            let valid = !text.is_empty() && text.chars().all(|c| c.is_ascii_alphanumeric());
+            valid.then(|| text.to_string())
+        }
+    }
+}
+
+fn volume(value: &str) -> Result<PlayerCommand, String> {
+    let invalid = || format!("Invalid volume: {}", value);
+    if value.starts_with(['+', '-']) {
+        let delta = value.parse().map_err(|_| invalid())?;
+        return Ok(PlayerCommand::ChangeVolume { delta });
+    }
+    match value.parse() {
+        Ok(percent) if percent <= 100 => Ok(PlayerCommand::SetVolume { percent }),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parse the arguments that follow the program name
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Invocation, String> {
+    let mut json = false;
+    let mut limit = None;
+    let mut words = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Invocation::Help),
+            "--json" => json = true,
+            "--limit" => {
+                let value = args.next().ok_or("--limit needs a value")?;
+                let value = value
+                    .parse()
+                    .map_err(|_| format!("Invalid limit: {}", value))?;
+                limit = Some(value);
+            }
+            // Volume changes like -5 are operands, not options
+            flag if flag.starts_with("--") => return Err(format!("Unknown option: {}", flag)),
+            _ => words.push(arg),
+        }
+    }
+
+    let Some((command, operands)) = words.split_first() else {
+        return Ok(Invocation::Help);
+    };
+    let no_operands = |request: Request| {
+        if operands.is_empty() {
+            Ok(request)
+        } else {
+            Err(format!("{} takes no arguments", command))
+        }
+    };
+    let player = |command| no_operands(Request::Player { command });
+
+    let request = match command.as_str() {
+        "help" => return Ok(Invocation::Help),
+        "now-playing" => no_operands(Request::NowPlaying)?,
+        "play" => player(PlayerCommand::Play)?,
+        "pause" => player(PlayerCommand::Pause)?,
+        "toggle" => player(PlayerCommand::TogglePlay)?,
+        "next" => player(PlayerCommand::Next)?,
+        "previous" => player(PlayerCommand::Previous)?,
+        "volume" => match operands {
+            [value] => Request::Player {
+                command: volume(value)?,
+            },
+            _ => return Err("volume takes one value".into()),
+        },
+        "queue" if !operands.is_empty() => Request::Queue {
+            uris: operands.to_vec(),
+        },
+        "queue" => return Err("queue needs at least one URI".into()),
+        "search" if !operands.is_empty() => Request::Search {
+            query: operands.join(" "),
+            limit,
+        },
+        "search" => return Err("search needs a query".into()),
+        "export-playlist" => match operands {
+            [playlist] => Request::ExportPlaylist {
+                playlist_id: playlist_id(playlist)
+                    .ok_or_else(|| format!("Not a playlist: {}", playlist))?,
+            },
+            _ => return Err("export-playlist takes one playlist".into()),
+        },
+        other => return Err(format!("Unknown command: {}", other)),
+    };
+    Ok(Invocation::Run { request, json })
+}
+
+fn describe_track(track: &Value) -> String {
+    let artists: Vec<&str> = track["artists"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|a| a["name"].as_str())
+        .collect();
+    let name = track["name"].as_str().unwrap_or_default();
+    if artists.is_empty() {
+        name.to_string()
+    } else {
+        format!("{} - {}", name, artists.join(", "))
+    }
+}
+
+/// Text printed for a reply, empty when there is nothing to say
+pub fn render(request: &Request, reply: &Value, json: bool) -> String {
+    let pretty = || serde_json::to_string_pretty(reply).unwrap_or_default();
+    if json {
+        return pretty();
+    }
+
+    match request {
+        Request::NowPlaying => {
+            let track = &reply["track"];
+            if track.is_null() {
+                return "Nothing is playing".into();
+            }
+            let state = if reply["is_playing"].as_bool() == Some(true) {
+                "Playing"
+            } else {
+                "Paused"
+            };
+            let mut lines = vec![format!("{}: {}", state, describe_track(track))];
+            if let Some(album) = track["album"]["name"].as_str().filter(|a| !a.is_empty()) {
+                lines.push(format!("Album: {}", album));
+            }
+            if let Some(device) = reply["device"]["name"].as_str() {
+                lines.push(format!("Device: {}", device));
+            }
+            lines.join("\n")
+        }
+        Request::Player { .. } => String::new(),
+        Request::Queue { uris } => {
+            let queued = reply["items"].as_array().map_or(0, Vec::len);
+            format!(
+                "Added {} item(s), {} in the local queue",
+                uris.len(),
+                queued
+            )
+        }
+        Request::Search { .. } => {
+            let mut lines: Vec<String> = reply["tracks"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|t| {
+                    format!(
+                        "{}\t{}",
+                        t["uri"].as_str().unwrap_or_default(),
+                        describe_track(t)
+                    )
+                })
+                .collect();
+            match reply["hidden"].as_u64() {
+                Some(hidden) if hidden > 0 => {
+                    lines.push(format!("({} hidden by the blocklist)", hidden))
+                }
+                _ => {}
+            }
+            if lines.is_empty() {
+                "No results".into()
+            } else {
+                lines.join("\n")
+            }
+        }
+        Request::ExportPlaylist { .. } => pretty(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn run(args: &[&str]) -> Result<Invocation, String> {
+        parse(args.iter().map(|a| a.to_string()))
+    }
+
+    fn request(args: &[&str]) -> Request {
+        match run(args) {
+            Ok(Invocation::Run { request, .. }) => request,
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parses_commands_and_options() {
+        assert_eq!(run(&[]), Ok(Invocation::Help));
+        assert_eq!(run(&["next", "--help"]), Ok(Invocation::Help));
+        assert_eq!(
+            run(&["now-playing", "--json"]),
+            Ok(Invocation::Run {
+                request: Request::NowPlaying,
+                json: true
+            })
+        );
+        assert_eq!(
+            request(&["volume", "-5"]),
+            Request::Player {
+                command: PlayerCommand::ChangeVolume { delta: -5 }
+            }
+        );
+        assert_eq!(
+            request(&["volume", "40"]),
+            Request::Player {
+                command: PlayerCommand::SetVolume { percent: 40 }
+            }
+        );
+        assert_eq!(
+            request(&["search", "daft", "punk", "--limit", "5"]),
+            Request::Search {
+                query: "daft punk".into(),
+                limit: Some(5)
+            }
+        );
+        assert_eq!(
+            request(&["queue", "spotify:track:a", "spotify:track:b"]),
+            Request::Queue {
+                uris: vec!["spotify:track:a".into(), "spotify:track:b".into()]
+            }
+        );
+
+        assert!(run(&["volume", "150"]).is_err());
+        assert!(run(&["pause", "now"]).is_err());
+        assert!(run(&["queue"]).is_err());
+        assert!(run(&["search", "x", "--limit"]).is_err());
+        assert!(run(&["rewind"]).is_err());
+        assert!(run(&["next", "--force"]).is_err());
+    }
+
+    #[test]
+    fn test_playlist_ids_come_from_ids_uris_and_links() {
+        let id = "37i9dQZF1DXcBWIGoYBM5M";
+        assert_eq!(playlist_id(id).as_deref(), Some(id));
+        assert_eq!(
+            playlist_id(&format!("spotify:playlist:{}", id)).as_deref(),
+            Some(id)
+        );
+        assert_eq!(
+            playlist_id(&format!(
+                "https://open.spotify.com/intl-fr/playlist/{}?si=x",
+                id
+            ))
+            .as_deref(),
+            Some(id)
+        );
+        assert_eq!(
+            playlist_id(&format!("https://open.spotify.com/album/{}", id)),
+            None
+        );
+        assert_eq!(playlist_id("../me"), None);
+        assert_eq!(playlist_id(""), None);
+    }
+
+    #[test]
+    fn test_renders_now_playing() {
+        let reply = json!({
+            "is_playing": false,
+            "device": { "name": "Desk" },
+            "track": {
+                "name": "Song",
+                "artists": [{ "name": "A" }, { "name": "B" }],
+                "album": { "name": "Record" }
+            }
+        });
+        assert_eq!(
+            render(&Request::NowPlaying, &reply, false),
+            "Paused: Song - A, B\nAlbum: Record\nDevice: Desk"
+        );
+        assert_eq!(
+            render(&Request::NowPlaying, &Value::Null, false),
+            "Nothing is playing"
+        );
+        assert_eq!(render(&Request::NowPlaying, &Value::Null, true), "null");
+    }
+}
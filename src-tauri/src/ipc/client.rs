@@ -0,0 +1,30 @@
+use serde_json::Value;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use super::protocol::{Request, Response};
+use super::transport;
+
+/// Send a request to the running app and wait for its reply
+pub async fn send(request: &Request) -> Result<Value, String> {
+    let mut stream = transport::connect()
+        .await
+        .map_err(|e| format!("The app is not running ({})", e))?;
+
+    let mut line = serde_json::to_vec(request).map_err(|e| e.to_string())?;
+    line.push(b'\n');
+    stream
+        .write_all(&line)
+        .await
+        .map_err(|e| format!("Failed to send the request: {}", e))?;
+
+    let mut reply = Vec::new();
+    stream
+        .read_to_end(&mut reply)
+        .await
+        .map_err(|e| format!("Failed to read the reply: {}", e))?;
+    match serde_json::from_slice(&reply) {
+        Ok(Response::Ok(value)) => Ok(value),
+        Ok(Response::Error(message)) => Err(message),
+        Err(e) => Err(format!("Invalid reply: {}", e)),
+    }
+}
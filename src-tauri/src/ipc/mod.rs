@@ -0,0 +1,7 @@
+pub mod cli;
+pub mod client;
+pub mod protocol;
+mod server;
+mod transport;
+
+pub use server::spawn_ipc_server;
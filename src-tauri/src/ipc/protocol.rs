@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub use crate::playback::PlayerCommand;
+
+/// Longest request line the app reads, in bytes
+pub const MAX_REQUEST_LEN: u64 = 64 * 1024;
+
+/// One request of the command-line client
+///
+/// Each connection carries a single JSON line in each direction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "request", rename_all = "snake_case")]
+pub enum Request {
+    NowPlaying,
+    Player {
+        command: PlayerCommand,
+    },
+    /// Append URIs to the local queue
+    Queue {
+        uris: Vec<String>,
+    },
+    Search {
+        query: String,
+        limit: Option<u32>,
+    },
+    ExportPlaylist {
+        playlist_id: String,
+    },
+}
+
+/// Reply to a request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Response {
+    Ok(Value),
+    Error(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_requests_are_tagged_json() {
+        let request = Request::Player {
+            command: PlayerCommand::ChangeVolume { delta: -5 },
+        };
+        let line = serde_json::to_string(&request).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&line).unwrap(),
+            json!({ "request": "player", "command": { "type": "change_volume", "delta": -5 } })
+        );
+        assert_eq!(serde_json::from_str::<Request>(&line).unwrap(), request);
+
+        assert_eq!(
+            serde_json::from_str::<Request>(r#"{"request":"now_playing"}"#).unwrap(),
+            Request::NowPlaying
+        );
+        assert!(serde_json::from_str::<Request>(r#"{"request":"shutdown"}"#).is_err());
+    }
+
+    #[test]
+    fn test_responses_wrap_a_value_or_an_error() {
+        assert_eq!(
+            serde_json::to_value(Response::Ok(json!(null))).unwrap(),
+            json!({ "ok": null })
+        );
+        assert_eq!(
+            serde_json::to_value(Response::Error("Not signed in".into())).unwrap(),
+            json!({ "error": "Not signed in" })
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::io;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+
+use super::protocol::{Request, Response, MAX_REQUEST_LEN};
+use super::transport::Listener;
+use crate::api::SpotifyApi;
+use crate::auth::AppAuthState;
+use crate::blocklist;
+use crate::error::AppError;
+use crate::playback::{self, PlaybackHub};
+use crate::queue;
+
+fn to_value<T: Serialize>(value: T) -> Result<Value, AppError> {
+    serde_json::to_value(value).map_err(|e| AppError::Parse(e.to_string()))
+}
+
+async fn export_playlist(state: &AppAuthState, playlist_id: &str) -> Result<Value, AppError> {
+    if playlist_id.is_empty() || !playlist_id.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(AppError::InvalidInput(format!(
+            "Not a playlist id: {}",
+            playlist_id
+        )));
+    }
+    let api = SpotifyApi::new(state);
+    let (playlist, items) =
+        tokio::try_join!(api.playlist(playlist_id), api.playlist_items(playlist_id))?;
+    Ok(json!({
+        "exported_at": chrono::Utc::now(),
+        "playlist": playlist,
+        "items": items,
+    }))
+}
+
+async fn handle(app: &AppHandle, request: Request) -> Result<Value, AppError> {
+    let state = app.state::<AppAuthState>();
+    let hub = app.state::<PlaybackHub>();
+    match request {
+        Request::NowPlaying => to_value(hub.current()),
+        Request::Player { command } => {
+            playback::execute(&state, &hub, command).await?;
+            Ok(Value::Null)
+        }
+        Request::Queue { uris } => to_value(queue::enqueue(app, &state, &uris, None)?),
+        Request::Search { query, limit } => {
+            to_value(blocklist::search(&state, &query, limit).await?)
+        }
+        Request::ExportPlaylist { playlist_id } => export_playlist(&state, &playlist_id).await,
+    }
+}
+
+/// Answer the single request of a connection
+async fn serve(app: &AppHandle, stream: impl AsyncRead + AsyncWrite + Unpin) -> io::Result<()> {
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut line = String::new();
+    BufReader::new(reader.take(MAX_REQUEST_LEN))
+        .read_line(&mut line)
+        .await?;
+
+    let response = match serde_json::from_str::<Request>(&line) {
+        Ok(request) => match handle(app, request).await {
+            Ok(value) => Response::Ok(value),
+            Err(e) => Response::Error(e.to_string()),
+        },
+        Err(e) => Response::Error(format!("Invalid request: {}", e)),
+    };
+    let mut reply = serde_json::to_vec(&response)?;
+    reply.push(b'\n');
+    writer.write_all(&reply).await?;
+    writer.shutdown().await
+}
+
+/// Listen for the command-line client for as long as the app runs
+pub fn spawn_ipc_server(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut listener = match Listener::bind().await {
+            Ok(listener) => listener,
+            Err(e) => {
+                log::warn!("Command-line interface unavailable: {}", e);
+                return;
+            }
+        };
+
+        loop {
+            let stream = match listener.accept().await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    log::warn!("Failed to accept a command-line client: {}", e);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            };
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = serve(&app, stream).await {
+                    log::debug!("Command-line client disconnected: {}", e);
+                }
+            });
+        }
+    });
+}
@@ -0,0 +1,111 @@
+pub use platform::{connect, Listener};
+
+/// Unix domain socket in the data directory, readable by the owner only
+#[cfg(unix)]
+mod platform {
+    use std::fs::{self, Permissions};
+    use std::io;
+    use std::os::unix::fs::PermissionsExt;
+    use tokio::net::{UnixListener, UnixStream};
+
+    use crate::error::AppError;
+    use crate::store;
+
+    const SOCKET_FILE: &str = "cli.sock";
+
+    pub struct Listener(UnixListener);
+
+    impl Listener {
+        pub async fn bind() -> Result<Self, AppError> {
+            let path = store::app_file(SOCKET_FILE)?;
+            if UnixStream::connect(&path).await.is_ok() {
+                return Err(AppError::Conflict(format!(
+                    "Another instance is listening on {:?}",
+                    path
+                )));
+            }
+            // Left behind by an instance that did not exit cleanly
+            let _ = fs::remove_file(&path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| {
+                    AppError::Storage(format!("Failed to create {:?}: {}", parent, e))
+                })?;
+            }
+
+            let listener = UnixListener::bind(&path)
+                .map_err(|e| AppError::Storage(format!("Failed to bind {:?}: {}", path, e)))?;
+            fs::set_permissions(&path, Permissions::from_mode(0o600))
+                .map_err(|e| AppError::Storage(format!("Failed to restrict {:?}: {}", path, e)))?;
+            Ok(Self(listener))
+        }
+
+        pub async fn accept(&mut self) -> io::Result<UnixStream> {
+            self.0.accept().await.map(|(stream, _)| stream)
+        }
+    }
+
+    pub async fn connect() -> io::Result<UnixStream> {
+        let path = store::app_file(SOCKET_FILE).map_err(io::Error::other)?;
+        UnixStream::connect(path).await
+    }
+}
+
+/// Named pipe of the current user, closed to remote clients
+#[cfg(windows)]
+mod platform {
+    use std::io;
+    use std::time::Duration;
+    use tokio::net::windows::named_pipe::{
+        ClientOptions, NamedPipeClient, NamedPipeServer, ServerOptions,
+    };
+
+    use crate::error::AppError;
+    use crate::store;
+
+    const ERROR_PIPE_BUSY: i32 = 231;
+
+    fn pipe_name() -> String {
+        let user = std::env::var("USERNAME").unwrap_or_default();
+        format!(
+            r"\\.\pipe\spotify-rework-cli-{}",
+            store::sanitize_file_name(&user)
+        )
+    }
+
+    pub struct Listener {
+        name: String,
+        /// Instance waiting for the next client
+        next: NamedPipeServer,
+    }
+
+    impl Listener {
+        pub async fn bind() -> Result<Self, AppError> {
+            let name = pipe_name();
+            let next = ServerOptions::new()
+                .first_pipe_instance(true)
+                .reject_remote_clients(true)
+                .create(&name)
+                .map_err(|e| AppError::Conflict(format!("Cannot create pipe {}: {}", name, e)))?;
+            Ok(Self { name, next })
+        }
+
+        pub async fn accept(&mut self) -> io::Result<NamedPipeServer> {
+            self.next.connect().await?;
+            let fresh = ServerOptions::new()
+                .reject_remote_clients(true)
+                .create(&self.name)?;
+            Ok(std::mem::replace(&mut self.next, fresh))
+        }
+    }
+
+    pub async fn connect() -> io::Result<NamedPipeClient> {
+        loop {
+            match ClientOptions::new().open(pipe_name()) {
+                Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY) => {
+                    tokio::time::sleep(Duration::from_millis(50)).await
+                }
+                result => return result,
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use crate::links::SpotifyLink;
+use crate::playback::PlayerCommand;
+
+/// What a command line asks of the app, besides showing its window
+#[derive(Debug, Default, PartialEq)]
+pub struct LaunchArgs {
+    pub command: Option<PlayerCommand>,
+    /// Spotify URI or link to open, as passed by a clicked link
+    pub link: Option<SpotifyLink>,
+}
+
+impl LaunchArgs {
+    /// Parse a full command line, program name included
+    ///
+    /// Unknown arguments are ignored: the OS and the webview add their own.
+    pub fn parse(argv: &[String]) -> Self {
+        let mut args = Self::default();
+        for arg in argv.iter().skip(1) {
+            let command = match arg.as_str() {
+                "--play-pause" => PlayerCommand::TogglePlay,
+                "--play" => PlayerCommand::Play,
+                "--pause" => PlayerCommand::Pause,
+                "--next" => PlayerCommand::Next,
+                "--previous" => PlayerCommand::Previous,
+                other => {
+                    if let Some(link) = SpotifyLink::parse(other) {
+                        args.link = Some(link);
+                    }
+                    continue;
+                }
+            };
+            args.command = Some(command);
+        }
+        args
+    }
+
+    /// Whether the launch only controls playback, so the window can stay hidden
+    pub fn is_remote_control(&self) -> bool {
+        self.command.is_some() && self.link.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> LaunchArgs {
+        let argv: Vec<String> = std::iter::once("spotify")
+            .chain(args.iter().copied())
+            .map(String::from)
+            .collect();
+        LaunchArgs::parse(&argv)
+    }
+
+    #[test]
+    fn test_reads_flags_and_uris() {
+        assert_eq!(parse(&[]), LaunchArgs::default());
+        assert_eq!(
+            parse(&["--play-pause"]),
+            LaunchArgs {
+                command: Some(PlayerCommand::TogglePlay),
+                link: None
+            }
+        );
+        assert!(parse(&["--next"]).is_remote_control());
+
+        let args = parse(&[
+            "-psn_0_123",
+            "https://open.spotify.com/album/4uLU6hMCjMI75M1A2tKUQC",
+        ]);
+        assert_eq!(
+            args.link.as_ref().map(|l| l.uri()).as_deref(),
+            Some("spotify:album:4uLU6hMCjMI75M1A2tKUQC")
+        );
+        assert_eq!(args.command, None);
+        assert!(!args.is_remote_control());
+        assert!(!parse(&["--pause", "spotify:track:4uLU6hMCjMI75M1A2tKUQC"]).is_remote_control());
+        assert_eq!(parse(&["spotify:track:a"]).link, None);
+    }
+}
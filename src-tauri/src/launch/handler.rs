@@ -0,0 +1,52 @@
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::args::LaunchArgs;
+use crate::auth::AppAuthState;
+use crate::links::{self, LinkHub};
+use crate::playback::{self, PlaybackHub, PlayerCommand};
+use crate::window;
+
+/// Run the playback flag of a command line
+fn run(app: &AppHandle, command: PlayerCommand) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppAuthState>();
+        let hub = app.state::<PlaybackHub>();
+        if let Err(e) = playback::execute(&state, &hub, command).await {
+            log::warn!("Launch argument failed: {}", e);
+            let _ = app.emit("launch:failed", e.to_string());
+        }
+    });
+}
+
+/// Act on the arguments this process was started with
+///
+/// A link is kept for the frontend to pick up once loaded, since an event
+/// sent now would arrive before anything listens.
+pub fn handle_startup_args(app: &AppHandle) {
+    let argv: Vec<String> = std::env::args().collect();
+    let args = LaunchArgs::parse(&argv);
+    if let Some(link) = args.link {
+        app.state::<LinkHub>().set_startup(link);
+    }
+    if let Some(command) = args.command {
+        run(app, command);
+    }
+}
+
+/// Called in the running instance when the app is launched again
+///
+/// The second process exits right away; its arguments are handled here and
+/// the existing window comes to the front unless they only control playback.
+pub fn handle_second_instance(app: &AppHandle, argv: Vec<String>, _cwd: String) {
+    let args = LaunchArgs::parse(&argv);
+    if !args.is_remote_control() {
+        window::show_main_window(app);
+    }
+    if let Some(link) = args.link {
+        links::open_or_hold(app, link);
+    }
+    if let Some(command) = args.command {
+        run(app, command);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod args;
+pub mod handler;
+
+pub use handler::*;
@@ -1,16 +1,74 @@
+mod annotations;
+mod api;
 mod auth;
+mod autoskip;
+mod blocklist;
+#[cfg(desktop)]
+mod clipboard;
+mod devices;
+mod error;
+pub mod ipc;
+#[cfg(desktop)]
+mod launch;
+mod library;
+mod links;
+mod loopback;
+#[cfg(target_os = "linux")]
+mod mpris;
+#[cfg(desktop)]
+mod notifications;
+mod nowplaying;
+mod playback;
+mod playlists;
+mod queue;
+mod remote;
+#[cfg(desktop)]
+mod shortcuts;
+mod smart;
+mod store;
+mod timers;
+#[cfg(desktop)]
+mod tray;
 mod window;
 
 use auth::{AppAuthState, SpotifyConfig};
+#[cfg(desktop)]
+use clipboard::ClipboardHub;
+use devices::DeviceHub;
+use links::LinkHub;
+use nowplaying::NowPlayingHub;
+use playback::PlaybackHub;
+use remote::RemoteHub;
+#[cfg(desktop)]
+use shortcuts::ShortcutHub;
+#[cfg(desktop)]
+use tauri::Manager;
+use timers::SleepTimerHub;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Load Spotify config from environment
     let spotify_config = SpotifyConfig::default();
 
-    tauri::Builder::default()
+    let builder = tauri::Builder::default();
+    // Must come first so a second launch exits before anything else starts
+    #[cfg(desktop)]
+    let builder = builder
+        .plugin(tauri_plugin_single_instance::init(
+            launch::handle_second_instance,
+        ))
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_clipboard_manager::init());
+
+    builder
         .plugin(tauri_plugin_shell::init())
         .manage(AppAuthState::new(spotify_config))
+        .manage(PlaybackHub::default())
+        .manage(DeviceHub::default())
+        .manage(SleepTimerHub::default())
+        .manage(RemoteHub::default())
+        .manage(LinkHub::default())
+        .manage(NowPlayingHub::default())
         .invoke_handler(tauri::generate_handler![
             auth::get_auth_url,
             auth::exchange_code,
@@ -23,6 +81,115 @@ pub fn run() {
             window::set_fullscreen,
             window::is_fullscreen,
             window::toggle_fullscreen,
+            playlists::preview_playlist_import,
+            playlists::create_playlist_from_import,
+            playlists::get_backup_config,
+            playlists::set_backup_config,
+            playlists::backup_playlist_now,
+            playlists::list_playlist_versions,
+            playlists::get_playlist_version,
+            playlists::diff_playlist_versions,
+            playlists::restore_playlist_version,
+            playlists::analyze_playlist_cleanup,
+            playlists::apply_playlist_cleanup,
+            playlists::preview_playlist_set_operation,
+            playlists::apply_playlist_set_operation,
+            playlists::preview_playlist_split,
+            playlists::apply_playlist_split,
+            playlists::plan_playlist_reorder,
+            playlists::reorder_playlist,
+            library::get_library_status,
+            library::sync_library,
+            library::get_library_tree,
+            library::list_library_folders,
+            library::create_library_folder,
+            library::update_library_folder,
+            library::delete_library_folder,
+            library::move_library_node,
+            library::set_library_order,
+            library::set_library_pinned,
+            library::set_library_item_style,
+            annotations::get_annotations,
+            annotations::set_annotation,
+            annotations::set_rating,
+            annotations::edit_tags,
+            annotations::list_tags,
+            annotations::query_tags,
+            annotations::search_annotations,
+            annotations::export_annotations,
+            annotations::import_annotations,
+            playback::get_playback_state,
+            playback::refresh_playback_state,
+            playback::player_command,
+            devices::list_devices,
+            devices::transfer_playback,
+            devices::get_device_preferences,
+            devices::set_device_auto_reconnect,
+            devices::set_preferred_device,
+            devices::transfer_to_preferred_device,
+            queue::get_local_queue,
+            queue::add_to_local_queue,
+            queue::remove_from_local_queue,
+            queue::move_local_queue_item,
+            queue::shuffle_local_queue,
+            queue::clear_local_queue,
+            queue::play_local_queue,
+            queue::save_local_queue_as_playlist,
+            timers::start_sleep_timer,
+            timers::cancel_sleep_timer,
+            timers::get_sleep_timer,
+            timers::list_alarms,
+            timers::save_alarm,
+            timers::delete_alarm,
+            timers::set_alarm_enabled,
+            timers::test_alarm,
+            autoskip::get_skip_settings,
+            autoskip::set_skip_enabled,
+            autoskip::add_skip_rule,
+            autoskip::remove_skip_rule,
+            autoskip::set_skip_rule_enabled,
+            autoskip::set_skip_context_override,
+            autoskip::get_skip_log,
+            autoskip::clear_skip_log,
+            blocklist::get_blocklist,
+            blocklist::block_artist,
+            blocklist::unblock_artist,
+            blocklist::block_track,
+            blocklist::unblock_track,
+            blocklist::search_tracks,
+            blocklist::get_recommendations,
+            smart::list_smart_playlists,
+            smart::save_smart_playlist,
+            smart::delete_smart_playlist,
+            smart::preview_smart_playlist,
+            smart::refresh_smart_playlist,
+            remote::get_remote_settings,
+            remote::set_remote_settings,
+            remote::regenerate_remote_token,
+            links::resolve_spotify_link,
+            links::take_startup_link,
+            nowplaying::get_now_playing_settings,
+            nowplaying::set_now_playing_settings,
+            #[cfg(desktop)]
+            shortcuts::get_shortcuts,
+            #[cfg(desktop)]
+            shortcuts::set_shortcut,
+            #[cfg(desktop)]
+            shortcuts::reset_shortcuts,
+            #[cfg(desktop)]
+            tray::get_tray_settings,
+            #[cfg(desktop)]
+            tray::set_minimize_to_tray,
+            #[cfg(desktop)]
+            notifications::get_notification_settings,
+            #[cfg(desktop)]
+            notifications::set_notification_settings,
+            #[cfg(desktop)]
+            clipboard::get_clipboard_settings,
+            #[cfg(desktop)]
+            clipboard::set_clipboard_watcher,
+            #[cfg(desktop)]
+            clipboard::run_clipboard_action,
         ])
         .setup(|app| {
             if cfg!(debug_assertions) {
@@ -32,6 +199,37 @@ pub fn run() {
                         .build(),
                 )?;
             }
+
+            playlists::spawn_backup_job(app.handle().clone());
+            library::spawn_history_job(app.handle().clone());
+            smart::spawn_smart_playlist_job(app.handle().clone());
+            playback::spawn_playback_poller(app.handle().clone());
+            devices::spawn_device_jobs(app.handle().clone());
+            queue::spawn_queue_feeder(app.handle().clone());
+            timers::spawn_sleep_timer_job(app.handle().clone());
+            timers::spawn_alarm_job(app.handle().clone());
+            autoskip::spawn_autoskip_job(app.handle().clone());
+            remote::spawn_remote_server(app.handle().clone());
+            ipc::spawn_ipc_server(app.handle().clone());
+            nowplaying::spawn_now_playing_job(app.handle().clone());
+            #[cfg(target_os = "linux")]
+            mpris::spawn_mpris(app.handle().clone());
+            #[cfg(desktop)]
+            {
+                app.manage(ShortcutHub::default());
+                app.manage(ClipboardHub::default());
+                app.handle().plugin(
+                    tauri_plugin_global_shortcut::Builder::new()
+                        .with_handler(shortcuts::handle_shortcut)
+                        .build(),
+                )?;
+                shortcuts::register_shortcuts(app.handle());
+                tray::spawn_tray(app.handle())?;
+                notifications::spawn_notification_job(app.handle().clone());
+                launch::handle_startup_args(app.handle());
+                links::listen_deep_links(app.handle());
+                clipboard::spawn_clipboard_watcher(app.handle().clone());
+            }
             Ok(())
         })
         .run(tauri::generate_context!())
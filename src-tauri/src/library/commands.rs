@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use super::{history, mirror};
+use crate::auth::{self, AppAuthState};
+use crate::error::AppError;
+
+/// How often the recently played endpoint is merged into the local history
+const HISTORY_SYNC_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+/// Summary of the local library data
+#[derive(Debug, Clone, Serialize)]
+pub struct LibraryStatus {
+    pub synced_at: Option<DateTime<Utc>>,
+    pub track_count: usize,
+    pub artist_count: usize,
+    pub play_count: usize,
+}
+
+fn status(state: &AppAuthState) -> Result<LibraryStatus, AppError> {
+    let mirror = mirror::load_mirror(state)?;
+    let history = history::load_history(state)?;
+    Ok(LibraryStatus {
+        synced_at: mirror.synced_at,
+        track_count: mirror.tracks.len(),
+        artist_count: mirror.genres.len(),
+        play_count: history.records.len(),
+    })
+}
+
+/// Start the background job that keeps the play history growing
+pub fn spawn_history_job(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let state = app.state::<AppAuthState>();
+            if auth::current_user(&state).is_some() {
+                if let Err(e) = history::sync_recently_played(&state).await {
+                    log::warn!("Failed to sync play history: {}", e);
+                }
+            }
+            tokio::time::sleep(HISTORY_SYNC_INTERVAL).await;
+        }
+    });
+}
+
+/// Get the state of the local library mirror and play history
+#[tauri::command]
+pub fn get_library_status(state: State<AppAuthState>) -> Result<LibraryStatus, AppError> {
+    status(&state)
+}
+
+/// Resync the library mirror and play history from Spotify
+#[tauri::command]
+pub async fn sync_library(state: State<'_, AppAuthState>) -> Result<LibraryStatus, AppError> {
+    mirror::sync_mirror(&state).await?;
+    history::sync_recently_played(&state).await?;
+    status(&state)
+}
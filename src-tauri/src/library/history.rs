@@ -0,0 +1,141 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::api::SpotifyApi;
+use crate::auth::AppAuthState;
+use crate::error::AppError;
+use crate::store;
+
+const HISTORY_FILE: &str = "history.json";
+
+/// Plays of the same track closer than this are the same play seen twice
+/// (e.g. by the playback poller and by the recently played endpoint)
+const DUPLICATE_WINDOW_MINUTES: i64 = 10;
+
+/// Plays older than this are dropped
+const RETENTION_DAYS: i64 = 730;
+
+/// A single play of a track
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayRecord {
+    pub uri: String,
+    pub played_at: DateTime<Utc>,
+    pub context_uri: Option<String>,
+}
+
+/// Aggregated plays of a track
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PlayStats {
+    pub count: u32,
+    pub last_played: DateTime<Utc>,
+}
+
+/// Local play history, oldest first
+///
+/// Spotify only exposes the last 50 plays, so the history is accumulated locally.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlayHistoryLog {
+    pub records: Vec<PlayRecord>,
+}
+
+impl PlayHistoryLog {
+    /// Merge new plays, skipping ones already recorded
+    ///
+    /// Returns the number of plays added.
+    pub fn merge(&mut self, plays: impl IntoIterator<Item = PlayRecord>) -> usize {
+        let window = Duration::minutes(DUPLICATE_WINDOW_MINUTES);
+        let mut added = 0;
+
+        for play in plays {
+            let duplicate = self
+                .records
+                .iter()
+                .rev()
+                .any(|r| r.uri == play.uri && (r.played_at - play.played_at).abs() < window);
+            if !duplicate {
+                self.records.push(play);
+                added += 1;
+            }
+        }
+
+        if added > 0 {
+            self.records.sort_by_key(|r| r.played_at);
+            let cutoff = Utc::now() - Duration::days(RETENTION_DAYS);
+            self.records.retain(|r| r.played_at >= cutoff);
+        }
+        added
+    }
+
+    /// Play count and last play per track URI
+    pub fn stats(&self) -> HashMap<String, PlayStats> {
+        let mut stats: HashMap<String, PlayStats> = HashMap::new();
+        for record in &self.records {
+            stats
+                .entry(record.uri.clone())
+                .and_modify(|s| {
+                    s.count += 1;
+                    s.last_played = s.last_played.max(record.played_at);
+                })
+                .or_insert(PlayStats {
+                    count: 1,
+                    last_played: record.played_at,
+                });
+        }
+        stats
+    }
+}
+
+pub fn load_history(state: &AppAuthState) -> Result<PlayHistoryLog, AppError> {
+    Ok(store::load_json(&store::account_file(state, HISTORY_FILE)?)?.unwrap_or_default())
+}
+
+/// Add plays to the stored history
+pub fn record_plays(
+    state: &AppAuthState,
+    plays: impl IntoIterator<Item = PlayRecord>,
+) -> Result<usize, AppError> {
+    store::update_json(
+        &store::account_file(state, HISTORY_FILE)?,
+        |history: &mut PlayHistoryLog| Ok(history.merge(plays)),
+    )
+}
+
+/// Pull the recently played endpoint into the stored history
+pub async fn sync_recently_played(state: &AppAuthState) -> Result<usize, AppError> {
+    let plays = SpotifyApi::new(state).recently_played().await?;
+    record_plays(
+        state,
+        plays.into_iter().map(|p| PlayRecord {
+            uri: p.track.uri,
+            played_at: p.played_at,
+            context_uri: p.context.map(|c| c.uri),
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(uri: &str, minutes_ago: i64) -> PlayRecord {
+        PlayRecord {
+            uri: uri.into(),
+            played_at: Utc::now() - Duration::minutes(minutes_ago),
+            context_uri: None,
+        }
+    }
+
+    #[test]
+    fn test_merge_skips_same_play() {
+        let mut history = PlayHistoryLog::default();
+        assert_eq!(history.merge([play("a", 60), play("b", 30)]), 2);
+        // Same play reported again a few minutes off, plus a genuine replay
+        assert_eq!(history.merge([play("a", 57), play("a", 5)]), 1);
+
+        let stats = history.stats();
+        assert_eq!(stats["a"].count, 2);
+        assert_eq!(stats["b"].count, 1);
+        assert_eq!(history.records.last().unwrap().uri, "a");
+    }
+}
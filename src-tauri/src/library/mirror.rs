@@ -0,0 +1,143 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::api::{SavedTrack, SimplifiedArtist, SpotifyApi};
+use crate::auth::AppAuthState;
+use crate::error::AppError;
+use crate::store;
+
+const MIRROR_FILE: &str = "library.json";
+
+/// A saved track as kept in the local mirror
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryTrack {
+    pub uri: String,
+    pub name: String,
+    pub artists: Vec<SimplifiedArtist>,
+    pub album: String,
+    pub album_uri: String,
+    pub release_date: Option<String>,
+    pub duration_ms: u64,
+    pub explicit: bool,
+    pub popularity: Option<u32>,
+    pub isrc: Option<String>,
+    pub added_at: DateTime<Utc>,
+}
+
+impl LibraryTrack {
+    /// Release year parsed from `release_date` (which may be `YYYY`, `YYYY-MM` or `YYYY-MM-DD`)
+    pub fn release_year(&self) -> Option<i32> {
+        self.release_date
+            .as_deref()
+            .and_then(|d| d.get(..4))
+            .and_then(|y| y.parse().ok())
+    }
+}
+
+impl From<SavedTrack> for LibraryTrack {
+    fn from(saved: SavedTrack) -> Self {
+        let track = saved.track;
+        Self {
+            isrc: track.isrc().map(str::to_string),
+            uri: track.uri,
+            name: track.name,
+            artists: track.artists,
+            album: track.album.name,
+            album_uri: track.album.uri,
+            release_date: track.album.release_date,
+            duration_ms: track.duration_ms,
+            explicit: track.explicit,
+            popularity: track.popularity,
+            added_at: saved.added_at,
+        }
+    }
+}
+
+/// Local copy of the user's saved tracks and the genres of their artists
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LibraryMirror {
+    pub synced_at: Option<DateTime<Utc>>,
+    pub tracks: Vec<LibraryTrack>,
+    /// Artist id -> genres
+    pub genres: HashMap<String, Vec<String>>,
+}
+
+impl LibraryMirror {
+    /// Genres of every artist on a track
+    pub fn track_genres<'a>(&'a self, track: &'a LibraryTrack) -> impl Iterator<Item = &'a str> {
+        track
+            .artists
+            .iter()
+            .filter_map(|a| a.id.as_ref())
+            .filter_map(|id| self.genres.get(id))
+            .flatten()
+            .map(String::as_str)
+    }
+
+    pub fn is_stale(&self, max_age: chrono::Duration) -> bool {
+        self.synced_at
+            .map(|t| Utc::now() - t > max_age)
+            .unwrap_or(true)
+    }
+}
+
+/// Load the mirror from disk (empty if never synced)
+pub fn load_mirror(state: &AppAuthState) -> Result<LibraryMirror, AppError> {
+    Ok(store::load_json(&store::account_file(state, MIRROR_FILE)?)?.unwrap_or_default())
+}
+
+/// Refresh the mirror from Spotify
+///
+/// Artist genres are cached across syncs, only artists not seen before are fetched.
+pub async fn sync_mirror(state: &AppAuthState) -> Result<LibraryMirror, AppError> {
+    let api = SpotifyApi::new(state);
+    let mut mirror = load_mirror(state)?;
+
+    mirror.tracks = api
+        .saved_tracks()
+        .await?
+        .into_iter()
+        .filter(|s| !s.track.is_local)
+        .map(LibraryTrack::from)
+        .collect();
+
+    let referenced: HashSet<&String> = mirror
+        .tracks
+        .iter()
+        .flat_map(|t| t.artists.iter())
+        .filter_map(|a| a.id.as_ref())
+        .collect();
+    let missing: Vec<String> = referenced
+        .iter()
+        .filter(|id| !mirror.genres.contains_key(**id))
+        .map(|id| id.to_string())
+        .collect();
+
+    for artist in api.artists(&missing).await? {
+        mirror.genres.insert(artist.id, artist.genres);
+    }
+
+    mirror.synced_at = Some(Utc::now());
+    store::save_json(&store::account_file(state, MIRROR_FILE)?, &mirror)?;
+
+    log::info!(
+        "Library mirror synced: {} tracks, {} artists",
+        mirror.tracks.len(),
+        mirror.genres.len()
+    );
+    Ok(mirror)
+}
+
+/// Load the mirror, syncing it first when older than `max_age`
+pub async fn ensure_fresh(
+    state: &AppAuthState,
+    max_age: chrono::Duration,
+) -> Result<LibraryMirror, AppError> {
+    let mirror = load_mirror(state)?;
+    if mirror.is_stale(max_age) {
+        sync_mirror(state).await
+    } else {
+        Ok(mirror)
+    }
+}
@@ -0,0 +1,9 @@
+pub mod commands;
+pub mod history;
+pub mod mirror;
+pub mod organizer;
+
+pub use commands::*;
+pub use history::PlayStats;
+pub use mirror::{LibraryMirror, LibraryTrack};
+pub use organizer::*;
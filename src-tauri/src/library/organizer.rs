@@ -0,0 +1,617 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{HashMap, HashSet};
+use tauri::State;
+
+use crate::auth::AppAuthState;
+use crate::error::AppError;
+use crate::store;
+
+const ORGANIZER_FILE: &str = "organizer.json";
+
+/// Node keys of folders are prefixed so they never collide with Spotify URIs
+const FOLDER_PREFIX: &str = "folder:";
+
+/// A local folder; folders nest through `parent_id`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Folder {
+    pub id: String,
+    pub name: String,
+    pub parent_id: Option<String>,
+    pub color: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Local metadata of a library item (playlist, album, artist...) keyed by URI
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ItemMeta {
+    pub folder_id: Option<String>,
+    pub color: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+/// Everything the library page organisation persists for an account
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Organizer {
+    pub folders: Vec<Folder>,
+    pub items: HashMap<String, ItemMeta>,
+    /// Pinned node keys, in display order
+    pub pinned: Vec<String>,
+    /// Custom order of node keys per container (`""` is the root)
+    pub orders: HashMap<String, Vec<String>>,
+}
+
+/// Changes to a folder; absent fields are left untouched
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FolderUpdate {
+    pub name: Option<String>,
+    /// `null` clears the colour, an absent field keeps it
+    #[serde(default, deserialize_with = "present")]
+    pub color: Option<Option<String>>,
+    pub labels: Option<Vec<String>>,
+}
+
+/// Marks a field that was sent, even as `null`
+fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// A node of the library tree returned to the frontend
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TreeNode {
+    Folder {
+        id: String,
+        key: String,
+        name: String,
+        color: Option<String>,
+        labels: Vec<String>,
+        pinned: bool,
+        children: Vec<TreeNode>,
+    },
+    Item {
+        uri: String,
+        color: Option<String>,
+        labels: Vec<String>,
+        pinned: bool,
+    },
+}
+
+impl TreeNode {
+    fn key(&self) -> String {
+        match self {
+            TreeNode::Folder { key, .. } => key.clone(),
+            TreeNode::Item { uri, .. } => uri.clone(),
+        }
+    }
+}
+
+/// The organised library
+#[derive(Debug, Clone, Serialize)]
+pub struct LibraryTree {
+    pub pinned: Vec<String>,
+    pub root: Vec<TreeNode>,
+}
+
+fn folder_key(id: &str) -> String {
+    format!("{}{}", FOLDER_PREFIX, id)
+}
+
+fn container_key(folder_id: Option<&str>) -> String {
+    folder_id.unwrap_or_default().to_string()
+}
+
+impl Organizer {
+    fn folder(&self, id: &str) -> Result<&Folder, AppError> {
+        self.folders
+            .iter()
+            .find(|f| f.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("Folder {}", id)))
+    }
+
+    fn folder_mut(&mut self, id: &str) -> Result<&mut Folder, AppError> {
+        self.folders
+            .iter_mut()
+            .find(|f| f.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("Folder {}", id)))
+    }
+
+    /// Whether `folder_id` is `ancestor_id` or nested somewhere below it
+    fn is_within(&self, folder_id: &str, ancestor_id: &str) -> bool {
+        let mut cursor = Some(folder_id.to_string());
+        let mut visited = HashSet::new();
+        while let Some(id) = cursor {
+            if id == ancestor_id {
+                return true;
+            }
+            if !visited.insert(id.clone()) {
+                return false;
+            }
+            cursor = self
+                .folders
+                .iter()
+                .find(|f| f.id == id)
+                .and_then(|f| f.parent_id.clone());
+        }
+        false
+    }
+
+    fn remove_from_orders(&mut self, key: &str) {
+        for order in self.orders.values_mut() {
+            order.retain(|k| k != key);
+        }
+    }
+
+    fn insert_in_order(&mut self, container: &str, key: &str, index: Option<usize>) {
+        let order = self.orders.entry(container.to_string()).or_default();
+        order.retain(|k| k != key);
+        let index = index.unwrap_or(order.len()).min(order.len());
+        order.insert(index, key.to_string());
+    }
+
+    pub fn create_folder(
+        &mut self,
+        name: &str,
+        parent_id: Option<&str>,
+    ) -> Result<Folder, AppError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::InvalidInput("Folder name is required".into()));
+        }
+        if let Some(parent) = parent_id {
+            self.folder(parent)?;
+        }
+
+        let folder = Folder {
+            id: store::new_id(),
+            name: name.to_string(),
+            parent_id: parent_id.map(str::to_string),
+            color: None,
+            labels: Vec::new(),
+            created_at: Utc::now(),
+        };
+        self.insert_in_order(&container_key(parent_id), &folder_key(&folder.id), None);
+        self.folders.push(folder.clone());
+        Ok(folder)
+    }
+
+    pub fn update_folder(&mut self, id: &str, update: FolderUpdate) -> Result<Folder, AppError> {
+        let folder = self.folder_mut(id)?;
+        if let Some(name) = update.name {
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(AppError::InvalidInput("Folder name is required".into()));
+            }
+            folder.name = name.to_string();
+        }
+        if let Some(color) = update.color {
+            folder.color = color;
+        }
+        if let Some(labels) = update.labels {
+            folder.labels = labels;
+        }
+        Ok(folder.clone())
+    }
+
+    /// Delete a folder; without `recursive` its content moves up to the parent
+    pub fn delete_folder(&mut self, id: &str, recursive: bool) -> Result<(), AppError> {
+        let parent = self.folder(id)?.parent_id.clone();
+
+        let doomed: Vec<String> = if recursive {
+            self.folders
+                .iter()
+                .filter(|f| self.is_within(&f.id, id))
+                .map(|f| f.id.clone())
+                .collect()
+        } else {
+            vec![id.to_string()]
+        };
+
+        for folder in self.folders.iter_mut() {
+            if folder.parent_id.as_deref() == Some(id) && !doomed.contains(&folder.id) {
+                folder.parent_id = parent.clone();
+            }
+        }
+        for meta in self.items.values_mut() {
+            if let Some(folder) = meta.folder_id.as_ref() {
+                if doomed.contains(folder) {
+                    meta.folder_id = if recursive { None } else { parent.clone() };
+                }
+            }
+        }
+
+        // Children of a non-recursive delete keep their relative order in the parent
+        let moved = self
+            .orders
+            .remove(&container_key(Some(id)))
+            .unwrap_or_default();
+        let parent_container = container_key(parent.as_deref());
+        let position = self
+            .orders
+            .get(&parent_container)
+            .and_then(|o| o.iter().position(|k| *k == folder_key(id)));
+        for (offset, key) in moved.into_iter().enumerate() {
+            if !recursive {
+                self.insert_in_order(&parent_container, &key, position.map(|p| p + 1 + offset));
+            }
+        }
+
+        for folder in &doomed {
+            self.orders.remove(&container_key(Some(folder)));
+            self.remove_from_orders(&folder_key(folder));
+            self.pinned.retain(|k| *k != folder_key(folder));
+        }
+        self.folders.retain(|f| !doomed.contains(&f.id));
+        Ok(())
+    }
+
+    /// Move an item (URI) or folder (`folder:<id>`) into a folder, or to the root
+    pub fn move_node(
+        &mut self,
+        key: &str,
+        folder_id: Option<&str>,
+        index: Option<usize>,
+    ) -> Result<(), AppError> {
+        if let Some(target) = folder_id {
+            self.folder(target)?;
+        }
+
+        if let Some(id) = key.strip_prefix(FOLDER_PREFIX) {
+            if let Some(target) = folder_id {
+                if self.is_within(target, id) {
+                    return Err(AppError::InvalidInput(
+                        "A folder cannot be moved into itself".into(),
+                    ));
+                }
+            }
+            self.folder_mut(id)?.parent_id = folder_id.map(str::to_string);
+        } else {
+            self.items.entry(key.to_string()).or_default().folder_id =
+                folder_id.map(str::to_string);
+        }
+
+        self.remove_from_orders(key);
+        self.insert_in_order(&container_key(folder_id), key, index);
+        Ok(())
+    }
+
+    pub fn set_order(
+        &mut self,
+        folder_id: Option<&str>,
+        keys: Vec<String>,
+    ) -> Result<(), AppError> {
+        if let Some(id) = folder_id {
+            self.folder(id)?;
+        }
+        let mut seen = HashSet::new();
+        let keys = keys
+            .into_iter()
+            .filter(|k| seen.insert(k.clone()))
+            .collect();
+        self.orders.insert(container_key(folder_id), keys);
+        Ok(())
+    }
+
+    pub fn set_pinned(&mut self, key: &str, pinned: bool) -> Result<(), AppError> {
+        if let Some(id) = key.strip_prefix(FOLDER_PREFIX) {
+            self.folder(id)?;
+        }
+        self.pinned.retain(|k| k != key);
+        if pinned {
+            self.pinned.push(key.to_string());
+        }
+        Ok(())
+    }
+
+    pub fn set_item_style(&mut self, uri: &str, color: Option<String>, labels: Vec<String>) {
+        let meta = self.items.entry(uri.to_string()).or_default();
+        meta.color = color;
+        meta.labels = labels;
+    }
+
+    /// Build the tree for the items currently in the user's library
+    ///
+    /// Items unknown to the organizer land at the root in the given order; stored
+    /// metadata of items no longer in the library is ignored.
+    pub fn tree(&self, uris: &[String]) -> LibraryTree {
+        let present: HashSet<&String> = uris.iter().collect();
+        let mut children: HashMap<String, Vec<TreeNode>> = HashMap::new();
+
+        for uri in uris {
+            let meta = self.items.get(uri).cloned().unwrap_or_default();
+            let container = meta
+                .folder_id
+                .filter(|id| self.folders.iter().any(|f| f.id == *id))
+                .unwrap_or_default();
+            children.entry(container).or_default().push(TreeNode::Item {
+                uri: uri.clone(),
+                color: meta.color,
+                labels: meta.labels,
+                pinned: self.pinned.contains(uri),
+            });
+        }
+
+        let root = self.build_children("", &mut children, &mut HashSet::new());
+        let pinned = self
+            .pinned
+            .iter()
+            .filter(|k| {
+                k.strip_prefix(FOLDER_PREFIX)
+                    .map(|id| self.folders.iter().any(|f| f.id == id))
+                    .unwrap_or_else(|| present.contains(k))
+            })
+            .cloned()
+            .collect();
+
+        LibraryTree { pinned, root }
+    }
+
+    fn build_children(
+        &self,
+        container: &str,
+        items: &mut HashMap<String, Vec<TreeNode>>,
+        visited: &mut HashSet<String>,
+    ) -> Vec<TreeNode> {
+        let mut nodes = Vec::new();
+
+        for folder in &self.folders {
+            let parent = folder
+                .parent_id
+                .clone()
+                .filter(|id| self.folders.iter().any(|f| f.id == *id))
+                .unwrap_or_default();
+            if parent != container || !visited.insert(folder.id.clone()) {
+                continue;
+            }
+            let key = folder_key(&folder.id);
+            nodes.push(TreeNode::Folder {
+                id: folder.id.clone(),
+                pinned: self.pinned.contains(&key),
+                key,
+                name: folder.name.clone(),
+                color: folder.color.clone(),
+                labels: folder.labels.clone(),
+                children: self.build_children(&folder.id, items, visited),
+            });
+        }
+        nodes.extend(items.remove(container).unwrap_or_default());
+
+        // Custom order first, everything else keeps its natural position after it
+        if let Some(order) = self.orders.get(container) {
+            let rank: HashMap<&String, usize> =
+                order.iter().enumerate().map(|(i, k)| (k, i)).collect();
+            nodes.sort_by_key(|n| rank.get(&n.key()).copied().unwrap_or(usize::MAX));
+        }
+        nodes
+    }
+}
+
+/// Load, modify and save the organizer in one locked step
+fn update<T>(
+    state: &AppAuthState,
+    f: impl FnOnce(&mut Organizer) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    store::update_json(&store::account_file(state, ORGANIZER_FILE)?, f)
+}
+
+fn load(state: &AppAuthState) -> Result<Organizer, AppError> {
+    Ok(store::load_json(&store::account_file(state, ORGANIZER_FILE)?)?.unwrap_or_default())
+}
+
+/// Get the organised library tree for the given library item URIs
+#[tauri::command]
+pub fn get_library_tree(
+    uris: Vec<String>,
+    state: State<AppAuthState>,
+) -> Result<LibraryTree, AppError> {
+    Ok(load(&state)?.tree(&uris))
+}
+
+/// List every local folder
+#[tauri::command]
+pub fn list_library_folders(state: State<AppAuthState>) -> Result<Vec<Folder>, AppError> {
+    Ok(load(&state)?.folders)
+}
+
+/// Create a folder, optionally inside another one
+#[tauri::command]
+pub fn create_library_folder(
+    name: String,
+    parent_id: Option<String>,
+    state: State<AppAuthState>,
+) -> Result<Folder, AppError> {
+    update(&state, |o| o.create_folder(&name, parent_id.as_deref()))
+}
+
+/// Rename or restyle a folder
+#[tauri::command]
+pub fn update_library_folder(
+    id: String,
+    update_data: FolderUpdate,
+    state: State<AppAuthState>,
+) -> Result<Folder, AppError> {
+    update(&state, |o| o.update_folder(&id, update_data))
+}
+
+/// Delete a folder; without `recursive` its content moves to the parent folder
+#[tauri::command]
+pub fn delete_library_folder(
+    id: String,
+    recursive: Option<bool>,
+    state: State<AppAuthState>,
+) -> Result<(), AppError> {
+    update(&state, |o| o.delete_folder(&id, recursive.unwrap_or(false)))
+}
+
+/// Move an item URI or a `folder:<id>` key into a folder (or the root when `None`)
+#[tauri::command]
+pub fn move_library_node(
+    key: String,
+    folder_id: Option<String>,
+    index: Option<usize>,
+    state: State<AppAuthState>,
+) -> Result<(), AppError> {
+    update(&state, |o| o.move_node(&key, folder_id.as_deref(), index))
+}
+
+/// Store a custom order for the content of a folder (or the root when `None`)
+#[tauri::command]
+pub fn set_library_order(
+    folder_id: Option<String>,
+    keys: Vec<String>,
+    state: State<AppAuthState>,
+) -> Result<(), AppError> {
+    update(&state, |o| o.set_order(folder_id.as_deref(), keys))
+}
+
+/// Pin or unpin an item URI or `folder:<id>` key
+#[tauri::command]
+pub fn set_library_pinned(
+    key: String,
+    pinned: bool,
+    state: State<AppAuthState>,
+) -> Result<(), AppError> {
+    update(&state, |o| o.set_pinned(&key, pinned))
+}
+
+/// Set the colour and labels of a library item
+#[tauri::command]
+pub fn set_library_item_style(
+    uri: String,
+    color: Option<String>,
+    labels: Vec<String>,
+    state: State<AppAuthState>,
+) -> Result<(), AppError> {
+    update(&state, |o| {
+        o.set_item_style(&uri, color, labels);
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uris(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn keys(nodes: &[TreeNode]) -> Vec<String> {
+        nodes.iter().map(TreeNode::key).collect()
+    }
+
+    #[test]
+    fn test_tree_with_nested_folders() {
+        let mut organizer = Organizer::default();
+        let mixes = organizer.create_folder("Mixes", None).unwrap();
+        let gym = organizer.create_folder("Gym", Some(&mixes.id)).unwrap();
+        organizer
+            .move_node("spotify:playlist:a", Some(&gym.id), None)
+            .unwrap();
+        organizer
+            .move_node("spotify:playlist:b", Some(&mixes.id), None)
+            .unwrap();
+        organizer.set_pinned("spotify:playlist:c", true).unwrap();
+
+        let tree = organizer.tree(&uris(&[
+            "spotify:playlist:a",
+            "spotify:playlist:b",
+            "spotify:playlist:c",
+        ]));
+
+        assert_eq!(
+            keys(&tree.root),
+            vec![folder_key(&mixes.id), "spotify:playlist:c".into()]
+        );
+        assert_eq!(tree.pinned, vec!["spotify:playlist:c".to_string()]);
+        let TreeNode::Folder { children, .. } = &tree.root[0] else {
+            panic!("expected folder");
+        };
+        assert_eq!(
+            keys(children),
+            vec![folder_key(&gym.id), "spotify:playlist:b".into()]
+        );
+    }
+
+    #[test]
+    fn test_custom_order_and_unknown_items() {
+        let mut organizer = Organizer::default();
+        organizer
+            .set_order(None, uris(&["spotify:playlist:c", "spotify:playlist:a"]))
+            .unwrap();
+
+        let tree = organizer.tree(&uris(&[
+            "spotify:playlist:a",
+            "spotify:playlist:b",
+            "spotify:playlist:c",
+        ]));
+
+        assert_eq!(
+            keys(&tree.root),
+            uris(&[
+                "spotify:playlist:c",
+                "spotify:playlist:a",
+                "spotify:playlist:b"
+            ])
+        );
+    }
+
+    #[test]
+    fn test_folder_cannot_move_into_descendant() {
+        let mut organizer = Organizer::default();
+        let outer = organizer.create_folder("Outer", None).unwrap();
+        let inner = organizer.create_folder("Inner", Some(&outer.id)).unwrap();
+
+        assert!(organizer
+            .move_node(&folder_key(&outer.id), Some(&inner.id), None)
+            .is_err());
+        assert!(organizer
+            .move_node(&folder_key(&outer.id), Some(&outer.id), None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_delete_folder_moves_content_up() {
+        let mut organizer = Organizer::default();
+        let outer = organizer.create_folder("Outer", None).unwrap();
+        let inner = organizer.create_folder("Inner", Some(&outer.id)).unwrap();
+        organizer
+            .move_node("spotify:playlist:a", Some(&inner.id), None)
+            .unwrap();
+
+        organizer.delete_folder(&inner.id, false).unwrap();
+        assert_eq!(
+            organizer.items["spotify:playlist:a"].folder_id,
+            Some(outer.id.clone())
+        );
+
+        organizer.delete_folder(&outer.id, true).unwrap();
+        assert!(organizer.folders.is_empty());
+        let tree = organizer.tree(&uris(&["spotify:playlist:a"]));
+        assert_eq!(keys(&tree.root), uris(&["spotify:playlist:a"]));
+    }
+
+    #[test]
+    fn test_folder_update_clears_color() {
+        let mut organizer = Organizer::default();
+        let folder = organizer.create_folder("Mixes", None).unwrap();
+        let set: FolderUpdate = serde_json::from_str(r##"{"color": "#ff0000"}"##).unwrap();
+        organizer.update_folder(&folder.id, set).unwrap();
+
+        let rename: FolderUpdate = serde_json::from_str(r#"{"name": "Gym"}"#).unwrap();
+        let renamed = organizer.update_folder(&folder.id, rename).unwrap();
+        assert_eq!(renamed.color.as_deref(), Some("#ff0000"));
+
+        let clear: FolderUpdate = serde_json::from_str(r#"{"color": null}"#).unwrap();
+        let cleared = organizer.update_folder(&folder.id, clear).unwrap();
+        assert_eq!(cleared.color, None);
+        assert_eq!(cleared.name, "Gym");
+    }
+}
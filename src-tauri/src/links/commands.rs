@@ -0,0 +1,175 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::parse::{LinkKind, SpotifyLink};
+use crate::api::SpotifyApi;
+use crate::auth::AppAuthState;
+use crate::error::AppError;
+use crate::window;
+
+#[derive(Default)]
+struct Startup {
+    link: Option<SpotifyLink>,
+    /// Set once the frontend has loaded and asked for the link
+    taken: bool,
+}
+
+/// Link the app was started with, kept until the frontend has loaded
+#[derive(Default)]
+pub struct LinkHub {
+    startup: Mutex<Startup>,
+}
+
+impl LinkHub {
+    pub fn set_startup(&self, link: SpotifyLink) {
+        self.startup.lock().unwrap().link = Some(link);
+    }
+
+    /// Keep a link until the frontend has loaded, or hand it back to open now
+    ///
+    /// On macOS the link a cold start was opened with arrives as an event,
+    /// possibly before the window can receive one.
+    fn hold(&self, link: SpotifyLink) -> Option<SpotifyLink> {
+        let mut startup = self.startup.lock().unwrap();
+        if startup.taken {
+            return Some(link);
+        }
+        startup.link = Some(link);
+        None
+    }
+
+    fn take(&self) -> Option<SpotifyLink> {
+        let mut startup = self.startup.lock().unwrap();
+        startup.taken = true;
+        startup.link.take()
+    }
+}
+
+/// Where a link leads in the app
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkTarget {
+    #[serde(flatten)]
+    pub link: SpotifyLink,
+    pub uri: String,
+    /// Page to navigate to, `None` for items without one (shows, episodes)
+    pub route: Option<String>,
+}
+
+/// Find the page of a link; tracks lead to their album
+pub async fn resolve(state: &AppAuthState, link: SpotifyLink) -> Result<LinkTarget, AppError> {
+    let route = match link.kind {
+        LinkKind::Track => SpotifyApi::new(state)
+            .tracks(std::slice::from_ref(&link.id))
+            .await?
+            .into_iter()
+            .next()
+            .and_then(|track| track.album.id)
+            .map(|id| format!("/album/{}", id)),
+        _ => link.route(),
+    };
+    Ok(LinkTarget {
+        uri: link.uri(),
+        route,
+        link,
+    })
+}
+
+/// Resolve a link, falling back to its own route when the lookup fails
+async fn target(state: &AppAuthState, link: SpotifyLink) -> LinkTarget {
+    match resolve(state, link.clone()).await {
+        Ok(target) => target,
+        Err(e) => {
+            log::warn!("Failed to resolve {}: {}", link.uri(), e);
+            LinkTarget {
+                uri: link.uri(),
+                route: link.route(),
+                link,
+            }
+        }
+    }
+}
+
+/// Bring the window to the front and ask the frontend to show the item
+pub fn open_link(app: &AppHandle, link: SpotifyLink) {
+    window::show_main_window(app);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let target = target(&app.state::<AppAuthState>(), link).await;
+        let _ = app.emit("deeplink:navigate", &target);
+    });
+}
+
+/// Open a link, or keep it for [`take_startup_link`] while the frontend loads
+pub fn open_or_hold(app: &AppHandle, link: SpotifyLink) {
+    if let Some(link) = app.state::<LinkHub>().hold(link) {
+        open_link(app, link);
+    }
+}
+
+/// Open links handed over by the OS while the app runs
+///
+/// Links arriving before the frontend has loaded are kept for
+/// [`take_startup_link`]. On Windows and Linux a clicked link starts a new
+/// process instead, whose arguments reach us through the single-instance
+/// handler.
+#[cfg(desktop)]
+pub fn listen_deep_links(app: &AppHandle) {
+    use tauri_plugin_deep_link::DeepLinkExt;
+
+    // Bundles register the scheme on install; development builds do it here
+    #[cfg(all(debug_assertions, any(windows, target_os = "linux")))]
+    if let Err(e) = app.deep_link().register_all() {
+        log::warn!("Failed to register the link scheme: {}", e);
+    }
+
+    // Links delivered before this listener was registered
+    match app.deep_link().get_current() {
+        Ok(urls) => {
+            let hub = app.state::<LinkHub>();
+            for link in urls
+                .iter()
+                .flatten()
+                .filter_map(|u| SpotifyLink::parse(u.as_str()))
+            {
+                hub.set_startup(link);
+            }
+        }
+        Err(e) => log::warn!("Failed to read the startup link: {}", e),
+    }
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            let Some(link) = SpotifyLink::parse(url.as_str()) else {
+                log::warn!("Ignoring unsupported link {}", url);
+                continue;
+            };
+            open_or_hold(&handle, link);
+        }
+    });
+}
+
+/// Parse a Spotify URI or link and find the page showing it
+#[tauri::command]
+pub async fn resolve_spotify_link(
+    text: String,
+    state: State<'_, AppAuthState>,
+) -> Result<LinkTarget, AppError> {
+    let link = SpotifyLink::parse(&text)
+        .ok_or_else(|| AppError::InvalidInput(format!("Not a Spotify link: {}", text)))?;
+    resolve(&state, link).await
+}
+
+/// Take the link the app was started with, if any
+#[tauri::command]
+pub async fn take_startup_link(
+    hub: State<'_, LinkHub>,
+    state: State<'_, AppAuthState>,
+) -> Result<Option<LinkTarget>, AppError> {
+    let link = hub.take();
+    Ok(match link {
+        Some(link) => Some(target(&state, link).await),
+        None => None,
+    })
+}
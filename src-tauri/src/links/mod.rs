@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod parse;
+
+pub use commands::*;
+pub use parse::SpotifyLink;
@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+
+/// Kind of item a Spotify link points to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    Track,
+    Album,
+    Artist,
+    Playlist,
+    Show,
+    Episode,
+    User,
+}
+
+impl LinkKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Track => "track",
+            Self::Album => "album",
+            Self::Artist => "artist",
+            Self::Playlist => "playlist",
+            Self::Show => "show",
+            Self::Episode => "episode",
+            Self::User => "user",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        Some(match value {
+            "track" => Self::Track,
+            "album" => Self::Album,
+            "artist" => Self::Artist,
+            "playlist" => Self::Playlist,
+            "show" => Self::Show,
+            "episode" => Self::Episode,
+            "user" => Self::User,
+            _ => return None,
+        })
+    }
+}
+
+/// Item named by a Spotify URI or web link
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SpotifyLink {
+    pub kind: LinkKind,
+    pub id: String,
+}
+
+/// Hosts serving the web player, whose paths mirror URIs
+const WEB_HOSTS: [&str; 2] = ["open.spotify.com/", "play.spotify.com/"];
+
+impl SpotifyLink {
+    /// Parse a `spotify:` URI or an open.spotify.com link
+    ///
+    /// Accepts `spotify:track:<id>`, the legacy `spotify:user:<user>:playlist:<id>`,
+    /// `spotify://album/<id>` as some apps emit it, and web links with a locale
+    /// prefix, `embed/` or tracking parameters.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let segments: Vec<&str> = if let Some(rest) = text.strip_prefix("spotify:") {
+            let rest = rest.trim_start_matches('/').split(['?', '#']).next()?;
+            rest.split([':', '/']).collect()
+        } else {
+            let rest = text
+                .strip_prefix("https://")
+                .or_else(|| text.strip_prefix("http://"))
+                .unwrap_or(text);
+            let path = WEB_HOSTS.iter().find_map(|host| rest.strip_prefix(host))?;
+            let path = path.split(['?', '#']).next()?;
+            let mut segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+            if segments.first().is_some_and(|s| s.starts_with("intl-")) {
+                segments.remove(0);
+            }
+            if segments.first() == Some(&"embed") {
+                segments.remove(0);
+            }
+            segments
+        };
+
+        match segments.as_slice() {
+            ["user", _, "playlist", id] => Self::new(LinkKind::Playlist, id),
+            [kind, id] => Self::new(LinkKind::parse(kind)?, id),
+            _ => None,
+        }
+    }
+
+    fn new(kind: LinkKind, id: &str) -> Option<Self> {
+        let valid = match kind {
+            // Usernames of older accounts are not base62
+            LinkKind::User => {
+                !id.is_empty()
+                    && id
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+            }
+            _ => id.len() == 22 && id.chars().all(|c| c.is_ascii_alphanumeric()),
+        };
+        valid.then(|| Self {
+            kind,
+            id: id.to_string(),
+        })
+    }
+
+    pub fn uri(&self) -> String {
+        format!("spotify:{}:{}", self.kind.as_str(), self.id)
+    }
+
+    /// Page of the app showing the item, for kinds that have their own page
+    pub fn route(&self) -> Option<String> {
+        let page = match self.kind {
+            LinkKind::Album => "album",
+            LinkKind::Artist => "artist",
+            LinkKind::Playlist => "playlist",
+            LinkKind::User => "profile",
+            LinkKind::Track | LinkKind::Show | LinkKind::Episode => return None,
+        };
+        Some(format!("/{}/{}", page, self.id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "4uLU6hMCjMI75M1A2tKUQC";
+
+    fn parse(text: &str) -> Option<(LinkKind, String)> {
+        SpotifyLink::parse(text).map(|l| (l.kind, l.id))
+    }
+
+    #[test]
+    fn test_parses_uris() {
+        assert_eq!(
+            parse(&format!("spotify:track:{}", ID)),
+            Some((LinkKind::Track, ID.into()))
+        );
+        assert_eq!(
+            parse(&format!("spotify:user:someone:playlist:{}", ID)),
+            Some((LinkKind::Playlist, ID.into()))
+        );
+        assert_eq!(
+            parse(&format!("spotify://album/{}", ID)),
+            Some((LinkKind::Album, ID.into()))
+        );
+        assert_eq!(
+            parse("spotify:user:old.name_1"),
+            Some((LinkKind::User, "old.name_1".into()))
+        );
+        assert_eq!(parse("spotify:track:short"), None);
+        assert_eq!(parse(&format!("spotify:concert:{}", ID)), None);
+        assert_eq!(parse(&format!("spotify:track:{}:extra", ID)), None);
+    }
+
+    #[test]
+    fn test_parses_web_links() {
+        assert_eq!(
+            parse(&format!(
+                "https://open.spotify.com/intl-de/album/{}?si=abc#x",
+                ID
+            )),
+            Some((LinkKind::Album, ID.into()))
+        );
+        assert_eq!(
+            parse(&format!("open.spotify.com/embed/episode/{}", ID)),
+            Some((LinkKind::Episode, ID.into()))
+        );
+        assert_eq!(
+            parse(&format!("https://open.spotify.com/user/x/playlist/{}", ID)),
+            Some((LinkKind::Playlist, ID.into()))
+        );
+        assert_eq!(
+            parse(&format!("http://play.spotify.com/artist/{}/", ID)),
+            Some((LinkKind::Artist, ID.into()))
+        );
+        assert_eq!(
+            parse(&format!(
+                "https://example.com/open.spotify.com/track/{}",
+                ID
+            )),
+            None
+        );
+        assert_eq!(parse("https://open.spotify.com/"), None);
+    }
+
+    #[test]
+    fn test_builds_uris_and_routes() {
+        let link =
+            SpotifyLink::parse(&format!("https://open.spotify.com/playlist/{}", ID)).unwrap();
+        assert_eq!(link.uri(), format!("spotify:playlist:{}", ID));
+        assert_eq!(link.route(), Some(format!("/playlist/{}", ID)));
+        assert_eq!(
+            SpotifyLink::parse("spotify:user:me")
+                .unwrap()
+                .route()
+                .as_deref(),
+            Some("/profile/me")
+        );
+        assert_eq!(
+            SpotifyLink::parse(&format!("spotify:track:{}", ID))
+                .unwrap()
+                .route(),
+            None
+        );
+    }
+}
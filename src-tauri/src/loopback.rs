@@ -0,0 +1,84 @@
+use axum::Router;
+use std::net::{Ipv4Addr, SocketAddr};
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+
+use crate::error::AppError;
+
+/// Whether a `Host` header names the loopback server
+///
+/// Rejecting other hosts stops web pages from reaching the server through DNS
+/// rebinding.
+pub fn allowed_host(host: Option<&str>, port: u16) -> bool {
+    let Some(host) = host else {
+        return false;
+    };
+    let name = match host.rsplit_once(':') {
+        Some((name, p)) if p.parse() == Ok(port) => name,
+        Some(_) => return false,
+        None => host,
+    };
+    matches!(name, "127.0.0.1" | "localhost" | "[::1]")
+}
+
+/// A running loopback server
+pub struct LoopbackServer {
+    stop: watch::Sender<()>,
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+impl LoopbackServer {
+    /// Stop accepting requests and wait until the port is released
+    pub async fn stop(self) {
+        drop(self.stop);
+        let _ = self.task.await;
+    }
+}
+
+/// Bind to loopback and serve until stopped
+///
+/// The router is built with a receiver that closes on stop, for handlers
+/// holding connections open.
+pub async fn serve(
+    name: &str,
+    port: u16,
+    router: impl FnOnce(watch::Receiver<()>) -> Router,
+) -> Result<LoopbackServer, AppError> {
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let listener = TcpListener::bind(address)
+        .await
+        .map_err(|e| AppError::Conflict(format!("Cannot listen on port {}: {}", port, e)))?;
+
+    let (stop, stopped) = watch::channel(());
+    let mut shutdown = stopped.clone();
+    let router = router(stopped);
+    let name = name.to_string();
+    log::info!("{} listening on http://{}", name, address);
+    let task = tauri::async_runtime::spawn(async move {
+        let result = axum::serve(listener, router)
+            .with_graceful_shutdown(async move {
+                let _ = shutdown.changed().await;
+            })
+            .await;
+        if let Err(e) = result {
+            log::warn!("{} stopped: {}", name, e);
+        }
+    });
+    Ok(LoopbackServer { stop, task })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_loopback_hosts_are_allowed() {
+        assert!(allowed_host(Some("127.0.0.1:8974"), 8974));
+        assert!(allowed_host(Some("localhost:8974"), 8974));
+        assert!(allowed_host(Some("[::1]:8974"), 8974));
+        assert!(allowed_host(Some("localhost"), 8974));
+        assert!(!allowed_host(Some("localhost:80"), 8974));
+        assert!(!allowed_host(Some("evil.example:8974"), 8974));
+        assert!(!allowed_host(None, 8974));
+    }
+}
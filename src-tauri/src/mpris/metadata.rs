@@ -0,0 +1,151 @@
+use chrono::{DateTime, Utc};
+
+use crate::api::RepeatState;
+use crate::playback::events::PlaybackSnapshot;
+
+/// Track id MPRIS reserves for "no track"
+pub const NO_TRACK: &str = "/org/mpris/MediaPlayer2/TrackList/NoTrack";
+
+/// Track metadata in the shape of the MPRIS `Metadata` property
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackMetadata {
+    pub track_id: String,
+    pub length_us: i64,
+    pub title: String,
+    pub artists: Vec<String>,
+    pub album: String,
+    pub art_url: Option<String>,
+    pub url: String,
+}
+
+/// D-Bus object path identifying a Spotify track
+///
+/// Object path elements only allow `[A-Za-z0-9_]`, which base62 ids satisfy;
+/// anything else (local files...) is replaced.
+pub fn track_id(uri: &str) -> String {
+    let id: String = uri
+        .rsplit(':')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if id.is_empty() {
+        NO_TRACK.to_string()
+    } else {
+        format!("/org/spotify_rework/track/{}", id)
+    }
+}
+
+pub fn metadata(snapshot: Option<&PlaybackSnapshot>) -> Option<TrackMetadata> {
+    let track = snapshot?.track.as_ref()?;
+    Some(TrackMetadata {
+        track_id: track_id(&track.uri),
+        length_us: track.duration_ms as i64 * 1000,
+        title: track.name.clone(),
+        artists: track.artists.iter().map(|a| a.name.clone()).collect(),
+        album: track.album.name.clone(),
+        // Spotify lists images largest first
+        art_url: track.album.images.first().map(|i| i.url.clone()),
+        url: track
+            .id
+            .as_ref()
+            .map(|id| format!("https://open.spotify.com/track/{}", id))
+            .unwrap_or_else(|| track.uri.clone()),
+    })
+}
+
+pub fn playback_status(snapshot: Option<&PlaybackSnapshot>) -> &'static str {
+    match snapshot {
+        Some(s) if s.track.is_none() => "Stopped",
+        Some(s) if s.is_playing => "Playing",
+        Some(_) => "Paused",
+        None => "Stopped",
+    }
+}
+
+pub fn loop_status(repeat: RepeatState) -> &'static str {
+    match repeat {
+        RepeatState::Off => "None",
+        RepeatState::Track => "Track",
+        RepeatState::Context => "Playlist",
+    }
+}
+
+pub fn parse_loop_status(value: &str) -> Option<RepeatState> {
+    match value {
+        "None" => Some(RepeatState::Off),
+        "Track" => Some(RepeatState::Track),
+        "Playlist" => Some(RepeatState::Context),
+        _ => None,
+    }
+}
+
+/// Current position in microseconds
+pub fn position_us(snapshot: Option<&PlaybackSnapshot>, now: DateTime<Utc>) -> i64 {
+    snapshot
+        .map(|s| s.position_ms(now) as i64 * 1000)
+        .unwrap_or(0)
+}
+
+/// Volume as the 0.0 to 1.0 MPRIS scale
+pub fn volume(snapshot: Option<&PlaybackSnapshot>) -> f64 {
+    snapshot
+        .and_then(|s| s.device.volume_percent)
+        .map(|v| v as f64 / 100.0)
+        .unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(playing: bool) -> PlaybackSnapshot {
+        PlaybackSnapshot {
+            is_playing: playing,
+            progress_ms: 10_000,
+            repeat: RepeatState::Context,
+            ..PlaybackSnapshot::for_test(serde_json::json!({
+                "id": "4uLU6hMCjMI75M1A2tKUQC",
+                "uri": "spotify:track:4uLU6hMCjMI75M1A2tKUQC",
+                "name": "Song",
+                "duration_ms": 213_000,
+                "artists": [{"id": null, "name": "A", "uri": ""}, {"id": null, "name": "B", "uri": ""}],
+                "album": {"id": null, "name": "Album", "images": [{"url": "https://i.scdn.co/image/big", "height": 640, "width": 640}]}
+            }))
+        }
+    }
+
+    #[test]
+    fn test_metadata() {
+        let snapshot = snapshot(true);
+        let track = metadata(Some(&snapshot)).unwrap();
+
+        assert_eq!(
+            track.track_id,
+            "/org/spotify_rework/track/4uLU6hMCjMI75M1A2tKUQC"
+        );
+        assert_eq!(track.length_us, 213_000_000);
+        assert_eq!(track.artists, vec!["A", "B"]);
+        assert_eq!(
+            track.art_url.as_deref(),
+            Some("https://i.scdn.co/image/big")
+        );
+        assert_eq!(volume(Some(&snapshot)), 0.5);
+        assert!(metadata(None).is_none());
+    }
+
+    #[test]
+    fn test_statuses() {
+        assert_eq!(playback_status(Some(&snapshot(true))), "Playing");
+        assert_eq!(playback_status(Some(&snapshot(false))), "Paused");
+        assert_eq!(playback_status(None), "Stopped");
+        assert_eq!(loop_status(snapshot(true).repeat), "Playlist");
+        assert_eq!(parse_loop_status("Track"), Some(RepeatState::Track));
+        assert_eq!(parse_loop_status("Forever"), None);
+        assert_eq!(
+            track_id("spotify:local:a:b:c:120"),
+            "/org/spotify_rework/track/120"
+        );
+    }
+}
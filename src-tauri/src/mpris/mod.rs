@@ -0,0 +1,4 @@
+pub mod metadata;
+pub mod server;
+
+pub use server::*;
@@ -0,0 +1,465 @@
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast::error::RecvError;
+use zbus::object_server::SignalEmitter;
+use zbus::zvariant::{ObjectPath, OwnedValue, Value};
+use zbus::{connection, fdo, interface, Connection};
+
+use super::metadata;
+use crate::auth::AppAuthState;
+use crate::error::AppError;
+use crate::playback::events::{PlaybackEvent, PlaybackSnapshot};
+use crate::playback::{self, PlaybackHub, PlaybackUpdate, PlayerCommand};
+use crate::window;
+
+/// Well-known bus name the player is published under
+pub const BUS_NAME: &str = "org.mpris.MediaPlayer2.spotify_rework";
+
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+
+const IDENTITY: &str = "Spotify Rework";
+
+/// What the MPRIS service reads and controls
+///
+/// The app implementation goes through the playback hub and Web API; tests use
+/// a fake, so the D-Bus surface can be exercised on a private bus.
+pub trait MprisBackend: Send + Sync + 'static {
+    fn snapshot(&self) -> Option<PlaybackSnapshot>;
+    fn command(&self, command: PlayerCommand) -> BoxFuture<'static, Result<(), AppError>>;
+    fn raise(&self);
+}
+
+struct AppBackend {
+    app: AppHandle,
+}
+
+impl MprisBackend for AppBackend {
+    fn snapshot(&self) -> Option<PlaybackSnapshot> {
+        self.app.state::<PlaybackHub>().current()
+    }
+
+    fn command(&self, command: PlayerCommand) -> BoxFuture<'static, Result<(), AppError>> {
+        let app = self.app.clone();
+        Box::pin(async move {
+            let state = app.state::<AppAuthState>();
+            let hub = app.state::<PlaybackHub>();
+            playback::execute(&state, &hub, command).await
+        })
+    }
+
+    fn raise(&self) {
+        window::show_main_window(&self.app);
+    }
+}
+
+fn to_fdo(error: AppError) -> fdo::Error {
+    fdo::Error::Failed(error.to_string())
+}
+
+fn owned<'a>(value: impl Into<Value<'a>>) -> OwnedValue {
+    // Only plain values are stored, which never fail to convert
+    value
+        .into()
+        .try_to_owned()
+        .expect("metadata values hold no file descriptors")
+}
+
+struct Root {
+    backend: Arc<dyn MprisBackend>,
+}
+
+#[interface(name = "org.mpris.MediaPlayer2")]
+impl Root {
+    fn raise(&self) {
+        self.backend.raise();
+    }
+
+    fn quit(&self) {}
+
+    #[zbus(property)]
+    fn can_quit(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn can_raise(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn has_track_list(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn identity(&self) -> &str {
+        IDENTITY
+    }
+
+    #[zbus(property)]
+    fn supported_uri_schemes(&self) -> Vec<String> {
+        vec!["spotify".to_string()]
+    }
+
+    #[zbus(property)]
+    fn supported_mime_types(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+struct Player {
+    backend: Arc<dyn MprisBackend>,
+}
+
+impl Player {
+    async fn run(&self, command: PlayerCommand) -> fdo::Result<()> {
+        self.backend.command(command).await.map_err(to_fdo)
+    }
+}
+
+#[interface(name = "org.mpris.MediaPlayer2.Player")]
+impl Player {
+    async fn play(&self) -> fdo::Result<()> {
+        self.run(PlayerCommand::Play).await
+    }
+
+    async fn pause(&self) -> fdo::Result<()> {
+        self.run(PlayerCommand::Pause).await
+    }
+
+    async fn play_pause(&self) -> fdo::Result<()> {
+        self.run(PlayerCommand::TogglePlay).await
+    }
+
+    async fn stop(&self) -> fdo::Result<()> {
+        self.run(PlayerCommand::Pause).await
+    }
+
+    async fn next(&self) -> fdo::Result<()> {
+        self.run(PlayerCommand::Next).await
+    }
+
+    async fn previous(&self) -> fdo::Result<()> {
+        self.run(PlayerCommand::Previous).await
+    }
+
+    /// Seek by an offset in microseconds
+    async fn seek(&self, offset: i64) -> fdo::Result<()> {
+        self.run(PlayerCommand::SeekBy {
+            offset_ms: offset / 1000,
+        })
+        .await
+    }
+
+    /// Seek to an absolute position, ignored when the track is no longer current
+    async fn set_position(&self, track_id: ObjectPath<'_>, position: i64) -> fdo::Result<()> {
+        let current = metadata::metadata(self.backend.snapshot().as_ref());
+        match current {
+            Some(track) if track.track_id == track_id.as_str() && position >= 0 => {
+                self.run(PlayerCommand::Seek {
+                    position_ms: position as u64 / 1000,
+                })
+                .await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn open_uri(&self, uri: String) -> fdo::Result<()> {
+        self.run(PlayerCommand::PlayUri { uri }).await
+    }
+
+    #[zbus(signal)]
+    async fn seeked(emitter: &SignalEmitter<'_>, position: i64) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn playback_status(&self) -> &str {
+        metadata::playback_status(self.backend.snapshot().as_ref())
+    }
+
+    #[zbus(property)]
+    fn loop_status(&self) -> &str {
+        self.backend
+            .snapshot()
+            .map(|s| metadata::loop_status(s.repeat))
+            .unwrap_or("None")
+    }
+
+    #[zbus(property)]
+    async fn set_loop_status(&mut self, value: String) -> zbus::Result<()> {
+        let repeat = metadata::parse_loop_status(&value)
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("Unknown loop status {}", value)))?;
+        Ok(self.run(PlayerCommand::SetRepeat { repeat }).await?)
+    }
+
+    #[zbus(property)]
+    fn rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn set_rate(&mut self, _rate: f64) {}
+
+    #[zbus(property)]
+    fn minimum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn maximum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn shuffle(&self) -> bool {
+        self.backend.snapshot().map(|s| s.shuffle).unwrap_or(false)
+    }
+
+    #[zbus(property)]
+    async fn set_shuffle(&mut self, shuffle: bool) -> zbus::Result<()> {
+        Ok(self.run(PlayerCommand::SetShuffle { shuffle }).await?)
+    }
+
+    #[zbus(property)]
+    fn metadata(&self) -> HashMap<String, OwnedValue> {
+        let mut map = HashMap::new();
+        let Some(track) = metadata::metadata(self.backend.snapshot().as_ref()) else {
+            if let Ok(path) = ObjectPath::try_from(metadata::NO_TRACK) {
+                map.insert("mpris:trackid".to_string(), owned(path));
+            }
+            return map;
+        };
+
+        if let Ok(path) = ObjectPath::try_from(track.track_id.as_str()) {
+            map.insert("mpris:trackid".to_string(), owned(path));
+        }
+        map.insert("mpris:length".to_string(), owned(track.length_us));
+        map.insert("xesam:title".to_string(), owned(track.title));
+        map.insert("xesam:artist".to_string(), owned(track.artists));
+        map.insert("xesam:album".to_string(), owned(track.album));
+        map.insert("xesam:url".to_string(), owned(track.url));
+        if let Some(art) = track.art_url {
+            map.insert("mpris:artUrl".to_string(), owned(art));
+        }
+        map
+    }
+
+    #[zbus(property)]
+    fn volume(&self) -> f64 {
+        metadata::volume(self.backend.snapshot().as_ref())
+    }
+
+    #[zbus(property)]
+    async fn set_volume(&mut self, volume: f64) -> zbus::Result<()> {
+        let percent = (volume.clamp(0.0, 1.0) * 100.0).round() as u32;
+        Ok(self.run(PlayerCommand::SetVolume { percent }).await?)
+    }
+
+    /// Polled by clients; position changes are not signalled except for seeks
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn position(&self) -> i64 {
+        metadata::position_us(self.backend.snapshot().as_ref(), chrono::Utc::now())
+    }
+
+    #[zbus(property)]
+    fn can_go_next(&self) -> bool {
+        self.backend.snapshot().is_some()
+    }
+
+    #[zbus(property)]
+    fn can_go_previous(&self) -> bool {
+        self.backend.snapshot().is_some()
+    }
+
+    #[zbus(property)]
+    fn can_play(&self) -> bool {
+        self.backend.snapshot().is_some()
+    }
+
+    #[zbus(property)]
+    fn can_pause(&self) -> bool {
+        self.backend.snapshot().is_some()
+    }
+
+    #[zbus(property)]
+    fn can_seek(&self) -> bool {
+        self.backend
+            .snapshot()
+            .map(|s| s.track.is_some())
+            .unwrap_or(false)
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn can_control(&self) -> bool {
+        true
+    }
+}
+
+/// Publish the player on a bus: the session bus, or the given address
+pub async fn serve(
+    backend: Arc<dyn MprisBackend>,
+    address: Option<&str>,
+) -> zbus::Result<Connection> {
+    let builder = match address {
+        Some(address) => connection::Builder::address(address)?,
+        None => connection::Builder::session()?,
+    };
+    builder
+        .name(BUS_NAME)?
+        .serve_at(
+            OBJECT_PATH,
+            Root {
+                backend: backend.clone(),
+            },
+        )?
+        .serve_at(OBJECT_PATH, Player { backend })?
+        .build()
+        .await
+}
+
+/// Signal the property changes and seeks of a playback poll
+pub async fn publish(connection: &Connection, update: &PlaybackUpdate) -> zbus::Result<()> {
+    if update.events.is_empty() {
+        return Ok(());
+    }
+
+    let iface_ref = connection
+        .object_server()
+        .interface::<_, Player>(OBJECT_PATH)
+        .await?;
+    let emitter = iface_ref.signal_emitter();
+    let player = iface_ref.get().await;
+
+    player.playback_status_changed(emitter).await?;
+    player.metadata_changed(emitter).await?;
+    player.volume_changed(emitter).await?;
+    player.shuffle_changed(emitter).await?;
+    player.loop_status_changed(emitter).await?;
+    player.can_seek_changed(emitter).await?;
+
+    for event in &update.events {
+        if let PlaybackEvent::Seeked { to_ms, .. } = event {
+            Player::seeked(emitter, *to_ms as i64 * 1000).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Start the MPRIS service on the session bus and keep it in sync with playback
+pub fn spawn_mpris(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let backend = Arc::new(AppBackend { app: app.clone() });
+        let connection = match serve(backend, None).await {
+            Ok(connection) => connection,
+            Err(e) => {
+                log::warn!("MPRIS service unavailable: {}", e);
+                return;
+            }
+        };
+
+        let mut updates = app.state::<PlaybackHub>().subscribe();
+        loop {
+            let update = match updates.recv().await {
+                Ok(update) => update,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+            if let Err(e) = publish(&connection, &update).await {
+                log::warn!("Failed to publish MPRIS changes: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct FakeBackend {
+        commands: Mutex<Vec<PlayerCommand>>,
+    }
+
+    impl MprisBackend for FakeBackend {
+        fn snapshot(&self) -> Option<PlaybackSnapshot> {
+            None
+        }
+
+        fn command(&self, command: PlayerCommand) -> BoxFuture<'static, Result<(), AppError>> {
+            self.commands.lock().unwrap().push(command);
+            Box::pin(async { Ok(()) })
+        }
+
+        fn raise(&self) {}
+    }
+
+    /// A private bus, so tests never touch the desktop session
+    struct PrivateBus(Child);
+
+    impl PrivateBus {
+        fn start() -> Option<(Self, String)> {
+            let mut child = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .spawn()
+                .ok()?;
+            let mut address = String::new();
+            BufReader::new(child.stdout.take()?)
+                .read_line(&mut address)
+                .ok()?;
+            Some((Self(child), address.trim().to_string()))
+        }
+    }
+
+    impl Drop for PrivateBus {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_player_on_private_bus() {
+        let Some((_bus, address)) = PrivateBus::start() else {
+            eprintln!("dbus-daemon not available, skipping");
+            return;
+        };
+
+        let backend = Arc::new(FakeBackend::default());
+        let _server = serve(backend.clone(), Some(&address)).await.unwrap();
+
+        let client = connection::Builder::address(address.as_str())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let proxy = zbus::Proxy::new(
+            &client,
+            BUS_NAME,
+            OBJECT_PATH,
+            "org.mpris.MediaPlayer2.Player",
+        )
+        .await
+        .unwrap();
+
+        let status: String = proxy.get_property("PlaybackStatus").await.unwrap();
+        assert_eq!(status, "Stopped");
+
+        proxy.call_method("PlayPause", &()).await.unwrap();
+        proxy.call_method("Seek", &(5_000_000i64,)).await.unwrap();
+        proxy.set_property("Volume", 0.5f64).await.unwrap();
+
+        assert_eq!(
+            *backend.commands.lock().unwrap(),
+            vec![
+                PlayerCommand::TogglePlay,
+                PlayerCommand::SeekBy { offset_ms: 5000 },
+                PlayerCommand::SetVolume { percent: 50 },
+            ]
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::model;
+use crate::api;
+use crate::error::AppError;
+use crate::store;
+
+/// Files kept in the artwork cache before the oldest are removed
+const MAX_CACHED: usize = 200;
+
+fn cache_dir() -> Result<PathBuf, AppError> {
+    let mut path = store::data_dir()?;
+    path.push("artwork");
+    Ok(path)
+}
+
+/// Local copy of an artwork URL, downloaded on first use
+pub async fn cached(url: &str) -> Result<PathBuf, AppError> {
+    let dir = cache_dir()?;
+    let path = dir.join(model::artwork_file_name(url));
+    if path.exists() {
+        return Ok(path);
+    }
+
+    let bytes = api::download_image(url).await?;
+
+    store::write_file(&path, &bytes)?;
+    if let Err(e) = prune(&dir) {
+        log::warn!("Failed to prune the artwork cache: {}", e);
+    }
+    Ok(path)
+}
+
+/// Remove the least recently written files beyond `MAX_CACHED`
+fn prune(dir: &Path) -> std::io::Result<()> {
+    let mut files = fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let modified = entry.metadata().and_then(|m| m.modified()).ok()?;
+            Some((modified, entry.path()))
+        })
+        .collect::<Vec<_>>();
+    if files.len() <= MAX_CACHED {
+        return Ok(());
+    }
+
+    files.sort();
+    for (_, path) in &files[..files.len() - MAX_CACHED] {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
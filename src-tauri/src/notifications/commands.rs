@@ -0,0 +1,128 @@
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::broadcast::error::RecvError;
+
+use super::artwork;
+use super::desktop::Notifier;
+use super::model::{self, NotificationAction, NotificationSettings, TrackNotification};
+use crate::auth::{self, AppAuthState};
+use crate::error::AppError;
+use crate::playback::events::PlaybackEvent;
+use crate::playback::{self, PlaybackHub, PlayerCommand};
+use crate::store;
+
+const NOTIFICATIONS_FILE: &str = "notifications.json";
+
+fn load() -> Result<NotificationSettings, AppError> {
+    Ok(store::load_json(&store::app_file(NOTIFICATIONS_FILE)?)?.unwrap_or_default())
+}
+
+fn save(settings: &NotificationSettings) -> Result<(), AppError> {
+    store::save_json(&store::app_file(NOTIFICATIONS_FILE)?, settings)
+}
+
+fn main_window_focused(app: &AppHandle) -> bool {
+    app.get_webview_window("main")
+        .map(|w| w.is_visible().unwrap_or(false) && w.is_focused().unwrap_or(false))
+        .unwrap_or(false)
+}
+
+/// Skip or like from a notification button
+fn handle_action(app: &AppHandle, action: NotificationAction) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppAuthState>();
+        let hub = app.state::<PlaybackHub>();
+        let result = match action {
+            NotificationAction::Skip => playback::execute(&state, &hub, PlayerCommand::Next).await,
+            NotificationAction::Like => {
+                playback::like_current_track(&state, &hub)
+                    .await
+                    .map(|track| {
+                        let _ = app.emit("library:track-saved", &track);
+                    })
+            }
+        };
+        if let Err(e) = result {
+            log::warn!("Notification action failed: {}", e);
+        }
+    });
+}
+
+/// Start the background job that announces track changes
+pub fn spawn_notification_job(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let app_name = app.package_info().name.clone();
+        let handle = app.clone();
+        let notifier = match Notifier::connect(&app_name, move |action| {
+            handle_action(&handle, action)
+        })
+        .await
+        {
+            Ok(notifier) => notifier,
+            Err(e) => {
+                log::warn!("Desktop notifications unavailable: {}", e);
+                return;
+            }
+        };
+
+        let mut updates = app.state::<PlaybackHub>().subscribe();
+        // Context of the previous track, `None` until a track has been seen
+        let mut previous_context: Option<Option<String>> = None;
+        loop {
+            let update = match updates.recv().await {
+                Ok(update) => update,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+            let track = update.events.into_iter().find_map(|e| match e {
+                PlaybackEvent::TrackChanged { track, .. } => track.map(|t| *t),
+                _ => None,
+            });
+            let (Some(track), Some(snapshot)) = (track, update.snapshot) else {
+                continue;
+            };
+
+            let context = snapshot.context_uri;
+            let previous = previous_context.replace(context.clone());
+            if !snapshot.is_playing || auth::current_user(&app.state::<AppAuthState>()).is_none() {
+                continue;
+            }
+            let settings = load().unwrap_or_default();
+            if !model::should_notify(
+                &settings,
+                main_window_focused(&app),
+                previous.as_ref().map(|c| c.as_deref()),
+                context.as_deref(),
+            ) {
+                continue;
+            }
+
+            let notification = TrackNotification::new(&track);
+            let artwork = match notification.artwork_url.as_deref() {
+                Some(url) => artwork::cached(url)
+                    .await
+                    .inspect_err(|e| log::warn!("Failed to fetch artwork: {}", e))
+                    .ok(),
+                None => None,
+            };
+            if let Err(e) = notifier
+                .show(&notification, artwork.as_deref(), settings.show_actions)
+                .await
+            {
+                log::warn!("Failed to show notification: {}", e);
+            }
+        }
+    });
+}
+
+/// Get the track notification settings
+#[tauri::command]
+pub fn get_notification_settings() -> Result<NotificationSettings, AppError> {
+    load()
+}
+
+/// Replace the track notification settings
+#[tauri::command]
+pub fn set_notification_settings(settings: NotificationSettings) -> Result<(), AppError> {
+    save(&settings)
+}
@@ -0,0 +1,224 @@
+use super::model::{NotificationAction, TrackNotification};
+
+pub use platform::Notifier;
+
+/// Freedesktop notifications over D-Bus
+///
+/// Talked to directly rather than through notify-rust so action buttons can be
+/// received on the connection that sent the notification, which is where
+/// GNOME delivers `ActionInvoked`.
+#[cfg(target_os = "linux")]
+mod platform {
+    use futures::StreamExt;
+    use std::collections::HashMap;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use zbus::zvariant::Value;
+    use zbus::{proxy, Connection};
+
+    use super::{NotificationAction, TrackNotification};
+    use crate::notifications::model;
+
+    /// Milliseconds a notification stays up; -1 leaves it to the server
+    const EXPIRE_TIMEOUT: i32 = -1;
+
+    #[proxy(
+        interface = "org.freedesktop.Notifications",
+        default_service = "org.freedesktop.Notifications",
+        default_path = "/org/freedesktop/Notifications"
+    )]
+    trait Notifications {
+        #[allow(clippy::too_many_arguments)]
+        fn notify(
+            &self,
+            app_name: &str,
+            replaces_id: u32,
+            app_icon: &str,
+            summary: &str,
+            body: &str,
+            actions: &[&str],
+            hints: HashMap<&str, Value<'_>>,
+            expire_timeout: i32,
+        ) -> zbus::Result<u32>;
+
+        fn get_capabilities(&self) -> zbus::Result<Vec<String>>;
+
+        #[zbus(signal)]
+        fn action_invoked(&self, id: u32, action_key: String) -> zbus::Result<()>;
+    }
+
+    pub struct Notifier {
+        app_name: String,
+        proxy: NotificationsProxy<'static>,
+        supports_actions: bool,
+        supports_markup: bool,
+        /// Id of the notification on screen; each one replaces the previous
+        last_id: Arc<AtomicU32>,
+    }
+
+    impl Notifier {
+        pub async fn connect(
+            app_name: &str,
+            on_action: impl Fn(NotificationAction) + Send + Sync + 'static,
+        ) -> Result<Self, String> {
+            let connection = Connection::session().await.map_err(|e| e.to_string())?;
+            let proxy = NotificationsProxy::new(&connection)
+                .await
+                .map_err(|e| e.to_string())?;
+            let capabilities = proxy.get_capabilities().await.unwrap_or_default();
+            let last_id = Arc::new(AtomicU32::new(0));
+
+            let mut invoked = proxy
+                .receive_action_invoked()
+                .await
+                .map_err(|e| e.to_string())?;
+            let current = last_id.clone();
+            tauri::async_runtime::spawn(async move {
+                while let Some(signal) = invoked.next().await {
+                    let Ok(args) = signal.args() else {
+                        continue;
+                    };
+                    // Buttons of an older, replaced notification refer to another track
+                    if args.id != current.load(Ordering::SeqCst) {
+                        continue;
+                    }
+                    if let Some(action) = NotificationAction::parse(&args.action_key) {
+                        on_action(action);
+                    }
+                }
+            });
+
+            Ok(Self {
+                app_name: app_name.to_string(),
+                proxy,
+                supports_actions: capabilities.iter().any(|c| c == "actions"),
+                supports_markup: capabilities.iter().any(|c| c == "body-markup"),
+                last_id,
+            })
+        }
+
+        pub async fn show(
+            &self,
+            notification: &TrackNotification,
+            artwork: Option<&Path>,
+            actions: bool,
+        ) -> Result<(), String> {
+            let body = if self.supports_markup {
+                model::escape_markup(&notification.body)
+            } else {
+                notification.body.clone()
+            };
+            let icon = artwork
+                .map(|p| format!("file://{}", p.display()))
+                .unwrap_or_default();
+
+            let mut hints = HashMap::new();
+            hints.insert("transient", Value::from(true));
+            if !icon.is_empty() {
+                hints.insert("image-path", Value::from(icon.as_str()));
+            }
+
+            let buttons: Vec<&str> = if actions && self.supports_actions {
+                NotificationAction::ALL
+                    .iter()
+                    .flat_map(|a| [a.key(), a.label()])
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+            let id = self
+                .proxy
+                .notify(
+                    &self.app_name,
+                    self.last_id.load(Ordering::SeqCst),
+                    &icon,
+                    &notification.title,
+                    &body,
+                    &buttons,
+                    hints,
+                    EXPIRE_TIMEOUT,
+                )
+                .await
+                .map_err(|e| e.to_string())?;
+            self.last_id.store(id, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+}
+
+/// Windows toasts and macOS notification center, without action buttons
+#[cfg(any(windows, target_os = "macos"))]
+mod platform {
+    use std::path::Path;
+
+    use super::{NotificationAction, TrackNotification};
+
+    pub struct Notifier {
+        app_name: String,
+    }
+
+    impl Notifier {
+        pub async fn connect(
+            app_name: &str,
+            _on_action: impl Fn(NotificationAction) + Send + Sync + 'static,
+        ) -> Result<Self, String> {
+            Ok(Self {
+                app_name: app_name.to_string(),
+            })
+        }
+
+        pub async fn show(
+            &self,
+            notification: &TrackNotification,
+            artwork: Option<&Path>,
+            _actions: bool,
+        ) -> Result<(), String> {
+            let mut toast = notify_rust::Notification::new();
+            toast
+                .appname(&self.app_name)
+                .summary(&notification.title)
+                .body(&notification.body);
+            #[cfg(windows)]
+            if let Some(path) = artwork {
+                toast.image_path(&path.to_string_lossy());
+            }
+            #[cfg(not(windows))]
+            let _ = artwork;
+
+            tauri::async_runtime::spawn_blocking(move || toast.show().map(|_| ()))
+                .await
+                .map_err(|e| e.to_string())?
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Other desktops, such as the BSDs, get no notifications
+#[cfg(not(any(target_os = "linux", windows, target_os = "macos")))]
+mod platform {
+    use std::path::Path;
+
+    use super::{NotificationAction, TrackNotification};
+
+    pub struct Notifier;
+
+    impl Notifier {
+        pub async fn connect(
+            _app_name: &str,
+            _on_action: impl Fn(NotificationAction) + Send + Sync + 'static,
+        ) -> Result<Self, String> {
+            Err("not supported on this platform".into())
+        }
+
+        pub async fn show(
+            &self,
+            _notification: &TrackNotification,
+            _artwork: Option<&Path>,
+            _actions: bool,
+        ) -> Result<(), String> {
+            Ok(())
+        }
+    }
+}
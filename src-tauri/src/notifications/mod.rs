@@ -0,0 +1,6 @@
+pub mod artwork;
+pub mod commands;
+pub mod desktop;
+pub mod model;
+
+pub use commands::*;
@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+
+use crate::api::Track;
+use crate::auth::SpotifyImage;
+use crate::store;
+
+/// Artwork edge length wanted for notifications, in pixels
+const ARTWORK_SIZE: u32 = 300;
+
+/// When a track change produces a notification
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub enabled: bool,
+    /// Stay quiet while the main window has focus
+    pub only_when_unfocused: bool,
+    /// Notify for the first track of a new album or playlist only
+    pub only_on_context_change: bool,
+    /// Offer skip and like buttons where the platform supports them
+    pub show_actions: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            only_when_unfocused: true,
+            only_on_context_change: false,
+            show_actions: true,
+        }
+    }
+}
+
+/// Button of a track notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationAction {
+    Skip,
+    Like,
+}
+
+impl NotificationAction {
+    pub const ALL: [NotificationAction; 2] = [NotificationAction::Skip, NotificationAction::Like];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            NotificationAction::Skip => "skip",
+            NotificationAction::Like => "like",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            NotificationAction::Skip => "Skip",
+            NotificationAction::Like => "Like",
+        }
+    }
+
+    pub fn parse(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.key() == key)
+    }
+}
+
+/// Text and artwork of a track notification
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackNotification {
+    pub title: String,
+    pub body: String,
+    pub artwork_url: Option<String>,
+}
+
+impl TrackNotification {
+    pub fn new(track: &Track) -> Self {
+        let artists = track.artist_names();
+        let body = match (artists.is_empty(), track.album.name.is_empty()) {
+            (false, false) => format!("{}\n{}", artists, track.album.name),
+            (false, true) => artists,
+            (true, _) => track.album.name.clone(),
+        };
+
+        Self {
+            title: track.name.clone(),
+            body,
+            artwork_url: artwork(&track.album.images).map(|i| i.url.clone()),
+        }
+    }
+}
+
+/// Smallest image at least `ARTWORK_SIZE` wide, or the largest one available
+fn artwork(images: &[SpotifyImage]) -> Option<&SpotifyImage> {
+    let width = |i: &SpotifyImage| i.width.unwrap_or(0);
+    images
+        .iter()
+        .filter(|i| width(i) >= ARTWORK_SIZE)
+        .min_by_key(|i| width(i))
+        .or_else(|| images.iter().max_by_key(|i| width(i)))
+}
+
+/// Cache file name of an artwork URL
+///
+/// Spotify image URLs end with a content hash, which makes a stable name.
+pub fn artwork_file_name(url: &str) -> String {
+    let name = url
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default();
+    format!("{}.jpg", store::sanitize_file_name(name))
+}
+
+/// Whether a track change should be announced
+///
+/// `previous_context` is the context of the previous track, `None` for the first
+/// track seen since launch.
+pub fn should_notify(
+    settings: &NotificationSettings,
+    focused: bool,
+    previous_context: Option<Option<&str>>,
+    context: Option<&str>,
+) -> bool {
+    if !settings.enabled || (settings.only_when_unfocused && focused) {
+        return false;
+    }
+    !settings.only_on_context_change || previous_context != Some(context)
+}
+
+/// Escape text for servers that render the body as markup
+pub fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(images: serde_json::Value) -> Track {
+        serde_json::from_value(serde_json::json!({
+            "id": "1", "uri": "spotify:track:1", "name": "Song",
+            "artists": [{"id": null, "name": "A", "uri": ""}, {"id": null, "name": "B", "uri": ""}],
+            "album": {"id": null, "name": "Album", "images": images}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_notification_picks_artwork_closest_to_size() {
+        let notification = TrackNotification::new(&track(serde_json::json!([
+            {"url": "https://i.scdn.co/image/big", "height": 640, "width": 640},
+            {"url": "https://i.scdn.co/image/mid", "height": 300, "width": 300},
+            {"url": "https://i.scdn.co/image/small", "height": 64, "width": 64}
+        ])));
+        assert_eq!(notification.title, "Song");
+        assert_eq!(notification.body, "A, B\nAlbum");
+        assert_eq!(
+            notification.artwork_url.as_deref(),
+            Some("https://i.scdn.co/image/mid")
+        );
+
+        let small = TrackNotification::new(&track(serde_json::json!([
+            {"url": "https://i.scdn.co/image/tiny", "height": 32, "width": 32},
+            {"url": "https://i.scdn.co/image/small", "height": 64, "width": 64}
+        ])));
+        assert_eq!(
+            small.artwork_url.as_deref(),
+            Some("https://i.scdn.co/image/small")
+        );
+        assert_eq!(
+            TrackNotification::new(&track(serde_json::json!([]))).artwork_url,
+            None
+        );
+    }
+
+    #[test]
+    fn test_artwork_names_are_file_safe() {
+        assert_eq!(
+            artwork_file_name("https://i.scdn.co/image/ab67616d00001e02ff9ca10b"),
+            "ab67616d00001e02ff9ca10b.jpg"
+        );
+        assert_eq!(artwork_file_name("https://x.y/a?b=c:d"), "a_b_c_d.jpg");
+    }
+
+    #[test]
+    fn test_should_notify_respects_settings() {
+        let defaults = NotificationSettings::default();
+        assert!(should_notify(&defaults, false, Some(Some("a")), Some("a")));
+        assert!(!should_notify(&defaults, true, None, Some("a")));
+
+        let disabled = NotificationSettings {
+            enabled: false,
+            ..defaults.clone()
+        };
+        assert!(!should_notify(&disabled, false, None, None));
+
+        let context_only = NotificationSettings {
+            only_on_context_change: true,
+            only_when_unfocused: false,
+            ..defaults
+        };
+        assert!(should_notify(&context_only, true, None, Some("a")));
+        assert!(!should_notify(
+            &context_only,
+            true,
+            Some(Some("a")),
+            Some("a")
+        ));
+        assert!(should_notify(
+            &context_only,
+            true,
+            Some(Some("a")),
+            Some("b")
+        ));
+        assert!(should_notify(&context_only, true, Some(Some("a")), None));
+        assert!(!should_notify(&context_only, true, Some(None), None));
+    }
+
+    #[test]
+    fn test_actions_round_trip() {
+        for action in NotificationAction::ALL {
+            assert_eq!(NotificationAction::parse(action.key()), Some(action));
+        }
+        assert_eq!(NotificationAction::parse("default"), None);
+        assert_eq!(escape_markup("Tom & <Jerry>"), "Tom &amp; &lt;Jerry&gt;");
+    }
+}
@@ -0,0 +1,173 @@
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::broadcast::error::RecvError;
+
+use super::model::{self, NowPlaying, OutputSettings};
+use super::overlay;
+use crate::api;
+use crate::error::AppError;
+use crate::loopback::LoopbackServer;
+use crate::playback::events::{PlaybackEvent, PlaybackSnapshot};
+use crate::playback::PlaybackHub;
+use crate::store;
+
+const OUTPUT_FILE: &str = "now_playing.json";
+
+/// The overlay server and what was last written to the cover file
+#[derive(Default)]
+pub struct NowPlayingHub {
+    overlay: tokio::sync::Mutex<Option<LoopbackServer>>,
+    /// Cover file and the URL it holds, to skip downloading it again on pause/resume
+    cover: Mutex<Option<(PathBuf, String)>>,
+}
+
+/// Settings and where the overlay can be reached, when running
+#[derive(Debug, Clone, Serialize)]
+pub struct NowPlayingStatus {
+    #[serde(flatten)]
+    pub settings: OutputSettings,
+    pub overlay_url: Option<String>,
+}
+
+fn load() -> Result<OutputSettings, AppError> {
+    Ok(store::load_json(&store::app_file(OUTPUT_FILE)?)?.unwrap_or_default())
+}
+
+fn save(settings: &OutputSettings) -> Result<(), AppError> {
+    store::save_json(&store::app_file(OUTPUT_FILE)?, settings)
+}
+
+/// Write the text, JSON and cover files for the current playback
+async fn write_files(
+    hub: &NowPlayingHub,
+    settings: &OutputSettings,
+    snapshot: Option<&PlaybackSnapshot>,
+) -> Result<(), AppError> {
+    let now_playing = snapshot
+        .and_then(|s| NowPlaying::new(s, chrono::Utc::now()))
+        .filter(|n| n.is_playing || !settings.clear_when_paused);
+
+    if let Some(path) = &settings.text_path {
+        let text = now_playing
+            .as_ref()
+            .map(|n| model::render(&settings.text_template, n))
+            .unwrap_or_default();
+        store::write_file(path, text.as_bytes())?;
+    }
+    if let Some(path) = &settings.json_path {
+        store::save_json(path, &now_playing)?;
+    }
+
+    if let Some(path) = &settings.cover_path {
+        let url = now_playing.as_ref().and_then(|n| n.cover_url.clone());
+        let written = hub.cover.lock().unwrap().clone();
+        let current = url.map(|url| (path.clone(), url));
+        if current != written {
+            match &current {
+                Some((path, url)) => store::write_file(path, &api::download_image(url).await?)?,
+                None if path.exists() => fs::remove_file(path).map_err(|e| {
+                    AppError::Storage(format!("Failed to remove {:?}: {}", path, e))
+                })?,
+                None => {}
+            }
+            *hub.cover.lock().unwrap() = current;
+        }
+    }
+    Ok(())
+}
+
+/// Stop the running overlay, then start it again if the settings enable it
+async fn apply_overlay(
+    app: &AppHandle,
+    hub: &NowPlayingHub,
+    settings: &OutputSettings,
+) -> Result<Option<String>, AppError> {
+    let mut server = hub.overlay.lock().await;
+    if let Some(running) = server.take() {
+        running.stop().await;
+    }
+    if !settings.overlay_enabled {
+        return Ok(None);
+    }
+    *server = Some(overlay::start(app.clone(), settings.overlay_port).await?);
+    Ok(Some(format!("http://127.0.0.1:{}/", settings.overlay_port)))
+}
+
+/// Start the overlay when enabled and keep the files in sync with playback
+pub fn spawn_now_playing_job(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let hub = app.state::<NowPlayingHub>();
+        let settings = load().unwrap_or_else(|e| {
+            log::warn!("Failed to load now-playing settings: {}", e);
+            OutputSettings::default()
+        });
+        if let Err(e) = apply_overlay(&app, &hub, &settings).await {
+            log::warn!("Failed to start the now-playing overlay: {}", e);
+        }
+
+        let mut updates = app.state::<PlaybackHub>().subscribe();
+        loop {
+            let update = match updates.recv().await {
+                Ok(update) => update,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+            let changed = update.events.iter().any(|e| {
+                matches!(
+                    e,
+                    PlaybackEvent::TrackChanged { .. }
+                        | PlaybackEvent::Paused { .. }
+                        | PlaybackEvent::Resumed { .. }
+                )
+            });
+            if !changed {
+                continue;
+            }
+
+            let settings = load().unwrap_or_default();
+            if !settings.files_enabled {
+                continue;
+            }
+            if let Err(e) = write_files(&hub, &settings, update.snapshot.as_ref()).await {
+                log::warn!("Failed to write now-playing files: {}", e);
+            }
+        }
+    });
+}
+
+/// Get the now-playing output settings
+#[tauri::command]
+pub async fn get_now_playing_settings(
+    hub: State<'_, NowPlayingHub>,
+) -> Result<NowPlayingStatus, AppError> {
+    let settings = load()?;
+    let running = hub.overlay.lock().await.is_some();
+    Ok(NowPlayingStatus {
+        overlay_url: running.then(|| format!("http://127.0.0.1:{}/", settings.overlay_port)),
+        settings,
+    })
+}
+
+/// Replace the output settings, restarting the overlay and rewriting the files
+#[tauri::command]
+pub async fn set_now_playing_settings(
+    app: AppHandle,
+    settings: OutputSettings,
+    hub: State<'_, NowPlayingHub>,
+    playback: State<'_, PlaybackHub>,
+) -> Result<NowPlayingStatus, AppError> {
+    settings.validate()?;
+    save(&settings)?;
+
+    let overlay_url = apply_overlay(&app, &hub, &settings).await?;
+    if settings.files_enabled {
+        write_files(&hub, &settings, playback.current().as_ref()).await?;
+    }
+    Ok(NowPlayingStatus {
+        settings,
+        overlay_url,
+    })
+}
@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod model;
+pub mod overlay;
+
+pub use commands::*;
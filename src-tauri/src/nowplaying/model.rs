@@ -0,0 +1,223 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::error::AppError;
+use crate::playback::events::PlaybackSnapshot;
+
+pub const DEFAULT_OVERLAY_PORT: u16 = 8975;
+
+/// Where and how the current track is published for streaming software
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputSettings {
+    /// Write the files below on every track change
+    pub files_enabled: bool,
+    pub text_path: Option<PathBuf>,
+    /// Text file contents, see [`render`] for placeholders
+    pub text_template: String,
+    pub json_path: Option<PathBuf>,
+    pub cover_path: Option<PathBuf>,
+    /// Empty the files while paused so scenes can hide the widget
+    pub clear_when_paused: bool,
+    pub overlay_enabled: bool,
+    pub overlay_port: u16,
+}
+
+impl Default for OutputSettings {
+    fn default() -> Self {
+        Self {
+            files_enabled: false,
+            text_path: None,
+            text_template: "{artist} - {title}".into(),
+            json_path: None,
+            cover_path: None,
+            clear_when_paused: true,
+            overlay_enabled: false,
+            overlay_port: DEFAULT_OVERLAY_PORT,
+        }
+    }
+}
+
+impl OutputSettings {
+    pub fn validate(&self) -> Result<(), AppError> {
+        let paths: Vec<&PathBuf> = [&self.text_path, &self.json_path, &self.cover_path]
+            .into_iter()
+            .flatten()
+            .collect();
+        if let Some(path) = paths.iter().find(|p| !p.is_absolute()) {
+            return Err(AppError::InvalidInput(format!(
+                "Output path must be absolute: {:?}",
+                path
+            )));
+        }
+        if paths
+            .iter()
+            .enumerate()
+            .any(|(i, p)| paths[..i].contains(p))
+        {
+            return Err(AppError::InvalidInput(
+                "Each output needs its own file".into(),
+            ));
+        }
+        if self.overlay_port < 1024 {
+            return Err(AppError::InvalidInput(
+                "Port must be between 1024 and 65535".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The playing track as published to files and the overlay
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NowPlaying {
+    pub title: String,
+    /// Every artist, comma separated
+    pub artist: String,
+    pub artists: Vec<String>,
+    pub album: String,
+    pub uri: String,
+    /// open.spotify.com link, absent for local files
+    pub url: Option<String>,
+    /// Largest album image
+    pub cover_url: Option<String>,
+    pub duration_ms: u64,
+    /// Estimated position when this was built
+    pub progress_ms: u64,
+    pub is_playing: bool,
+    pub device: String,
+}
+
+impl NowPlaying {
+    pub fn new(snapshot: &PlaybackSnapshot, at: DateTime<Utc>) -> Option<Self> {
+        let track = snapshot.track.as_ref()?;
+        let artists: Vec<String> = track.artists.iter().map(|a| a.name.clone()).collect();
+        Some(Self {
+            title: track.name.clone(),
+            artist: artists.join(", "),
+            artists,
+            album: track.album.name.clone(),
+            uri: track.uri.clone(),
+            url: track
+                .id
+                .as_ref()
+                .map(|id| format!("https://open.spotify.com/track/{}", id)),
+            cover_url: track.album.images.first().map(|i| i.url.clone()),
+            duration_ms: track.duration_ms,
+            progress_ms: snapshot.position_ms(at),
+            is_playing: snapshot.is_playing,
+            device: snapshot.device.name.clone(),
+        })
+    }
+}
+
+/// `m:ss`, or `h:mm:ss` past an hour
+fn format_duration(ms: u64) -> String {
+    let seconds = ms / 1000;
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/// Fill a text template
+///
+/// Known placeholders are `{title}`, `{artist}`, `{album}`, `{duration}`,
+/// `{uri}`, `{url}` and `{device}`; anything else is kept as written.
+pub fn render(template: &str, now_playing: &NowPlaying) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        let value = match &rest[1..end] {
+            "title" => now_playing.title.clone(),
+            "artist" => now_playing.artist.clone(),
+            "album" => now_playing.album.clone(),
+            "duration" => format_duration(now_playing.duration_ms),
+            "uri" => now_playing.uri.clone(),
+            "url" => now_playing.url.clone().unwrap_or_default(),
+            "device" => now_playing.device.clone(),
+            _ => rest[..=end].to_string(),
+        };
+        output.push_str(&value);
+        rest = &rest[end + 1..];
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn snapshot(is_playing: bool) -> PlaybackSnapshot {
+        PlaybackSnapshot {
+            is_playing,
+            progress_ms: 60_000,
+            observed_at: DateTime::UNIX_EPOCH,
+            ..PlaybackSnapshot::for_test(json!({
+                "id": "abc", "uri": "spotify:track:abc", "name": "Song",
+                "duration_ms": 200_000,
+                "artists": [{ "name": "A" }, { "name": "B" }],
+                "album": { "name": "Record", "images": [{ "url": "big" }, { "url": "small" }] }
+            }))
+        }
+    }
+
+    #[test]
+    fn test_builds_from_snapshots() {
+        let at = DateTime::UNIX_EPOCH + chrono::Duration::seconds(5);
+        let playing = NowPlaying::new(&snapshot(true), at).unwrap();
+        assert_eq!(playing.artist, "A, B");
+        assert_eq!(
+            playing.url.as_deref(),
+            Some("https://open.spotify.com/track/abc")
+        );
+        assert_eq!(playing.cover_url.as_deref(), Some("big"));
+        assert_eq!(playing.progress_ms, 65_000);
+
+        let paused = NowPlaying::new(&snapshot(false), at).unwrap();
+        assert_eq!(paused.progress_ms, 60_000);
+    }
+
+    #[test]
+    fn test_renders_templates() {
+        let now_playing = NowPlaying::new(&snapshot(true), DateTime::UNIX_EPOCH).unwrap();
+        assert_eq!(
+            render("{artist} - {title} [{duration}]", &now_playing),
+            "A, B - Song [3:20]"
+        );
+        assert_eq!(
+            render("{album} {unknown} {title", &now_playing),
+            "Record {unknown} {title"
+        );
+        assert_eq!(format_duration(3_723_000), "1:02:03");
+    }
+
+    #[test]
+    fn test_validates_paths_and_port() {
+        let absolute = |name: &str| Some(std::env::temp_dir().join(name));
+        let mut settings = OutputSettings {
+            text_path: absolute("song.txt"),
+            cover_path: absolute("cover.jpg"),
+            ..Default::default()
+        };
+        assert!(settings.validate().is_ok());
+
+        settings.json_path = absolute("song.txt");
+        assert!(settings.validate().is_err());
+        settings.json_path = Some("song.json".into());
+        assert!(settings.validate().is_err());
+        settings.json_path = None;
+        settings.overlay_port = 80;
+        assert!(settings.validate().is_err());
+    }
+}
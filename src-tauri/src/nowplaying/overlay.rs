@@ -0,0 +1,58 @@
+use axum::extract::{Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use tauri::{AppHandle, Manager};
+
+use super::model::NowPlaying;
+use crate::error::AppError;
+use crate::loopback::{self, LoopbackServer};
+use crate::playback::PlaybackHub;
+
+/// Browser source page, polling `/state`
+const PAGE: &str = include_str!("overlay.html");
+
+#[derive(Clone)]
+struct OverlayState {
+    app: AppHandle,
+    port: u16,
+}
+
+/// Only serve pages addressed to loopback, against DNS rebinding
+///
+/// There is no token: the overlay is read-only and OBS browser sources cannot
+/// be configured to send one.
+async fn check_host(State(overlay): State<OverlayState>, request: Request, next: Next) -> Response {
+    let host = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok());
+    if !loopback::allowed_host(host, overlay.port) {
+        return (StatusCode::FORBIDDEN, "Forbidden host").into_response();
+    }
+    next.run(request).await
+}
+
+async fn get_page() -> Html<&'static str> {
+    Html(PAGE)
+}
+
+async fn get_state(State(overlay): State<OverlayState>) -> Json<Option<NowPlaying>> {
+    let snapshot = overlay.app.state::<PlaybackHub>().current();
+    Json(snapshot.and_then(|s| NowPlaying::new(&s, chrono::Utc::now())))
+}
+
+/// Bind to loopback and serve the overlay until stopped
+pub async fn start(app: AppHandle, port: u16) -> Result<LoopbackServer, AppError> {
+    let state = OverlayState { app, port };
+    loopback::serve("Now-playing overlay", port, |_| {
+        Router::new()
+            .route("/", get(get_page))
+            .route("/state", get(get_state))
+            .layer(middleware::from_fn_with_state(state.clone(), check_host))
+            .with_state(state)
+    })
+    .await
+}
@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use super::poller::PlaybackHub;
+use crate::api::{RepeatState, SpotifyApi, Track};
+use crate::auth::AppAuthState;
+use crate::error::AppError;
+
+/// Volume change of one "volume up" or "volume down" press, in percent
+pub const VOLUME_STEP: i32 = 5;
+
+/// A player action, shared by every surface that controls playback
+/// (frontend, media keys, desktop integrations...)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PlayerCommand {
+    Play,
+    Pause,
+    TogglePlay,
+    Next,
+    Previous,
+    Seek {
+        position_ms: u64,
+    },
+    SeekBy {
+        offset_ms: i64,
+    },
+    SetVolume {
+        percent: u32,
+    },
+    ChangeVolume {
+        delta: i32,
+    },
+    SetShuffle {
+        shuffle: bool,
+    },
+    SetRepeat {
+        repeat: RepeatState,
+    },
+    /// Play a track, or an album/playlist/artist context
+    PlayUri {
+        uri: String,
+    },
+}
+
+/// Run a player command against the active device
+pub async fn execute(
+    state: &AppAuthState,
+    hub: &PlaybackHub,
+    command: PlayerCommand,
+) -> Result<(), AppError> {
+    let api = SpotifyApi::new(state);
+    let current = hub.current();
+    let device_id = current.as_ref().and_then(|s| s.device.id.clone());
+    let device_id = device_id.as_deref();
+
+    match command {
+        PlayerCommand::Play => api.resume(device_id).await?,
+        PlayerCommand::Pause => api.pause(device_id).await?,
+        PlayerCommand::TogglePlay => {
+            if current.as_ref().map(|s| s.is_playing).unwrap_or(false) {
+                api.pause(device_id).await?
+            } else {
+                api.resume(device_id).await?
+            }
+        }
+        PlayerCommand::Next => api.skip_next(device_id).await?,
+        PlayerCommand::Previous => api.skip_previous(device_id).await?,
+        PlayerCommand::Seek { position_ms } => api.seek(position_ms, device_id).await?,
+        PlayerCommand::SeekBy { offset_ms } => {
+            let snapshot = current
+                .as_ref()
+                .ok_or_else(|| AppError::InvalidInput("Nothing is playing".into()))?;
+            let position = snapshot.position_ms(chrono::Utc::now()) as i64 + offset_ms;
+            let duration = snapshot.track.as_ref().map(|t| t.duration_ms).unwrap_or(0) as i64;
+            api.seek(position.clamp(0, duration.max(0)) as u64, device_id)
+                .await?
+        }
+        PlayerCommand::SetVolume { percent } => api.set_volume(percent, device_id).await?,
+        PlayerCommand::ChangeVolume { delta } => {
+            let volume = current
+                .as_ref()
+                .and_then(|s| s.device.volume_percent)
+                .ok_or_else(|| AppError::InvalidInput("The device has no volume".into()))?;
+            api.set_volume((volume as i32 + delta).clamp(0, 100) as u32, device_id)
+                .await?
+        }
+        PlayerCommand::SetShuffle { shuffle } => api.set_shuffle(shuffle, device_id).await?,
+        PlayerCommand::SetRepeat { repeat } => api.set_repeat(repeat, device_id).await?,
+        PlayerCommand::PlayUri { uri } => {
+            if uri.starts_with("spotify:track:") || uri.starts_with("spotify:episode:") {
+                api.play_uris(&[uri], device_id).await?
+            } else if uri.starts_with("spotify:") {
+                api.play_context(&uri, device_id).await?
+            } else {
+                return Err(AppError::InvalidInput(format!(
+                    "Not a Spotify URI: {}",
+                    uri
+                )));
+            }
+        }
+    }
+
+    hub.refresh();
+    Ok(())
+}
+
+/// Save the track that is currently playing to the user's library
+pub async fn like_current_track(
+    state: &AppAuthState,
+    hub: &PlaybackHub,
+) -> Result<Track, AppError> {
+    let track = hub
+        .current()
+        .and_then(|s| s.track)
+        .ok_or_else(|| AppError::InvalidInput("Nothing is playing".into()))?;
+    let id = track
+        .id
+        .clone()
+        .ok_or_else(|| AppError::InvalidInput("Local tracks cannot be saved".into()))?;
+
+    SpotifyApi::new(state).save_tracks(&[id]).await?;
+    Ok(track)
+}
+
+/// Run a player command from the frontend
+#[tauri::command]
+pub async fn player_command(
+    command: PlayerCommand,
+    state: State<'_, AppAuthState>,
+    hub: State<'_, PlaybackHub>,
+) -> Result<(), AppError> {
+    execute(&state, &hub, command).await
+}
@@ -0,0 +1,305 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::api::{CurrentPlayback, Device, RepeatState, Track};
+
+/// Progress drift beyond what elapsed time explains that counts as a seek
+const SEEK_TOLERANCE_MS: i64 = 2500;
+
+/// Playback state as last observed by the poller
+#[derive(Debug, Clone, Serialize)]
+pub struct PlaybackSnapshot {
+    pub device: Device,
+    pub track: Option<Track>,
+    pub context_uri: Option<String>,
+    pub is_playing: bool,
+    pub progress_ms: u64,
+    pub shuffle: bool,
+    pub repeat: RepeatState,
+    pub observed_at: DateTime<Utc>,
+}
+
+impl PlaybackSnapshot {
+    pub fn new(playback: CurrentPlayback, observed_at: DateTime<Utc>) -> Self {
+        Self {
+            device: playback.device,
+            track: playback.item,
+            context_uri: playback.context.map(|c| c.uri),
+            is_playing: playback.is_playing,
+            progress_ms: playback.progress_ms.unwrap_or(0),
+            shuffle: playback.shuffle_state,
+            repeat: playback.repeat_state,
+            observed_at,
+        }
+    }
+
+    pub fn track_uri(&self) -> Option<&str> {
+        self.track.as_ref().map(|t| t.uri.as_str())
+    }
+
+    /// Time left in the current track, if one is playing
+    pub fn remaining_ms(&self) -> Option<u64> {
+        let track = self.track.as_ref()?;
+        self.is_playing
+            .then(|| track.duration_ms.saturating_sub(self.progress_ms))
+    }
+
+    /// Estimated playback position at `at`, accounting for time since the poll
+    pub fn position_ms(&self, at: DateTime<Utc>) -> u64 {
+        let position = self.expected_progress(at).max(0) as u64;
+        match self.track.as_ref() {
+            Some(track) if track.duration_ms > 0 => position.min(track.duration_ms),
+            _ => position,
+        }
+    }
+
+    /// Progress expected at `at` if nothing but time happened
+    fn expected_progress(&self, at: DateTime<Utc>) -> i64 {
+        let elapsed = if self.is_playing {
+            (at - self.observed_at).num_milliseconds().max(0)
+        } else {
+            0
+        };
+        self.progress_ms as i64 + elapsed
+    }
+
+    /// Snapshot of `track`, a track object as JSON, playing from the start on a
+    /// computer at half volume; tests adjust the fields they care about
+    #[cfg(test)]
+    pub(crate) fn for_test(track: serde_json::Value) -> Self {
+        Self {
+            device: serde_json::from_value(serde_json::json!({
+                "id": "desk", "name": "Desk", "type": "Computer", "volume_percent": 50
+            }))
+            .unwrap(),
+            track: serde_json::from_value(track).unwrap(),
+            context_uri: None,
+            is_playing: true,
+            progress_ms: 0,
+            shuffle: false,
+            repeat: RepeatState::Off,
+            observed_at: Utc::now(),
+        }
+    }
+}
+
+/// A single change between two observations
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PlaybackEvent {
+    TrackChanged {
+        /// Boxed so the other, small events do not pay for its size
+        track: Option<Box<Track>>,
+        previous_uri: Option<String>,
+    },
+    Paused {
+        progress_ms: u64,
+    },
+    Resumed {
+        progress_ms: u64,
+    },
+    Seeked {
+        from_ms: u64,
+        to_ms: u64,
+    },
+    DeviceChanged {
+        device: Option<Device>,
+    },
+    VolumeChanged {
+        volume_percent: Option<u32>,
+    },
+    ShuffleChanged {
+        shuffle: bool,
+    },
+    RepeatChanged {
+        repeat: RepeatState,
+    },
+}
+
+impl PlaybackEvent {
+    /// Name of the Tauri event this change is emitted as
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlaybackEvent::TrackChanged { .. } => "playback:track-changed",
+            PlaybackEvent::Paused { .. } => "playback:paused",
+            PlaybackEvent::Resumed { .. } => "playback:resumed",
+            PlaybackEvent::Seeked { .. } => "playback:seeked",
+            PlaybackEvent::DeviceChanged { .. } => "playback:device-changed",
+            PlaybackEvent::VolumeChanged { .. } => "playback:volume-changed",
+            PlaybackEvent::ShuffleChanged { .. } => "playback:shuffle-changed",
+            PlaybackEvent::RepeatChanged { .. } => "playback:repeat-changed",
+        }
+    }
+}
+
+/// Work out what changed between two observations
+pub fn diff(
+    previous: Option<&PlaybackSnapshot>,
+    current: Option<&PlaybackSnapshot>,
+) -> Vec<PlaybackEvent> {
+    let mut events = Vec::new();
+
+    let (prev, curr) = match (previous, current) {
+        (None, None) => return events,
+        (Some(prev), None) => {
+            events.push(PlaybackEvent::DeviceChanged { device: None });
+            if prev.is_playing {
+                events.push(PlaybackEvent::Paused {
+                    progress_ms: prev.progress_ms,
+                });
+            }
+            return events;
+        }
+        (None, Some(curr)) => {
+            events.push(PlaybackEvent::DeviceChanged {
+                device: Some(curr.device.clone()),
+            });
+            events.push(PlaybackEvent::TrackChanged {
+                track: curr.track.clone().map(Box::new),
+                previous_uri: None,
+            });
+            return events;
+        }
+        (Some(prev), Some(curr)) => (prev, curr),
+    };
+
+    let same_device = prev.device.id == curr.device.id;
+    if !same_device {
+        events.push(PlaybackEvent::DeviceChanged {
+            device: Some(curr.device.clone()),
+        });
+    } else if prev.device.volume_percent != curr.device.volume_percent {
+        events.push(PlaybackEvent::VolumeChanged {
+            volume_percent: curr.device.volume_percent,
+        });
+    }
+
+    let same_track = prev.track_uri() == curr.track_uri();
+    if !same_track {
+        events.push(PlaybackEvent::TrackChanged {
+            track: curr.track.clone().map(Box::new),
+            previous_uri: prev.track_uri().map(str::to_string),
+        });
+    }
+
+    if prev.is_playing != curr.is_playing {
+        events.push(if curr.is_playing {
+            PlaybackEvent::Resumed {
+                progress_ms: curr.progress_ms,
+            }
+        } else {
+            PlaybackEvent::Paused {
+                progress_ms: curr.progress_ms,
+            }
+        });
+    }
+
+    if same_track && same_device {
+        let expected = prev.expected_progress(curr.observed_at);
+        if (curr.progress_ms as i64 - expected).abs() > SEEK_TOLERANCE_MS {
+            events.push(PlaybackEvent::Seeked {
+                from_ms: expected.max(0) as u64,
+                to_ms: curr.progress_ms,
+            });
+        }
+    }
+
+    if prev.shuffle != curr.shuffle {
+        events.push(PlaybackEvent::ShuffleChanged {
+            shuffle: curr.shuffle,
+        });
+    }
+    if prev.repeat != curr.repeat {
+        events.push(PlaybackEvent::RepeatChanged {
+            repeat: curr.repeat,
+        });
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn device(id: &str, volume: u32) -> Device {
+        Device {
+            id: Some(id.into()),
+            name: id.into(),
+            device_type: "Computer".into(),
+            is_active: true,
+            is_restricted: false,
+            volume_percent: Some(volume),
+            supports_volume: true,
+        }
+    }
+
+    fn snapshot(
+        track: &str,
+        progress_ms: u64,
+        playing: bool,
+        at: DateTime<Utc>,
+    ) -> PlaybackSnapshot {
+        PlaybackSnapshot {
+            is_playing: playing,
+            progress_ms,
+            observed_at: at,
+            ..PlaybackSnapshot::for_test(serde_json::json!({
+                "id": null, "uri": track, "name": track, "duration_ms": 200_000
+            }))
+        }
+    }
+
+    #[test]
+    fn test_steady_playback_has_no_events() {
+        let now = Utc::now();
+        let prev = snapshot("a", 10_000, true, now);
+        let curr = snapshot("a", 12_100, true, now + Duration::seconds(2));
+        assert!(diff(Some(&prev), Some(&curr)).is_empty());
+    }
+
+    #[test]
+    fn test_seek_pause_and_volume() {
+        let now = Utc::now();
+        let prev = snapshot("a", 10_000, true, now);
+        let mut curr = snapshot("a", 60_000, false, now + Duration::seconds(2));
+        curr.device.volume_percent = Some(80);
+
+        let events = serde_json::to_value(diff(Some(&prev), Some(&curr))).unwrap();
+        assert_eq!(
+            events,
+            serde_json::json!([
+                {"type": "volume_changed", "volume_percent": 80},
+                {"type": "paused", "progress_ms": 60_000},
+                {"type": "seeked", "from_ms": 12_000, "to_ms": 60_000},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_track_and_device_change() {
+        let now = Utc::now();
+        let prev = snapshot("a", 199_000, true, now);
+        let mut curr = snapshot("b", 1_000, true, now + Duration::seconds(2));
+        curr.device = device("phone", 50);
+        curr.shuffle = true;
+
+        let names: Vec<&str> = diff(Some(&prev), Some(&curr))
+            .iter()
+            .map(PlaybackEvent::name)
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "playback:device-changed",
+                "playback:track-changed",
+                "playback:shuffle-changed"
+            ]
+        );
+        assert!(matches!(
+            diff(Some(&curr), None)[0],
+            PlaybackEvent::DeviceChanged { device: None }
+        ));
+    }
+}
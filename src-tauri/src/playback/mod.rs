@@ -0,0 +1,6 @@
+pub mod control;
+pub mod events;
+pub mod poller;
+
+pub use control::*;
+pub use poller::*;
@@ -0,0 +1,171 @@
+use chrono::Utc;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::{broadcast, Notify};
+
+use super::events::{self, PlaybackEvent, PlaybackSnapshot};
+use crate::api::SpotifyApi;
+use crate::auth::{self, AppAuthState};
+use crate::devices;
+use crate::error::AppError;
+
+/// Poll interval while something is playing
+const PLAYING_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Poll interval while playback is paused on a device
+const PAUSED_INTERVAL: Duration = Duration::from_secs(6);
+
+/// Poll interval when no device is playing anything
+const IDLE_INTERVAL: Duration = Duration::from_secs(20);
+
+/// Poll interval while signed out or after an error
+const OFFLINE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Extra delay after the expected end of a track so the next one has started
+const TRACK_END_GRACE: Duration = Duration::from_millis(500);
+
+/// Capacity of the channel backend subsystems observe polls through
+const UPDATE_CHANNEL_CAPACITY: usize = 16;
+
+/// Result of one poll, broadcast to backend subscribers
+#[derive(Debug, Clone)]
+pub struct PlaybackUpdate {
+    pub snapshot: Option<PlaybackSnapshot>,
+    pub events: Vec<PlaybackEvent>,
+}
+
+/// Latest playback state shared between the poller, commands and the backend
+/// subsystems reacting to playback (queue feeding, timers...)
+pub struct PlaybackHub {
+    current: Mutex<Option<PlaybackSnapshot>>,
+    wake: Notify,
+    updates: broadcast::Sender<PlaybackUpdate>,
+}
+
+impl Default for PlaybackHub {
+    fn default() -> Self {
+        Self {
+            current: Mutex::new(None),
+            wake: Notify::new(),
+            updates: broadcast::channel(UPDATE_CHANNEL_CAPACITY).0,
+        }
+    }
+}
+
+impl PlaybackHub {
+    /// Receive the outcome of every poll, including polls where nothing changed
+    pub fn subscribe(&self) -> broadcast::Receiver<PlaybackUpdate> {
+        self.updates.subscribe()
+    }
+
+    pub fn current(&self) -> Option<PlaybackSnapshot> {
+        self.current.lock().unwrap().clone()
+    }
+
+    /// Ask the poller to fetch the state now instead of waiting for its interval
+    pub fn refresh(&self) {
+        self.wake.notify_one();
+    }
+
+    fn replace(&self, snapshot: Option<PlaybackSnapshot>) -> Option<PlaybackSnapshot> {
+        std::mem::replace(&mut *self.current.lock().unwrap(), snapshot)
+    }
+}
+
+/// Pick the next poll delay: fast while playing, slow when idle, and right
+/// after the current track is expected to end
+fn next_interval(snapshot: Option<&PlaybackSnapshot>) -> Duration {
+    let Some(snapshot) = snapshot else {
+        return IDLE_INTERVAL;
+    };
+    if !snapshot.is_playing {
+        return PAUSED_INTERVAL;
+    }
+
+    match snapshot.remaining_ms() {
+        Some(remaining) => PLAYING_INTERVAL.min(Duration::from_millis(remaining) + TRACK_END_GRACE),
+        None => PLAYING_INTERVAL,
+    }
+}
+
+/// Fetch the playback state, emit what changed and return the next delay
+async fn poll(
+    app: &AppHandle,
+    state: &AppAuthState,
+    hub: &PlaybackHub,
+) -> Result<Duration, AppError> {
+    let signed_in = auth::current_user(state).is_some();
+    let snapshot = if signed_in {
+        SpotifyApi::new(state)
+            .playback_state()
+            .await?
+            .map(|p| PlaybackSnapshot::new(p, Utc::now()))
+    } else {
+        None
+    };
+
+    let previous = hub.replace(snapshot.clone());
+    let changes = events::diff(previous.as_ref(), snapshot.as_ref());
+    for event in &changes {
+        if let PlaybackEvent::DeviceChanged {
+            device: Some(device),
+        } = event
+        {
+            if let Err(e) = devices::remember_device(state, device) {
+                log::warn!("Failed to remember device: {}", e);
+            }
+        }
+        let _ = app.emit(event.name(), event);
+    }
+    if !changes.is_empty() {
+        let _ = app.emit("playback:state", &snapshot);
+    }
+    // Nobody listening is not an error
+    let _ = hub.updates.send(PlaybackUpdate {
+        snapshot: snapshot.clone(),
+        events: changes,
+    });
+
+    Ok(if signed_in {
+        next_interval(snapshot.as_ref())
+    } else {
+        OFFLINE_INTERVAL
+    })
+}
+
+/// Start the background job that observes playback on every Connect device
+pub fn spawn_playback_poller(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let state = app.state::<AppAuthState>();
+            let hub = app.state::<PlaybackHub>();
+
+            let interval = match poll(&app, &state, &hub).await {
+                Ok(interval) => interval,
+                Err(AppError::RateLimited(seconds)) => Duration::from_secs(seconds),
+                Err(e) => {
+                    log::warn!("Failed to poll playback state: {}", e);
+                    OFFLINE_INTERVAL
+                }
+            };
+
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = hub.wake.notified() => {}
+            }
+        }
+    });
+}
+
+/// Get the last observed playback state
+#[tauri::command]
+pub fn get_playback_state(hub: State<PlaybackHub>) -> Option<PlaybackSnapshot> {
+    hub.current()
+}
+
+/// Poll the playback state immediately, e.g. right after a player action
+#[tauri::command]
+pub fn refresh_playback_state(hub: State<PlaybackHub>) {
+    hub.refresh();
+}
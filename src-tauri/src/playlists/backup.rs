@@ -0,0 +1,369 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::diff::{self, PlaylistDiff};
+use crate::api::{PlaylistItem, SpotifyApi};
+use crate::auth::{self, AppAuthState};
+use crate::error::AppError;
+use crate::store;
+
+const BACKUP_DIR: &str = "backups";
+const CONFIG_FILE: &str = "config.json";
+const INDEX_FILE: &str = "index.json";
+
+/// How often the background job wakes up to check whether a run is due
+const JOB_TICK: Duration = Duration::from_secs(60);
+
+/// Scheduled backup settings, stored per account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    pub enabled: bool,
+    pub interval_minutes: u32,
+    pub playlist_ids: Vec<String>,
+    /// Oldest versions beyond this count are pruned
+    pub max_versions: usize,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: 60,
+            playlist_ids: Vec::new(),
+            max_versions: 50,
+        }
+    }
+}
+
+/// Metadata of a stored playlist version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionInfo {
+    pub id: String,
+    pub snapshot_id: String,
+    pub name: String,
+    pub captured_at: DateTime<Utc>,
+    pub item_count: usize,
+}
+
+/// A playlist item as stored in a backup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionItem {
+    /// URI stored in the playlist, not the one a relinked track plays as
+    pub uri: String,
+    pub name: String,
+    pub artists: String,
+    pub added_at: Option<DateTime<Utc>>,
+    pub added_by: Option<String>,
+    pub is_local: bool,
+}
+
+impl From<&PlaylistItem> for Option<VersionItem> {
+    fn from(item: &PlaylistItem) -> Self {
+        let track = item.track.as_ref()?;
+        Some(VersionItem {
+            uri: track.original_uri().to_string(),
+            name: track.name.clone(),
+            artists: track.artist_names(),
+            added_at: item.added_at,
+            added_by: item.added_by.as_ref().map(|u| u.id.clone()),
+            is_local: item.is_local || track.is_local,
+        })
+    }
+}
+
+/// A full stored version of a playlist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaylistVersion {
+    pub info: VersionInfo,
+    pub playlist_id: String,
+    pub description: Option<String>,
+    pub items: Vec<VersionItem>,
+}
+
+impl PlaylistVersion {
+    fn uris(&self) -> Vec<String> {
+        self.items.iter().map(|i| i.uri.clone()).collect()
+    }
+}
+
+/// Result of restoring a playlist version
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreResult {
+    pub snapshot_id: String,
+    pub restored: usize,
+    /// Local files cannot be added through the Web API
+    pub skipped_local: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct BackupSaved {
+    playlist_id: String,
+    version: VersionInfo,
+}
+
+fn backup_dir(state: &AppAuthState) -> Result<PathBuf, AppError> {
+    store::account_file(state, BACKUP_DIR)
+}
+
+fn playlist_dir(state: &AppAuthState, playlist_id: &str) -> Result<PathBuf, AppError> {
+    let mut path = backup_dir(state)?;
+    path.push(store::sanitize_file_name(playlist_id));
+    Ok(path)
+}
+
+fn load_config(state: &AppAuthState) -> Result<BackupConfig, AppError> {
+    let path = backup_dir(state)?.join(CONFIG_FILE);
+    Ok(store::load_json(&path)?.unwrap_or_default())
+}
+
+/// Configured retention, used when other tools take a safety backup
+pub fn max_versions(state: &AppAuthState) -> usize {
+    load_config(state)
+        .map(|c| c.max_versions)
+        .unwrap_or_else(|_| BackupConfig::default().max_versions)
+}
+
+fn load_index(state: &AppAuthState, playlist_id: &str) -> Result<Vec<VersionInfo>, AppError> {
+    let path = playlist_dir(state, playlist_id)?.join(INDEX_FILE);
+    Ok(store::load_json(&path)?.unwrap_or_default())
+}
+
+fn load_version(
+    state: &AppAuthState,
+    playlist_id: &str,
+    version_id: &str,
+) -> Result<PlaylistVersion, AppError> {
+    let path = playlist_dir(state, playlist_id)?
+        .join(format!("{}.json", store::sanitize_file_name(version_id)));
+    store::load_json(&path)?
+        .ok_or_else(|| AppError::NotFound(format!("Version {} of {}", version_id, playlist_id)))
+}
+
+/// Fetch the live playlist as an unsaved version
+async fn fetch_version(
+    api: &SpotifyApi<'_>,
+    playlist_id: &str,
+) -> Result<PlaylistVersion, AppError> {
+    let playlist = api.playlist(playlist_id).await?;
+    let items: Vec<VersionItem> = api
+        .playlist_items(playlist_id)
+        .await?
+        .iter()
+        .filter_map(Option::<VersionItem>::from)
+        .collect();
+
+    let captured_at = Utc::now();
+    Ok(PlaylistVersion {
+        info: VersionInfo {
+            id: captured_at.format("%Y%m%dT%H%M%S%3fZ").to_string(),
+            snapshot_id: playlist.snapshot_id,
+            name: playlist.name,
+            captured_at,
+            item_count: items.len(),
+        },
+        playlist_id: playlist.id,
+        description: playlist.description,
+        items,
+    })
+}
+
+/// Snapshot a playlist unless its current `snapshot_id` is already stored
+///
+/// Returns the new version, or `None` when nothing changed since the last backup.
+pub async fn backup_playlist(
+    state: &AppAuthState,
+    playlist_id: &str,
+    max_versions: usize,
+) -> Result<Option<VersionInfo>, AppError> {
+    let api = SpotifyApi::new(state);
+    let is_stored = |index: &[VersionInfo], snapshot_id: &str| {
+        index.last().map(|v| v.snapshot_id.as_str()) == Some(snapshot_id)
+    };
+
+    // Cheap metadata call first; items are only fetched when the snapshot changed
+    let snapshot_id = api.playlist(playlist_id).await?.snapshot_id;
+    if is_stored(&load_index(state, playlist_id)?, &snapshot_id) {
+        return Ok(None);
+    }
+
+    let version = fetch_version(&api, playlist_id).await?;
+    let dir = playlist_dir(state, playlist_id)?;
+    let version_path = dir.join(format!("{}.json", version.info.id));
+    store::save_json(&version_path, &version)?;
+
+    // The backup job and a manual backup may have fetched the same snapshot
+    let added = store::update_json(&dir.join(INDEX_FILE), |index: &mut Vec<VersionInfo>| {
+        if is_stored(index, &version.info.snapshot_id) {
+            return Ok(false);
+        }
+        index.push(version.info.clone());
+        if max_versions > 0 && index.len() > max_versions {
+            for pruned in index.drain(..index.len() - max_versions) {
+                let _ = fs::remove_file(dir.join(format!("{}.json", pruned.id)));
+            }
+        }
+        Ok(true)
+    })?;
+    if !added {
+        let _ = fs::remove_file(&version_path);
+        return Ok(None);
+    }
+
+    log::info!(
+        "Backed up playlist {} ({} items, snapshot {})",
+        playlist_id,
+        version.info.item_count,
+        version.info.snapshot_id
+    );
+    Ok(Some(version.info))
+}
+
+/// Start the background job that snapshots the configured playlists
+pub fn spawn_backup_job(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_run: Option<Instant> = None;
+
+        loop {
+            tokio::time::sleep(JOB_TICK).await;
+
+            let state = app.state::<AppAuthState>();
+            if auth::current_user(&state).is_none() {
+                continue;
+            }
+
+            let config = match load_config(&state) {
+                Ok(config) => config,
+                Err(e) => {
+                    log::error!("Failed to load backup config: {}", e);
+                    continue;
+                }
+            };
+
+            let interval = Duration::from_secs(config.interval_minutes.max(1) as u64 * 60);
+            let due = last_run.map(|t| t.elapsed() >= interval).unwrap_or(true);
+            if !config.enabled || !due {
+                continue;
+            }
+            last_run = Some(Instant::now());
+
+            for playlist_id in &config.playlist_ids {
+                match backup_playlist(&state, playlist_id, config.max_versions).await {
+                    Ok(Some(version)) => {
+                        let _ = app.emit(
+                            "playlist-backup:saved",
+                            BackupSaved {
+                                playlist_id: playlist_id.clone(),
+                                version,
+                            },
+                        );
+                    }
+                    Ok(None) => {}
+                    Err(e) => log::warn!("Backup of playlist {} failed: {}", playlist_id, e),
+                }
+            }
+        }
+    });
+}
+
+/// Get the scheduled backup settings
+#[tauri::command]
+pub fn get_backup_config(state: State<AppAuthState>) -> Result<BackupConfig, AppError> {
+    load_config(&state)
+}
+
+/// Update the scheduled backup settings
+#[tauri::command]
+pub fn set_backup_config(config: BackupConfig, state: State<AppAuthState>) -> Result<(), AppError> {
+    if config.interval_minutes == 0 {
+        return Err(AppError::InvalidInput(
+            "Backup interval must be at least a minute".into(),
+        ));
+    }
+    store::save_json(&backup_dir(&state)?.join(CONFIG_FILE), &config)
+}
+
+/// Snapshot a playlist immediately
+#[tauri::command]
+pub async fn backup_playlist_now(
+    playlist_id: String,
+    state: State<'_, AppAuthState>,
+) -> Result<Option<VersionInfo>, AppError> {
+    backup_playlist(&state, &playlist_id, max_versions(&state)).await
+}
+
+/// List the stored versions of a playlist, newest first
+#[tauri::command]
+pub fn list_playlist_versions(
+    playlist_id: String,
+    state: State<AppAuthState>,
+) -> Result<Vec<VersionInfo>, AppError> {
+    let mut index = load_index(&state, &playlist_id)?;
+    index.reverse();
+    Ok(index)
+}
+
+/// Get a stored version with its items
+#[tauri::command]
+pub fn get_playlist_version(
+    playlist_id: String,
+    version_id: String,
+    state: State<AppAuthState>,
+) -> Result<PlaylistVersion, AppError> {
+    load_version(&state, &playlist_id, &version_id)
+}
+
+/// Diff two stored versions; without `to_version` the live playlist is used
+#[tauri::command]
+pub async fn diff_playlist_versions(
+    playlist_id: String,
+    from_version: String,
+    to_version: Option<String>,
+    state: State<'_, AppAuthState>,
+) -> Result<PlaylistDiff, AppError> {
+    let from = load_version(&state, &playlist_id, &from_version)?;
+    let to = match to_version {
+        Some(id) => load_version(&state, &playlist_id, &id)?,
+        None => fetch_version(&SpotifyApi::new(&state), &playlist_id).await?,
+    };
+
+    Ok(diff::diff(&from.uris(), &to.uris()))
+}
+
+/// Rewrite a playlist to a stored version
+///
+/// The current state is backed up first so the restore itself can be undone.
+#[tauri::command]
+pub async fn restore_playlist_version(
+    playlist_id: String,
+    version_id: String,
+    state: State<'_, AppAuthState>,
+) -> Result<RestoreResult, AppError> {
+    let version = load_version(&state, &playlist_id, &version_id)?;
+    backup_playlist(&state, &playlist_id, max_versions(&state)).await?;
+
+    let uris: Vec<String> = version
+        .items
+        .iter()
+        .filter(|i| !i.is_local)
+        .map(|i| i.uri.clone())
+        .collect();
+
+    let snapshot_id = SpotifyApi::new(&state)
+        .replace_playlist_items(&playlist_id, &uris)
+        .await?;
+
+    log::info!(
+        "Restored playlist {} to version {}",
+        playlist_id,
+        version_id
+    );
+    Ok(RestoreResult {
+        snapshot_id,
+        restored: uris.len(),
+        skipped_local: version.items.len() - uris.len(),
+    })
+}
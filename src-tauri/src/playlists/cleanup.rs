@@ -0,0 +1,353 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tauri::State;
+
+use super::{backup, matching};
+use crate::api::{PlaylistItem, SpotifyApi};
+use crate::auth::AppAuthState;
+use crate::error::AppError;
+
+/// Why items were grouped as duplicates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateKind {
+    /// Same Spotify URI
+    Exact,
+    /// Different releases sharing an ISRC
+    Isrc,
+    /// Same normalized title and primary artist
+    Similar,
+}
+
+/// A playlist item referenced by position
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CleanupItem {
+    pub position: usize,
+    /// URI stored in the playlist, which relinked tracks report under `linked_from`
+    pub uri: String,
+    pub name: String,
+    pub artists: String,
+    pub album: String,
+    pub added_at: Option<DateTime<Utc>>,
+}
+
+impl CleanupItem {
+    fn new(position: usize, item: &PlaylistItem) -> Option<Self> {
+        let track = item.track.as_ref()?;
+        Some(Self {
+            position,
+            uri: track.original_uri().to_string(),
+            name: track.name.clone(),
+            artists: track.artist_names(),
+            album: track.album.name.clone(),
+            added_at: item.added_at,
+        })
+    }
+}
+
+/// The first occurrence of a track and the later copies of it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    pub kind: DuplicateKind,
+    pub keep: CleanupItem,
+    pub duplicates: Vec<CleanupItem>,
+}
+
+/// Result of analysing a playlist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanupReport {
+    pub playlist_id: String,
+    /// Snapshot the positions refer to; must be passed back when applying
+    pub snapshot_id: String,
+    pub total: usize,
+    pub groups: Vec<DuplicateGroup>,
+    /// Tracks not playable in the user's market
+    pub unavailable: Vec<CleanupItem>,
+}
+
+/// An item chosen for removal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanupRemoval {
+    pub position: usize,
+    pub uri: String,
+}
+
+/// Result of applying a cleanup
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanupResult {
+    pub snapshot_id: String,
+    pub removed: usize,
+}
+
+/// Find duplicate groups and unavailable tracks
+///
+/// Kinds are checked from the strictest to the loosest. An item is reported as a
+/// duplicate at most once, while the earliest occurrence is always the one kept and
+/// may anchor groups of several kinds.
+pub fn analyze(items: &[PlaylistItem]) -> (Vec<DuplicateGroup>, Vec<CleanupItem>) {
+    let entries: Vec<(CleanupItem, &PlaylistItem)> = items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| !item.is_local)
+        .filter_map(|(position, item)| CleanupItem::new(position, item).map(|c| (c, item)))
+        .collect();
+
+    // Items already reported as duplicates, and items kept by an earlier group
+    let mut claimed = vec![false; entries.len()];
+    let mut kept = vec![false; entries.len()];
+    let mut groups = Vec::new();
+
+    for kind in [
+        DuplicateKind::Exact,
+        DuplicateKind::Isrc,
+        DuplicateKind::Similar,
+    ] {
+        // key -> indices into `entries`, in playlist order
+        let mut buckets: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut order = Vec::new();
+        for (index, (_, item)) in entries.iter().enumerate() {
+            if claimed[index] {
+                continue;
+            }
+            if let Some(key) = duplicate_key(kind, item) {
+                let bucket = buckets.entry(key.clone()).or_default();
+                if bucket.is_empty() {
+                    order.push(key);
+                }
+                bucket.push(index);
+            }
+        }
+
+        for key in order {
+            let bucket = &buckets[&key];
+            let duplicates: Vec<usize> =
+                bucket[1..].iter().copied().filter(|&i| !kept[i]).collect();
+            if duplicates.is_empty() {
+                continue;
+            }
+
+            kept[bucket[0]] = true;
+            for &index in &duplicates {
+                claimed[index] = true;
+            }
+            groups.push(DuplicateGroup {
+                kind,
+                keep: entries[bucket[0]].0.clone(),
+                duplicates: duplicates.iter().map(|&i| entries[i].0.clone()).collect(),
+            });
+        }
+    }
+
+    groups.sort_by_key(|g| g.keep.position);
+
+    let unavailable = entries
+        .iter()
+        .filter(|(_, item)| item.track.as_ref().and_then(|t| t.is_playable) == Some(false))
+        .map(|(entry, _)| entry.clone())
+        .collect();
+
+    (groups, unavailable)
+}
+
+/// Grouping key of an item for the given kind of duplicate
+fn duplicate_key(kind: DuplicateKind, item: &PlaylistItem) -> Option<String> {
+    let track = item.track.as_ref()?;
+    match kind {
+        DuplicateKind::Exact => Some(track.original_uri().to_string()),
+        DuplicateKind::Isrc => track.isrc().map(|isrc| isrc.to_ascii_uppercase()),
+        DuplicateKind::Similar => {
+            let title = matching::normalize_title(&track.name);
+            let artist = matching::normalize(&track.artists.first()?.name);
+            (!title.is_empty()).then(|| format!("{}\u{1f}{}", title, artist))
+        }
+    }
+}
+
+/// Analyse a playlist for duplicates and unavailable tracks
+#[tauri::command]
+pub async fn analyze_playlist_cleanup(
+    playlist_id: String,
+    state: State<'_, AppAuthState>,
+) -> Result<CleanupReport, AppError> {
+    let api = SpotifyApi::new(&state);
+    let playlist = api.playlist(&playlist_id).await?;
+    let items = api.playlist_items(&playlist_id).await?;
+
+    // Items may have changed between the two calls; make sure the snapshot matches them
+    let snapshot_id = api.playlist(&playlist_id).await?.snapshot_id;
+    if snapshot_id != playlist.snapshot_id {
+        return Err(AppError::Conflict(
+            "Playlist changed during analysis, try again".into(),
+        ));
+    }
+
+    let (groups, unavailable) = analyze(&items);
+    Ok(CleanupReport {
+        playlist_id,
+        snapshot_id,
+        total: items.len(),
+        groups,
+        unavailable,
+    })
+}
+
+/// Remove the chosen items from a playlist
+///
+/// Fails with a conflict if the playlist changed since it was analysed, or if a
+/// position no longer holds the expected track. A backup is taken before removing.
+#[tauri::command]
+pub async fn apply_playlist_cleanup(
+    playlist_id: String,
+    snapshot_id: String,
+    removals: Vec<CleanupRemoval>,
+    state: State<'_, AppAuthState>,
+) -> Result<CleanupResult, AppError> {
+    if removals.is_empty() {
+        return Ok(CleanupResult {
+            snapshot_id,
+            removed: 0,
+        });
+    }
+
+    let api = SpotifyApi::new(&state);
+    let current = api.playlist(&playlist_id).await?.snapshot_id;
+    if current != snapshot_id {
+        return Err(AppError::Conflict(
+            "Playlist changed since it was analysed, run the analysis again".into(),
+        ));
+    }
+
+    let items = api.playlist_items(&playlist_id).await?;
+    for removal in &removals {
+        let actual = items
+            .get(removal.position)
+            .and_then(|i| i.track.as_ref())
+            .map(|t| t.original_uri());
+        if actual != Some(removal.uri.as_str()) {
+            return Err(AppError::Conflict(format!(
+                "Position {} no longer holds {}",
+                removal.position, removal.uri
+            )));
+        }
+    }
+
+    backup::backup_playlist(&state, &playlist_id, backup::max_versions(&state)).await?;
+
+    let pairs: Vec<(String, usize)> = removals
+        .iter()
+        .map(|r| (r.uri.clone(), r.position))
+        .collect();
+    let snapshot_id = api
+        .remove_playlist_positions(&playlist_id, &snapshot_id, &pairs)
+        .await?;
+
+    // The same position may have been chosen from several groups
+    let removed = removals
+        .iter()
+        .map(|r| r.position)
+        .collect::<HashSet<_>>()
+        .len();
+    log::info!("Removed {} items from playlist {}", removed, playlist_id);
+    Ok(CleanupResult {
+        snapshot_id,
+        removed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{ExternalIds, LinkedTrack, SimplifiedAlbum, SimplifiedArtist, Track};
+
+    fn item(
+        id: &str,
+        name: &str,
+        artist: &str,
+        isrc: Option<&str>,
+        playable: bool,
+    ) -> PlaylistItem {
+        PlaylistItem {
+            added_at: None,
+            added_by: None,
+            is_local: false,
+            track: Some(Track {
+                id: Some(id.into()),
+                uri: format!("spotify:track:{}", id),
+                name: name.into(),
+                duration_ms: 200_000,
+                explicit: false,
+                artists: vec![SimplifiedArtist {
+                    id: None,
+                    name: artist.into(),
+                    uri: String::new(),
+                }],
+                album: SimplifiedAlbum::default(),
+                external_ids: ExternalIds {
+                    isrc: isrc.map(str::to_string),
+                },
+                is_playable: Some(playable),
+                is_local: false,
+                popularity: None,
+                linked_from: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_analyze_groups_by_strictest_kind() {
+        let items = vec![
+            item("a", "Song", "Artist", Some("ISRC1"), true),
+            item("b", "Other", "Someone", None, true),
+            item("a", "Song", "Artist", Some("ISRC1"), true),
+            item("c", "Song - Remastered", "Artist", Some("ISRC1"), true),
+            item("d", "Other (Live)", "Someone", None, false),
+        ];
+
+        let (groups, unavailable) = analyze(&items);
+
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].kind, DuplicateKind::Exact);
+        assert_eq!(groups[0].keep.position, 0);
+        assert_eq!(groups[0].duplicates[0].position, 2);
+        // "c" is another release of the kept "a", not a second copy of the exact duplicate
+        assert_eq!(groups[1].kind, DuplicateKind::Isrc);
+        assert_eq!(groups[1].keep.position, 0);
+        assert_eq!(groups[1].duplicates.len(), 1);
+        assert_eq!(groups[1].duplicates[0].position, 3);
+        assert_eq!(groups[2].kind, DuplicateKind::Similar);
+        assert_eq!(groups[2].keep.position, 1);
+        assert_eq!(groups[2].duplicates[0].position, 4);
+        assert_eq!(unavailable.len(), 1);
+        assert_eq!(unavailable[0].position, 4);
+    }
+
+    #[test]
+    fn test_analyze_isrc_group() {
+        let items = vec![
+            item("a", "Song", "Artist", Some("ISRC1"), true),
+            item("c", "Song (2011 Remaster)", "Artist", Some("isrc1"), true),
+        ];
+
+        let (groups, _) = analyze(&items);
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].kind, DuplicateKind::Isrc);
+    }
+
+    #[test]
+    fn test_analyze_uses_original_uri_of_relinked_tracks() {
+        let mut relinked = item("x", "Song", "Artist", None, true);
+        relinked.track.as_mut().unwrap().linked_from = Some(LinkedTrack {
+            id: Some("a".into()),
+            uri: "spotify:track:a".into(),
+        });
+        let items = vec![item("a", "Song", "Artist", None, true), relinked];
+
+        let (groups, _) = analyze(&items);
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].kind, DuplicateKind::Exact);
+        assert_eq!(groups[0].duplicates[0].uri, "spotify:track:a");
+    }
+}
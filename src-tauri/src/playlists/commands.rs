@@ -0,0 +1,272 @@
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tauri::{AppHandle, Emitter, State};
+
+use super::{
+    import::{self, ImportEntry, ImportFormat},
+    matching::{self, MatchConfidence, MatchScore, MIN_MATCH_SCORE},
+};
+use crate::api::{Playlist, SpotifyApi, Track};
+use crate::auth::{self, AppAuthState, AuthError};
+use crate::error::AppError;
+
+/// Number of search requests in flight while resolving an import
+const IMPORT_CONCURRENCY: usize = 4;
+
+/// Alternatives kept per entry for the review screen
+const IMPORT_ALTERNATIVES: usize = 3;
+
+/// A scored Spotify track for an import entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportCandidate {
+    pub track: Track,
+    pub score: MatchScore,
+}
+
+/// Resolution result of a single import entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportMatch {
+    pub entry: ImportEntry,
+    /// Best candidate, if any scored above the match threshold
+    pub best: Option<ImportCandidate>,
+    pub alternatives: Vec<ImportCandidate>,
+}
+
+/// Reviewable report of an import, sent to the frontend before the playlist is created
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub format: ImportFormat,
+    pub total: usize,
+    pub exact: usize,
+    pub high: usize,
+    pub medium: usize,
+    pub low: usize,
+    pub unmatched: usize,
+    pub items: Vec<ImportMatch>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ImportProgress {
+    resolved: usize,
+    total: usize,
+}
+
+/// Parse a track list and resolve every entry against Spotify
+///
+/// Emits `playlist-import:progress` while resolving.
+#[tauri::command]
+pub async fn preview_playlist_import(
+    app: AppHandle,
+    content: String,
+    file_name: Option<String>,
+    format: Option<ImportFormat>,
+    state: State<'_, AppAuthState>,
+) -> Result<ImportReport, AppError> {
+    let format = format.unwrap_or_else(|| ImportFormat::detect(file_name.as_deref(), &content));
+    let entries = import::parse(&content, format)?;
+    if entries.is_empty() {
+        return Err(AppError::InvalidInput("No tracks found in the file".into()));
+    }
+
+    let api = SpotifyApi::new(&state);
+    let total = entries.len();
+
+    // Entries that already carry a Spotify URI are fetched in bulk
+    let ids: Vec<String> = entries
+        .iter()
+        .filter_map(|e| e.uri.as_deref())
+        .filter_map(|uri| uri.strip_prefix("spotify:track:"))
+        .map(str::to_string)
+        .collect();
+    // Relinked tracks come back under another URI than the one asked for
+    let known: HashMap<String, Track> = api
+        .tracks(&ids)
+        .await?
+        .into_iter()
+        .map(|t| (t.original_uri().to_string(), t))
+        .collect();
+
+    let resolved = AtomicUsize::new(0);
+    let items: Vec<ImportMatch> = stream::iter(entries)
+        .map(|entry| {
+            let (api, known, resolved, app) = (&api, &known, &resolved, &app);
+            async move {
+                let item = match entry.uri.as_ref().and_then(|uri| known.get(uri)) {
+                    Some(track) => ImportMatch {
+                        best: Some(ImportCandidate {
+                            track: track.clone(),
+                            score: MatchScore::exact(),
+                        }),
+                        entry,
+                        alternatives: Vec::new(),
+                    },
+                    None => resolve_entry(api, entry).await,
+                };
+
+                let done = resolved.fetch_add(1, Ordering::SeqCst) + 1;
+                let _ = app.emit(
+                    "playlist-import:progress",
+                    ImportProgress {
+                        resolved: done,
+                        total,
+                    },
+                );
+                item
+            }
+        })
+        .buffered(IMPORT_CONCURRENCY)
+        .collect()
+        .await;
+
+    Ok(build_report(format, items))
+}
+
+/// Create a playlist from the tracks accepted in the review screen
+#[tauri::command]
+pub async fn create_playlist_from_import(
+    name: String,
+    description: Option<String>,
+    public: Option<bool>,
+    uris: Vec<String>,
+    state: State<'_, AppAuthState>,
+) -> Result<Playlist, AppError> {
+    if name.trim().is_empty() {
+        return Err(AppError::InvalidInput("Playlist name is required".into()));
+    }
+    if let Some(invalid) = uris.iter().find(|u| !u.starts_with("spotify:")) {
+        return Err(AppError::InvalidInput(format!(
+            "Not a Spotify URI: {}",
+            invalid
+        )));
+    }
+
+    let user = auth::current_user(&state).ok_or(AuthError::NotAuthenticated)?;
+    let api = SpotifyApi::new(&state);
+
+    let mut playlist = api
+        .create_playlist(
+            &user.id,
+            name.trim(),
+            description.as_deref(),
+            public.unwrap_or(false),
+        )
+        .await?;
+
+    if let Some(snapshot) = api.add_playlist_items(&playlist.id, &uris).await? {
+        playlist.snapshot_id = snapshot;
+    }
+
+    log::info!(
+        "Imported {} tracks into playlist {}",
+        uris.len(),
+        playlist.id
+    );
+    Ok(playlist)
+}
+
+/// Resolve an entry through ISRC lookup, then fuzzy search
+///
+/// Lookup failures are logged and reported as unmatched rather than failing the
+/// whole import.
+async fn resolve_entry(api: &SpotifyApi<'_>, entry: ImportEntry) -> ImportMatch {
+    if let Some(isrc) = entry.isrc.as_deref() {
+        match api.tracks_by_isrc(isrc).await {
+            Ok(tracks) if !tracks.is_empty() => {
+                let mut candidates = rank(&entry, tracks);
+                let best = candidates.remove(0);
+                return ImportMatch {
+                    entry,
+                    best: Some(best),
+                    alternatives: candidates.into_iter().take(IMPORT_ALTERNATIVES).collect(),
+                };
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("ISRC lookup failed for {}: {}", isrc, e),
+        }
+    }
+
+    let mut candidates = Vec::new();
+    for query in search_queries(&entry) {
+        match api.search_tracks(&query, 10).await {
+            Ok(tracks) if !tracks.is_empty() => {
+                candidates = rank(&entry, tracks);
+                break;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                log::warn!("Search failed for {:?}: {}", query, e);
+                break;
+            }
+        }
+    }
+
+    candidates.retain(|c| c.score.total >= MIN_MATCH_SCORE);
+    let best = (!candidates.is_empty()).then(|| candidates.remove(0));
+
+    ImportMatch {
+        entry,
+        best,
+        alternatives: candidates.into_iter().take(IMPORT_ALTERNATIVES).collect(),
+    }
+}
+
+/// Search queries to try, from most to least specific
+fn search_queries(entry: &ImportEntry) -> Vec<String> {
+    let title = entry.title.replace('"', "");
+    if title.is_empty() {
+        return Vec::new();
+    }
+
+    match entry.artist.as_deref() {
+        Some(artist) => {
+            let artist = artist.replace('"', "");
+            vec![
+                format!("track:\"{}\" artist:\"{}\"", title, artist),
+                format!("{} {}", matching::normalize_title(&title), artist),
+            ]
+        }
+        None => vec![title],
+    }
+}
+
+/// Score and sort candidates, best first
+fn rank(entry: &ImportEntry, tracks: Vec<Track>) -> Vec<ImportCandidate> {
+    let mut candidates: Vec<ImportCandidate> = tracks
+        .into_iter()
+        .map(|track| ImportCandidate {
+            score: matching::score(entry, &track),
+            track,
+        })
+        .collect();
+
+    candidates.sort_by(|a, b| b.score.total.total_cmp(&a.score.total));
+    candidates
+}
+
+fn build_report(format: ImportFormat, items: Vec<ImportMatch>) -> ImportReport {
+    let mut report = ImportReport {
+        format,
+        total: items.len(),
+        exact: 0,
+        high: 0,
+        medium: 0,
+        low: 0,
+        unmatched: 0,
+        items: Vec::new(),
+    };
+
+    for item in &items {
+        match item.best.as_ref().map(|b| b.score.confidence) {
+            Some(MatchConfidence::Exact) => report.exact += 1,
+            Some(MatchConfidence::High) => report.high += 1,
+            Some(MatchConfidence::Medium) => report.medium += 1,
+            Some(MatchConfidence::Low) => report.low += 1,
+            None => report.unmatched += 1,
+        }
+    }
+
+    report.items = items;
+    report
+}
@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// An entry that only exists on one side of a diff
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffEntry {
+    pub position: usize,
+    pub uri: String,
+}
+
+/// An entry present on both sides at a different relative position
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MovedEntry {
+    pub uri: String,
+    pub from: usize,
+    pub to: usize,
+}
+
+/// Differences between two versions of a playlist
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaylistDiff {
+    pub added: Vec<DiffEntry>,
+    pub removed: Vec<DiffEntry>,
+    pub moved: Vec<MovedEntry>,
+}
+
+/// Diff two ordered URI lists
+///
+/// Duplicates are matched by occurrence (the second copy of a track in the old
+/// version pairs with the second copy in the new one). Only the items outside the
+/// longest run that kept its relative order are reported as moved, so moving one
+/// track to the top reports a single move instead of shifting everything else.
+pub fn diff(old: &[String], new: &[String]) -> PlaylistDiff {
+    let old_keys = occurrence_keys(old);
+    let new_keys = occurrence_keys(new);
+
+    let old_positions: HashMap<(&str, usize), usize> = old_keys
+        .iter()
+        .enumerate()
+        .map(|(position, key)| (*key, position))
+        .collect();
+    let new_positions: HashMap<(&str, usize), usize> = new_keys
+        .iter()
+        .enumerate()
+        .map(|(position, key)| (*key, position))
+        .collect();
+
+    let removed = old_keys
+        .iter()
+        .enumerate()
+        .filter(|(_, key)| !new_positions.contains_key(key))
+        .map(|(position, (uri, _))| DiffEntry {
+            position,
+            uri: uri.to_string(),
+        })
+        .collect();
+
+    let mut added = Vec::new();
+    // (old position, new position) of the items present on both sides, in new order
+    let mut common = Vec::new();
+    for (position, key) in new_keys.iter().enumerate() {
+        match old_positions.get(key) {
+            Some(&from) => common.push((from, position)),
+            None => added.push(DiffEntry {
+                position,
+                uri: key.0.to_string(),
+            }),
+        }
+    }
+
+    let sequence: Vec<usize> = common.iter().map(|(from, _)| *from).collect();
+    let stable = longest_increasing_subsequence(&sequence);
+    let moved = common
+        .iter()
+        .zip(stable)
+        .filter(|(_, stable)| !stable)
+        .map(|(&(from, to), _)| MovedEntry {
+            uri: new[to].clone(),
+            from,
+            to,
+        })
+        .collect();
+
+    PlaylistDiff {
+        added,
+        removed,
+        moved,
+    }
+}
+
+/// Pair every item with its occurrence number so duplicates get distinct keys
+fn occurrence_keys(items: &[String]) -> Vec<(&str, usize)> {
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    items
+        .iter()
+        .map(|item| {
+            let count = seen.entry(item.as_str()).or_insert(0);
+            *count += 1;
+            (item.as_str(), *count - 1)
+        })
+        .collect()
+}
+
+/// Mark the members of one longest strictly increasing subsequence
+pub fn longest_increasing_subsequence(sequence: &[usize]) -> Vec<bool> {
+    // tails[k] = index in `sequence` of the smallest tail of an increasing run of length k + 1
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous: Vec<Option<usize>> = vec![None; sequence.len()];
+
+    for (i, value) in sequence.iter().enumerate() {
+        let length = tails.partition_point(|&t| sequence[t] < *value);
+        if length > 0 {
+            previous[i] = Some(tails[length - 1]);
+        }
+        if length == tails.len() {
+            tails.push(i);
+        } else {
+            tails[length] = i;
+        }
+    }
+
+    let mut members = vec![false; sequence.len()];
+    let mut cursor = tails.last().copied();
+    while let Some(i) = cursor {
+        members[i] = true;
+        cursor = previous[i];
+    }
+    members
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uris(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_diff_added_removed() {
+        let diff = diff(&uris(&["a", "b", "c"]), &uris(&["a", "c", "d"]));
+
+        assert_eq!(
+            diff.removed,
+            vec![DiffEntry {
+                position: 1,
+                uri: "b".into()
+            }]
+        );
+        assert_eq!(
+            diff.added,
+            vec![DiffEntry {
+                position: 2,
+                uri: "d".into()
+            }]
+        );
+        assert!(diff.moved.is_empty());
+    }
+
+    #[test]
+    fn test_diff_single_move() {
+        let diff = diff(&uris(&["a", "b", "c", "d"]), &uris(&["d", "a", "b", "c"]));
+
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(
+            diff.moved,
+            vec![MovedEntry {
+                uri: "d".into(),
+                from: 3,
+                to: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_duplicates_by_occurrence() {
+        let diff = diff(&uris(&["a", "b", "a"]), &uris(&["a", "b"]));
+
+        assert_eq!(
+            diff.removed,
+            vec![DiffEntry {
+                position: 2,
+                uri: "a".into()
+            }]
+        );
+        assert!(diff.added.is_empty() && diff.moved.is_empty());
+    }
+
+    #[test]
+    fn test_lis() {
+        let members = longest_increasing_subsequence(&[3, 0, 1, 4, 2]);
+        assert_eq!(members.iter().filter(|m| **m).count(), 3);
+        assert_eq!(members, vec![false, true, true, false, true]);
+    }
+}
@@ -0,0 +1,549 @@
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+/// Supported track list formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    M3u,
+    Xspf,
+    Csv,
+    Text,
+}
+
+impl ImportFormat {
+    /// Guess the format from the file name, falling back to sniffing the content
+    pub fn detect(file_name: Option<&str>, content: &str) -> Self {
+        let extension = file_name
+            .and_then(|n| n.rsplit_once('.'))
+            .map(|(_, ext)| ext.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("m3u") | Some("m3u8") => return Self::M3u,
+            Some("xspf") => return Self::Xspf,
+            Some("csv") => return Self::Csv,
+            Some("txt") => return Self::Text,
+            _ => {}
+        }
+
+        let head = content.trim_start_matches('\u{feff}').trim_start();
+        if head.starts_with("#EXTM3U") {
+            Self::M3u
+        } else if head.starts_with("<?xml") || head.starts_with("<playlist") {
+            Self::Xspf
+        } else if head
+            .lines()
+            .next()
+            .map(|l| l.contains(',') && find_column(&split_header(l), TITLE_COLUMNS).is_some())
+            .unwrap_or(false)
+        {
+            Self::Csv
+        } else {
+            Self::Text
+        }
+    }
+}
+
+/// One entry of an imported track list, before it is resolved against Spotify
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImportEntry {
+    /// Position of the entry in the source list
+    pub index: usize,
+    pub title: String,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub isrc: Option<String>,
+    /// Spotify track URI when the source already references one
+    pub uri: Option<String>,
+}
+
+impl ImportEntry {
+    fn is_empty(&self) -> bool {
+        self.title.is_empty() && self.uri.is_none() && self.isrc.is_none()
+    }
+}
+
+/// Parse a track list in the given format
+pub fn parse(content: &str, format: ImportFormat) -> Result<Vec<ImportEntry>, AppError> {
+    let content = content.trim_start_matches('\u{feff}');
+
+    let mut entries = match format {
+        ImportFormat::M3u => parse_m3u(content),
+        ImportFormat::Xspf => parse_xspf(content)?,
+        ImportFormat::Csv => parse_csv(content)?,
+        ImportFormat::Text => parse_text(content),
+    };
+
+    entries.retain(|e| !e.is_empty());
+    for (index, entry) in entries.iter_mut().enumerate() {
+        entry.index = index;
+    }
+
+    Ok(entries)
+}
+
+fn parse_m3u(content: &str) -> Vec<ImportEntry> {
+    let mut entries = Vec::new();
+    let mut pending: Option<ImportEntry> = None;
+
+    for line in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Some(info) = line.strip_prefix("#EXTINF:") {
+            // #EXTINF:<seconds>[ attributes],<display title>
+            let (meta, display) = info.split_once(',').unwrap_or((info, ""));
+            let seconds = meta
+                .split_whitespace()
+                .next()
+                .and_then(|s| s.parse::<i64>().ok())
+                .filter(|s| *s > 0);
+
+            let mut entry = entry_from_display(display);
+            entry.duration_ms = seconds.map(|s| s as u64 * 1000);
+            pending = Some(entry);
+        } else if line.starts_with('#') {
+            continue;
+        } else {
+            let mut entry = pending
+                .take()
+                .filter(|e| !e.title.is_empty())
+                .unwrap_or_else(|| entry_from_location(line));
+            entry.uri = entry.uri.or_else(|| spotify_track_uri(line));
+            entries.push(entry);
+        }
+    }
+
+    entries
+}
+
+fn parse_xspf(content: &str) -> Result<Vec<ImportEntry>, AppError> {
+    // Text is trimmed per field instead of per event so entity references keep their spacing
+    let mut reader = Reader::from_str(content);
+
+    let mut entries = Vec::new();
+    let mut current: Option<ImportEntry> = None;
+    // Name of the direct <track> child whose text is being collected
+    let mut field: Option<String> = None;
+    let mut text = String::new();
+    let mut depth_in_track = 0usize;
+
+    loop {
+        match reader
+            .read_event()
+            .map_err(|e| AppError::InvalidInput(format!("Invalid XSPF: {}", e)))?
+        {
+            Event::Start(e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                if current.is_some() {
+                    depth_in_track += 1;
+                    if depth_in_track == 1 {
+                        field = Some(name);
+                        text.clear();
+                    }
+                } else if name == "track" {
+                    current = Some(ImportEntry::default());
+                    depth_in_track = 0;
+                }
+            }
+            Event::Text(e) if field.is_some() => {
+                text.push_str(&e.decode().unwrap_or_default());
+            }
+            Event::CData(e) if field.is_some() => {
+                text.push_str(&String::from_utf8_lossy(&e));
+            }
+            Event::GeneralRef(e) if field.is_some() => {
+                let reference = format!("&{};", e.decode().unwrap_or_default());
+                match quick_xml::escape::unescape(&reference) {
+                    Ok(resolved) => text.push_str(&resolved),
+                    Err(_) => text.push_str(&reference),
+                }
+            }
+            Event::End(e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                if let Some(entry) = current.as_mut() {
+                    if depth_in_track == 0 && name == "track" {
+                        entries.push(current.take().unwrap());
+                        continue;
+                    }
+                    if depth_in_track == 1 {
+                        if let Some(field) = field.take() {
+                            apply_xspf_field(entry, &field, text.trim());
+                        }
+                    }
+                    depth_in_track = depth_in_track.saturating_sub(1);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(entries)
+}
+
+fn apply_xspf_field(entry: &mut ImportEntry, field: &str, value: &str) {
+    if value.is_empty() {
+        return;
+    }
+
+    match field {
+        "title" => entry.title = value.to_string(),
+        "creator" => entry.artist = Some(value.to_string()),
+        "album" => entry.album = Some(value.to_string()),
+        "duration" => entry.duration_ms = value.parse().ok(),
+        "location" | "identifier" => {
+            if let Some(uri) = spotify_track_uri(value) {
+                entry.uri = Some(uri);
+            } else if let Some(isrc) = value.strip_prefix("isrc:").filter(|i| is_isrc(i)) {
+                entry.isrc = Some(isrc.to_ascii_uppercase());
+            } else if entry.title.is_empty() && field == "location" {
+                let fallback = entry_from_location(value);
+                entry.title = fallback.title;
+                entry.artist = entry.artist.take().or(fallback.artist);
+            }
+        }
+        _ => {}
+    }
+}
+
+const TITLE_COLUMNS: &[&str] = &["track name", "title", "name", "track", "song"];
+const ARTIST_COLUMNS: &[&str] = &[
+    "artist name(s)",
+    "artist name",
+    "artists",
+    "artist",
+    "creator",
+];
+const ALBUM_COLUMNS: &[&str] = &["album name", "album"];
+const ISRC_COLUMNS: &[&str] = &["isrc"];
+const URI_COLUMNS: &[&str] = &["track uri", "spotify uri", "uri", "spotify url", "url"];
+const DURATION_MS_COLUMNS: &[&str] = &["duration (ms)", "duration_ms", "track duration (ms)"];
+const DURATION_COLUMNS: &[&str] = &["duration", "length", "time"];
+
+fn split_header(line: &str) -> Vec<String> {
+    line.split(',')
+        .map(|c| c.trim().trim_matches('"').to_ascii_lowercase())
+        .collect()
+}
+
+fn find_column(headers: &[String], candidates: &[&str]) -> Option<usize> {
+    candidates
+        .iter()
+        .find_map(|c| headers.iter().position(|h| h == c))
+}
+
+fn parse_csv(content: &str) -> Result<Vec<ImportEntry>, AppError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| AppError::InvalidInput(format!("Invalid CSV header: {}", e)))?
+        .iter()
+        .map(|h| h.to_ascii_lowercase())
+        .collect();
+
+    let title = find_column(&headers, TITLE_COLUMNS);
+    let artist = find_column(&headers, ARTIST_COLUMNS);
+    let album = find_column(&headers, ALBUM_COLUMNS);
+    let isrc = find_column(&headers, ISRC_COLUMNS);
+    let uri = find_column(&headers, URI_COLUMNS);
+    let duration_ms = find_column(&headers, DURATION_MS_COLUMNS);
+    let duration = find_column(&headers, DURATION_COLUMNS);
+
+    if title.is_none() && isrc.is_none() && uri.is_none() {
+        return Err(AppError::InvalidInput(
+            "CSV needs a title, ISRC or URI column".into(),
+        ));
+    }
+
+    let mut entries = Vec::new();
+    for record in reader.records() {
+        let record =
+            record.map_err(|e| AppError::InvalidInput(format!("Invalid CSV row: {}", e)))?;
+        let cell = |column: Option<usize>| {
+            column
+                .and_then(|c| record.get(c))
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+        };
+
+        entries.push(ImportEntry {
+            index: 0,
+            title: cell(title).unwrap_or_default().to_string(),
+            artist: cell(artist).map(str::to_string),
+            album: cell(album).map(str::to_string),
+            duration_ms: cell(duration_ms)
+                .and_then(|d| d.parse::<f64>().ok())
+                .map(|d| d as u64)
+                .or_else(|| cell(duration).and_then(parse_clock_duration)),
+            isrc: cell(isrc)
+                .filter(|i| is_isrc(i))
+                .map(str::to_ascii_uppercase),
+            uri: cell(uri).and_then(spotify_track_uri),
+        });
+    }
+
+    Ok(entries)
+}
+
+fn parse_text(content: &str) -> Vec<ImportEntry> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#') && !l.starts_with("//"))
+        .map(|line| match spotify_track_uri(line) {
+            Some(uri) => ImportEntry {
+                uri: Some(uri),
+                ..Default::default()
+            },
+            None => entry_from_display(line),
+        })
+        .collect()
+}
+
+/// Parse an `Artist - Title` style line, with an optional trailing `(m:ss)` or `[m:ss]`
+fn entry_from_display(display: &str) -> ImportEntry {
+    let mut text = strip_track_number(display.trim());
+    let mut duration_ms = None;
+
+    if let Some(stripped) = text.strip_suffix(')').or_else(|| text.strip_suffix(']')) {
+        if let Some(open) = stripped.rfind(['(', '[']) {
+            if let Some(ms) = parse_clock_duration(&stripped[open + 1..]) {
+                duration_ms = Some(ms);
+                text = stripped[..open].trim_end();
+            }
+        }
+    }
+
+    let (artist, title) = split_artist_title(text);
+    ImportEntry {
+        title: title.to_string(),
+        artist: artist.map(str::to_string),
+        duration_ms,
+        ..Default::default()
+    }
+}
+
+/// Build an entry from a file path or URL, using its file name as `Artist - Title`
+fn entry_from_location(location: &str) -> ImportEntry {
+    if let Some(uri) = spotify_track_uri(location) {
+        return ImportEntry {
+            uri: Some(uri),
+            ..Default::default()
+        };
+    }
+
+    let file = location.rsplit(['/', '\\']).next().unwrap_or(location);
+    let file = urlencoding::decode(file)
+        .map(|f| f.into_owned())
+        .unwrap_or_else(|_| file.to_string());
+    let stem = match file.rsplit_once('.') {
+        Some((stem, ext)) if ext.len() <= 4 && !stem.is_empty() => stem,
+        _ => file.as_str(),
+    };
+
+    entry_from_display(stem)
+}
+
+fn split_artist_title(text: &str) -> (Option<&str>, &str) {
+    for separator in [" - ", " – ", " — "] {
+        if let Some((artist, title)) = text.split_once(separator) {
+            let (artist, title) = (artist.trim(), title.trim());
+            if !artist.is_empty() && !title.is_empty() {
+                return (Some(artist), title);
+            }
+        }
+    }
+    (None, text.trim())
+}
+
+/// Remove leading track numbers such as `1 - `, `1. ` or `03 `
+///
+/// A number followed by a plain space is only taken for a track number when
+/// zero-padded, so names like `50 Cent` or `99 Luftballons` are kept.
+fn strip_track_number(text: &str) -> &str {
+    let digits = text.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits == 0 || digits > 3 {
+        return text;
+    }
+
+    let rest = &text[digits..];
+    let padded = digits > 1 && text.starts_with('0');
+    let separators: &[&str] = if padded {
+        &[" - ", ". ", " "]
+    } else {
+        &[" - ", ". "]
+    };
+    for prefix in separators {
+        if let Some(stripped) = rest.strip_prefix(prefix) {
+            if !stripped.trim().is_empty() {
+                return stripped.trim_start();
+            }
+        }
+    }
+    text
+}
+
+/// Parse `m:ss` or `h:mm:ss` into milliseconds
+fn parse_clock_duration(text: &str) -> Option<u64> {
+    let parts: Vec<u64> = text
+        .trim()
+        .split(':')
+        .map(|p| p.parse().ok())
+        .collect::<Option<_>>()?;
+
+    let seconds = match parts.as_slice() {
+        [m, s] if *s < 60 => m * 60 + s,
+        [h, m, s] if *m < 60 && *s < 60 => h * 3600 + m * 60 + s,
+        _ => return None,
+    };
+    Some(seconds * 1000)
+}
+
+fn is_isrc(value: &str) -> bool {
+    value.len() == 12 && value.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Extract a `spotify:track:<id>` URI from a Spotify URI or open.spotify.com link
+pub fn spotify_track_uri(text: &str) -> Option<String> {
+    let text = text.trim();
+    let id = if let Some(id) = text.strip_prefix("spotify:track:") {
+        id
+    } else {
+        let (_, rest) = text.split_once("open.spotify.com/")?;
+        let rest = rest.split(['?', '#']).next()?;
+        let mut segments = rest.split('/').filter(|s| !s.is_empty());
+        let mut segment = segments.next()?;
+        if segment.starts_with("intl-") {
+            segment = segments.next()?;
+        }
+        if segment != "track" {
+            return None;
+        }
+        segments.next()?
+    };
+
+    let valid = id.len() == 22 && id.chars().all(|c| c.is_ascii_alphanumeric());
+    valid.then(|| format!("spotify:track:{}", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_m3u_with_extinf() {
+        let content = "#EXTM3U\n#EXTINF:215,Daft Punk - Digital Love\n/music/digital_love.mp3\n\
+                       /music/Radiohead - Reckoner.flac\n";
+        let entries = parse(content, ImportFormat::M3u).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].artist.as_deref(), Some("Daft Punk"));
+        assert_eq!(entries[0].title, "Digital Love");
+        assert_eq!(entries[0].duration_ms, Some(215_000));
+        assert_eq!(entries[1].artist.as_deref(), Some("Radiohead"));
+        assert_eq!(entries[1].title, "Reckoner");
+        assert_eq!(entries[1].index, 1);
+    }
+
+    #[test]
+    fn test_parse_xspf() {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <playlist version="1" xmlns="http://xspf.org/ns/0/">
+              <trackList>
+                <track>
+                  <title>Rock &amp; Roll</title>
+                  <creator>Led Zeppelin</creator>
+                  <duration>220000</duration>
+                  <identifier>isrc:usat29900609</identifier>
+                </track>
+              </trackList>
+            </playlist>"#;
+        let entries = parse(content, ImportFormat::Xspf).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].title, "Rock & Roll");
+        assert_eq!(entries[0].artist.as_deref(), Some("Led Zeppelin"));
+        assert_eq!(entries[0].duration_ms, Some(220_000));
+        assert_eq!(entries[0].isrc.as_deref(), Some("USAT29900609"));
+    }
+
+    #[test]
+    fn test_parse_csv_exportify_columns() {
+        let content = "Track URI,Track Name,Artist Name(s),Duration (ms),ISRC\n\
+                       spotify:track:4uLU6hMCjMI75M1A2tKUQC,\"Never Gonna Give You Up\",Rick Astley,213573,GBARL9300135\n\
+                       ,\"Hello, Goodbye\",The Beatles,,\n";
+        let entries = parse(content, ImportFormat::Csv).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[0].uri.as_deref(),
+            Some("spotify:track:4uLU6hMCjMI75M1A2tKUQC")
+        );
+        assert_eq!(entries[0].isrc.as_deref(), Some("GBARL9300135"));
+        assert_eq!(entries[0].duration_ms, Some(213_573));
+        assert_eq!(entries[1].title, "Hello, Goodbye");
+        assert_eq!(entries[1].uri, None);
+    }
+
+    #[test]
+    fn test_parse_text_lines() {
+        let content = "# my list\n01. Massive Attack - Teardrop (5:29)\n\
+                       https://open.spotify.com/intl-de/track/4uLU6hMCjMI75M1A2tKUQC?si=abc\n\
+                       Just A Title\n";
+        let entries = parse(content, ImportFormat::Text).unwrap();
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].artist.as_deref(), Some("Massive Attack"));
+        assert_eq!(entries[0].title, "Teardrop");
+        assert_eq!(entries[0].duration_ms, Some(329_000));
+        assert_eq!(
+            entries[1].uri.as_deref(),
+            Some("spotify:track:4uLU6hMCjMI75M1A2tKUQC")
+        );
+        assert_eq!(entries[2].artist, None);
+        assert_eq!(entries[2].title, "Just A Title");
+    }
+
+    #[test]
+    fn test_strip_track_number_keeps_names_starting_with_numbers() {
+        assert_eq!(strip_track_number("01 Teardrop"), "Teardrop");
+        assert_eq!(strip_track_number("1. Teardrop"), "Teardrop");
+        assert_eq!(strip_track_number("12 - Teardrop"), "Teardrop");
+        assert_eq!(
+            strip_track_number("50 Cent - In Da Club"),
+            "50 Cent - In Da Club"
+        );
+        assert_eq!(strip_track_number("99 Luftballons"), "99 Luftballons");
+
+        let entry = entry_from_display("3 Doors Down - Kryptonite");
+        assert_eq!(entry.artist.as_deref(), Some("3 Doors Down"));
+        assert_eq!(entry.title, "Kryptonite");
+    }
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(
+            ImportFormat::detect(Some("list.M3U8"), ""),
+            ImportFormat::M3u
+        );
+        assert_eq!(ImportFormat::detect(None, "#EXTM3U\n"), ImportFormat::M3u);
+        assert_eq!(
+            ImportFormat::detect(None, "<?xml version=\"1.0\"?>"),
+            ImportFormat::Xspf
+        );
+        assert_eq!(
+            ImportFormat::detect(None, "Title,Artist\nA,B"),
+            ImportFormat::Csv
+        );
+        assert_eq!(
+            ImportFormat::detect(None, "Artist - Title"),
+            ImportFormat::Text
+        );
+    }
+}
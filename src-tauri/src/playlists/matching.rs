@@ -0,0 +1,323 @@
+use serde::{Deserialize, Serialize};
+
+use super::import::ImportEntry;
+use crate::api::Track;
+
+/// Durations closer than this are considered identical
+const DURATION_TOLERANCE_MS: u64 = 3_000;
+
+/// Past this difference the duration score drops to zero
+const DURATION_CUTOFF_MS: u64 = 20_000;
+
+/// Candidates below this score are not offered as a match at all
+pub const MIN_MATCH_SCORE: f64 = 0.45;
+
+/// How confident we are that a candidate is the entry the user meant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchConfidence {
+    /// Same ISRC or Spotify URI
+    Exact,
+    High,
+    Medium,
+    Low,
+}
+
+impl MatchConfidence {
+    fn from_score(score: f64) -> Self {
+        if score >= 0.85 {
+            Self::High
+        } else if score >= 0.65 {
+            Self::Medium
+        } else {
+            Self::Low
+        }
+    }
+}
+
+/// Breakdown of how well a Spotify track matches an import entry
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MatchScore {
+    pub total: f64,
+    pub title: f64,
+    pub artist: Option<f64>,
+    pub duration: Option<f64>,
+    pub confidence: MatchConfidence,
+}
+
+impl MatchScore {
+    pub fn exact() -> Self {
+        Self {
+            total: 1.0,
+            title: 1.0,
+            artist: None,
+            duration: None,
+            confidence: MatchConfidence::Exact,
+        }
+    }
+}
+
+/// Score a candidate track against an import entry
+pub fn score(entry: &ImportEntry, track: &Track) -> MatchScore {
+    if entry.isrc.is_some() && entry.isrc.as_deref() == track.isrc() {
+        return MatchScore::exact();
+    }
+
+    let title = text_similarity(
+        &normalize_title(&entry.title),
+        &normalize_title(&track.name),
+    );
+
+    let artist = entry.artist.as_deref().map(|artist| {
+        let wanted = split_artists(artist);
+        track
+            .artists
+            .iter()
+            .map(|a| normalize(&a.name))
+            .flat_map(|candidate| wanted.iter().map(move |w| text_similarity(w, &candidate)))
+            .fold(0.0, f64::max)
+            // "Artist A, Artist B" against a track listing both artists together
+            .max(text_similarity(
+                &normalize(artist),
+                &normalize(&track.artist_names()),
+            ))
+    });
+
+    let duration = entry
+        .duration_ms
+        .map(|wanted| duration_similarity(wanted, track.duration_ms));
+
+    // Weights are redistributed over the fields the entry actually has
+    let mut weighted = title * 0.6;
+    let mut weights = 0.6;
+    if let Some(artist) = artist {
+        weighted += artist * 0.3;
+        weights += 0.3;
+    }
+    if let Some(duration) = duration {
+        weighted += duration * 0.1;
+        weights += 0.1;
+    }
+    let total = weighted / weights;
+
+    MatchScore {
+        total,
+        title,
+        artist,
+        duration,
+        confidence: MatchConfidence::from_score(total),
+    }
+}
+
+fn duration_similarity(wanted: u64, actual: u64) -> f64 {
+    let diff = wanted.abs_diff(actual);
+    if diff <= DURATION_TOLERANCE_MS {
+        1.0
+    } else if diff >= DURATION_CUTOFF_MS {
+        0.0
+    } else {
+        1.0 - (diff - DURATION_TOLERANCE_MS) as f64
+            / (DURATION_CUTOFF_MS - DURATION_TOLERANCE_MS) as f64
+    }
+}
+
+/// Similarity of two normalized strings in `0.0..=1.0`
+///
+/// Takes the better of edit distance and token overlap so both typos and
+/// reordered words ("Beatles, The") score well.
+pub fn text_similarity(a: &str, b: &str) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    if a == b {
+        return 1.0;
+    }
+
+    let edit = strsim::normalized_levenshtein(a, b);
+
+    let tokens_a: Vec<&str> = a.split_whitespace().collect();
+    let tokens_b: Vec<&str> = b.split_whitespace().collect();
+    let common = tokens_a.iter().filter(|t| tokens_b.contains(t)).count();
+    let overlap = (2 * common) as f64 / (tokens_a.len() + tokens_b.len()) as f64;
+
+    edit.max(overlap)
+}
+
+/// Lowercase, fold common accents, drop punctuation and collapse whitespace
+pub fn normalize(text: &str) -> String {
+    let folded: String = text
+        .to_lowercase()
+        .chars()
+        .map(fold_accent)
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect();
+
+    folded
+        .split_whitespace()
+        .filter(|t| *t != "the")
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Normalize a track title, removing version noise such as
+/// "(feat. X)", "- Remastered 2011" or "[Live]"
+pub fn normalize_title(title: &str) -> String {
+    let mut cleaned = String::with_capacity(title.len());
+    let mut depth = 0usize;
+    for c in title.chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => cleaned.push(c),
+            _ => {}
+        }
+    }
+
+    let cleaned = match cleaned.split_once(" - ") {
+        Some((head, _)) if !head.trim().is_empty() => head.to_string(),
+        _ => cleaned,
+    };
+
+    let cleaned = match find_ignore_case(&cleaned, " feat. ")
+        .or_else(|| find_ignore_case(&cleaned, " ft. "))
+    {
+        Some(pos) => &cleaned[..pos],
+        None => cleaned.as_str(),
+    };
+
+    let normalized = normalize(cleaned);
+    if normalized.is_empty() {
+        normalize(title)
+    } else {
+        normalized
+    }
+}
+
+/// Byte offset of an ASCII marker in `text`, ignoring case
+///
+/// Searches `text` itself rather than a lowercased copy, whose offsets can
+/// differ once non-ASCII characters change length.
+fn find_ignore_case(text: &str, marker: &str) -> Option<usize> {
+    text.char_indices().map(|(i, _)| i).find(|&i| {
+        text.as_bytes()[i..]
+            .get(..marker.len())
+            .is_some_and(|s| s.eq_ignore_ascii_case(marker.as_bytes()))
+    })
+}
+
+fn split_artists(artists: &str) -> Vec<String> {
+    artists
+        .split([',', ';', '&', '/'])
+        .flat_map(|a| a.split(" feat. "))
+        .flat_map(|a| a.split(" x "))
+        .map(normalize)
+        .filter(|a| !a.is_empty())
+        .collect()
+}
+
+fn fold_accent(c: char) -> char {
+    match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => 'a',
+        'ç' => 'c',
+        'è' | 'é' | 'ê' | 'ë' => 'e',
+        'ì' | 'í' | 'î' | 'ï' => 'i',
+        'ñ' => 'n',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => 'o',
+        'ù' | 'ú' | 'û' | 'ü' => 'u',
+        'ý' | 'ÿ' => 'y',
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{ExternalIds, SimplifiedAlbum, SimplifiedArtist};
+
+    fn track(name: &str, artist: &str, duration_ms: u64) -> Track {
+        Track {
+            id: Some("id".into()),
+            uri: "spotify:track:id".into(),
+            name: name.into(),
+            duration_ms,
+            explicit: false,
+            artists: vec![SimplifiedArtist {
+                id: None,
+                name: artist.into(),
+                uri: String::new(),
+            }],
+            album: SimplifiedAlbum::default(),
+            external_ids: ExternalIds::default(),
+            is_playable: None,
+            is_local: false,
+            popularity: None,
+            linked_from: None,
+        }
+    }
+
+    fn entry(title: &str, artist: Option<&str>, duration_ms: Option<u64>) -> ImportEntry {
+        ImportEntry {
+            title: title.into(),
+            artist: artist.map(str::to_string),
+            duration_ms,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_normalize_title_strips_versions() {
+        assert_eq!(
+            normalize_title("Come Together - Remastered 2009"),
+            "come together"
+        );
+        assert_eq!(
+            normalize_title("Lose Yourself (feat. Someone) [Live]"),
+            "lose yourself"
+        );
+        assert_eq!(normalize_title("Beyoncé ft. Jay-Z"), "beyonce");
+    }
+
+    #[test]
+    fn test_normalize_title_non_ascii_before_feat() {
+        // The Kelvin sign shrinks when lowercased, İ grows
+        assert_eq!(normalize_title("\u{212A}\u{212A}é FEAT. Someone"), "kke");
+        assert_eq!(
+            normalize_title("İstanbul feat. Someone"),
+            normalize("İstanbul")
+        );
+    }
+
+    #[test]
+    fn test_score_prefers_close_match() {
+        let wanted = entry("Come Together", Some("The Beatles"), Some(259_000));
+        let good = score(
+            &wanted,
+            &track("Come Together - Remastered 2009", "The Beatles", 260_000),
+        );
+        let cover = score(&wanted, &track("Come Together", "Tribute Band", 200_000));
+
+        assert_eq!(good.confidence, MatchConfidence::High);
+        assert!(good.total > cover.total);
+        assert_ne!(cover.confidence, MatchConfidence::High);
+    }
+
+    #[test]
+    fn test_isrc_is_exact() {
+        let mut wanted = entry("Whatever", None, None);
+        wanted.isrc = Some("GBAYE0601498".into());
+        let mut candidate = track("Something Else", "Someone", 1);
+        candidate.external_ids.isrc = Some("GBAYE0601498".into());
+
+        assert_eq!(
+            score(&wanted, &candidate).confidence,
+            MatchConfidence::Exact
+        );
+    }
+
+    #[test]
+    fn test_duration_tolerance() {
+        assert_eq!(duration_similarity(200_000, 202_500), 1.0);
+        assert_eq!(duration_similarity(200_000, 230_000), 0.0);
+        assert!(duration_similarity(200_000, 210_000) > 0.5);
+    }
+}
@@ -0,0 +1,14 @@
+pub mod backup;
+pub mod cleanup;
+pub mod commands;
+pub mod diff;
+pub mod import;
+pub mod matching;
+pub mod reorder;
+pub mod setops;
+
+pub use backup::*;
+pub use cleanup::*;
+pub use commands::*;
+pub use reorder::*;
+pub use setops::*;
@@ -0,0 +1,476 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter, State};
+
+use super::{backup, diff};
+use crate::api::{PlaylistItem, SpotifyApi};
+use crate::auth::AppAuthState;
+use crate::error::AppError;
+
+/// Keys a playlist can be sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReorderKey {
+    AddedAt,
+    AddedBy,
+    Title,
+    Artist,
+    Album,
+    ReleaseDate,
+    Duration,
+    Popularity,
+    Tempo,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaylistSort {
+    pub key: ReorderKey,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// One Spotify "reorder items" operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReorderMove {
+    pub range_start: usize,
+    pub range_length: usize,
+    pub insert_before: usize,
+}
+
+impl ReorderMove {
+    /// Position of the range's first item once the move is applied
+    fn destination(&self) -> usize {
+        if self.insert_before > self.range_start {
+            self.insert_before - self.range_length
+        } else {
+            self.insert_before
+        }
+    }
+
+    /// The move that puts the range back where it was
+    fn inverse(&self) -> Self {
+        let destination = self.destination();
+        Self {
+            range_start: destination,
+            range_length: self.range_length,
+            insert_before: if self.range_start > destination {
+                self.range_start + self.range_length
+            } else {
+                self.range_start
+            },
+        }
+    }
+
+    fn apply<T>(&self, items: &mut Vec<T>) {
+        let range: Vec<T> = items
+            .drain(self.range_start..self.range_start + self.range_length)
+            .collect();
+        let destination = self.destination();
+        items.splice(destination..destination, range);
+    }
+}
+
+/// Moves computed for a reorder, with the snapshot they apply to
+#[derive(Debug, Clone, Serialize)]
+pub struct ReorderPlan {
+    pub snapshot_id: String,
+    pub moves: Vec<ReorderMove>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ReorderProgress {
+    playlist_id: String,
+    done: usize,
+    total: usize,
+    rolling_back: bool,
+}
+
+/// Compute the moves turning `0..n` into `order`
+///
+/// `order[i]` is the current position of the item that should end up at `i`.
+/// Items on the longest run already in relative order stay put; every other item
+/// is moved right behind its new predecessor, and neighbours that travel together
+/// are moved as one range.
+pub fn plan_moves(order: &[usize]) -> Vec<ReorderMove> {
+    let stable = diff::longest_increasing_subsequence(order);
+    let mut current: Vec<usize> = (0..order.len()).collect();
+    let mut moves = Vec::new();
+
+    let mut i = 0;
+    while i < order.len() {
+        if stable[i] {
+            i += 1;
+            continue;
+        }
+
+        let range_start = current.iter().position(|&x| x == order[i]).unwrap();
+        let mut range_length = 1;
+        while i + range_length < order.len()
+            && !stable[i + range_length]
+            && current.get(range_start + range_length) == Some(&order[i + range_length])
+        {
+            range_length += 1;
+        }
+
+        let insert_before = match i {
+            0 => 0,
+            _ => current.iter().position(|&x| x == order[i - 1]).unwrap() + 1,
+        };
+
+        if insert_before != range_start {
+            let step = ReorderMove {
+                range_start,
+                range_length,
+                insert_before,
+            };
+            step.apply(&mut current);
+            moves.push(step);
+        }
+        i += range_length;
+    }
+
+    moves
+}
+
+fn compare_optional<T: PartialOrd>(a: Option<T>, b: Option<T>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Value of an item for a sort key; all items of one sort share a variant
+#[derive(Debug, PartialEq, PartialOrd)]
+enum SortValue {
+    Text(String),
+    Time(DateTime<Utc>),
+    Number(f64),
+}
+
+fn sort_value(
+    item: &PlaylistItem,
+    key: ReorderKey,
+    tempos: &HashMap<String, f64>,
+) -> Option<SortValue> {
+    let text = |s: &str| SortValue::Text(s.to_lowercase());
+    let track = item.track.as_ref();
+    match key {
+        ReorderKey::AddedAt => item.added_at.map(SortValue::Time),
+        ReorderKey::AddedBy => item.added_by.as_ref().map(|u| text(&u.id)),
+        ReorderKey::Title => track.map(|t| text(&t.name)),
+        ReorderKey::Artist => track.and_then(|t| t.artists.first()).map(|a| text(&a.name)),
+        ReorderKey::Album => track.map(|t| text(&t.album.name)),
+        ReorderKey::ReleaseDate => track
+            .and_then(|t| t.album.release_date.clone())
+            .map(SortValue::Text),
+        ReorderKey::Duration => track.map(|t| SortValue::Number(t.duration_ms as f64)),
+        ReorderKey::Popularity => track
+            .and_then(|t| t.popularity)
+            .map(|p| SortValue::Number(p as f64)),
+        ReorderKey::Tempo => track
+            .and_then(|t| t.id.as_ref())
+            .and_then(|id| tempos.get(id))
+            .map(|&tempo| SortValue::Number(tempo)),
+    }
+}
+
+/// Desired order of items for a sort, as current positions
+///
+/// The sort is stable and items without a value for the key go last either way.
+pub fn sorted_order(
+    items: &[PlaylistItem],
+    sort: PlaylistSort,
+    tempos: &HashMap<String, f64>,
+) -> Vec<usize> {
+    let values: Vec<Option<SortValue>> = items
+        .iter()
+        .map(|item| sort_value(item, sort.key, tempos))
+        .collect();
+    let mut order: Vec<usize> = (0..items.len()).collect();
+
+    order.sort_by(|&a, &b| {
+        let (a, b) = (values[a].as_ref(), values[b].as_ref());
+        let ordering = compare_optional(a, b);
+        // Missing values stay last in descending order too
+        if sort.descending && a.is_some() == b.is_some() {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+
+    order
+}
+
+fn validate_order(order: &[usize], len: usize) -> Result<(), AppError> {
+    let mut seen = vec![false; len];
+    if order.len() != len {
+        return Err(AppError::InvalidInput(format!(
+            "Order has {} positions but the playlist has {} items",
+            order.len(),
+            len
+        )));
+    }
+    for &position in order {
+        if position >= len || std::mem::replace(&mut seen[position], true) {
+            return Err(AppError::InvalidInput(format!(
+                "Invalid position {} in order",
+                position
+            )));
+        }
+    }
+    Ok(())
+}
+
+async fn build_plan(
+    state: &AppAuthState,
+    playlist_id: &str,
+    snapshot_id: Option<&str>,
+    order: Option<Vec<usize>>,
+    sort: Option<PlaylistSort>,
+) -> Result<ReorderPlan, AppError> {
+    let api = SpotifyApi::new(state);
+    let current = api.playlist(playlist_id).await?.snapshot_id;
+    if let Some(expected) = snapshot_id {
+        if expected != current {
+            return Err(AppError::Conflict(
+                "Playlist changed since the order was chosen".into(),
+            ));
+        }
+    }
+
+    let items = api.playlist_items(playlist_id).await?;
+    let order = match (order, sort) {
+        (Some(order), _) => order,
+        (None, Some(sort)) => {
+            let tempos = if sort.key == ReorderKey::Tempo {
+                let ids: Vec<String> = items
+                    .iter()
+                    .filter_map(|i| i.track.as_ref()?.id.clone())
+                    .collect();
+                api.audio_features(&ids)
+                    .await?
+                    .into_iter()
+                    .map(|f| (f.id, f.tempo))
+                    .collect()
+            } else {
+                HashMap::new()
+            };
+            sorted_order(&items, sort, &tempos)
+        }
+        (None, None) => {
+            return Err(AppError::InvalidInput(
+                "Either an order or a sort is required".into(),
+            ))
+        }
+    };
+    validate_order(&order, items.len())?;
+
+    Ok(ReorderPlan {
+        snapshot_id: current,
+        moves: plan_moves(&order),
+    })
+}
+
+/// Preview the moves needed to reorder a playlist
+#[tauri::command]
+pub async fn plan_playlist_reorder(
+    playlist_id: String,
+    snapshot_id: Option<String>,
+    order: Option<Vec<usize>>,
+    sort: Option<PlaylistSort>,
+    state: State<'_, AppAuthState>,
+) -> Result<ReorderPlan, AppError> {
+    build_plan(&state, &playlist_id, snapshot_id.as_deref(), order, sort).await
+}
+
+/// Reorder a playlist on Spotify
+///
+/// Either `order` (current positions in their desired order) or `sort` must be
+/// given. Moves are chained through `snapshot_id` and reported through
+/// `playlist-reorder:progress`; if one fails, the moves already applied are undone.
+#[tauri::command]
+pub async fn reorder_playlist(
+    app: AppHandle,
+    playlist_id: String,
+    snapshot_id: Option<String>,
+    order: Option<Vec<usize>>,
+    sort: Option<PlaylistSort>,
+    state: State<'_, AppAuthState>,
+) -> Result<ReorderPlan, AppError> {
+    let plan = build_plan(&state, &playlist_id, snapshot_id.as_deref(), order, sort).await?;
+    if plan.moves.is_empty() {
+        return Ok(plan);
+    }
+
+    backup::backup_playlist(&state, &playlist_id, backup::max_versions(&state)).await?;
+
+    let api = SpotifyApi::new(&state);
+    let total = plan.moves.len();
+    let progress = |done: usize, rolling_back: bool| {
+        let _ = app.emit(
+            "playlist-reorder:progress",
+            ReorderProgress {
+                playlist_id: playlist_id.clone(),
+                done,
+                total,
+                rolling_back,
+            },
+        );
+    };
+
+    let mut snapshot = plan.snapshot_id.clone();
+    for (done, step) in plan.moves.iter().enumerate() {
+        let result = api
+            .reorder_playlist_items(
+                &playlist_id,
+                step.range_start,
+                step.range_length,
+                step.insert_before,
+                &snapshot,
+            )
+            .await;
+
+        match result {
+            Ok(next) => {
+                snapshot = next;
+                progress(done + 1, false);
+            }
+            Err(e) => {
+                log::error!(
+                    "Reorder of {} failed after {} moves: {}",
+                    playlist_id,
+                    done,
+                    e
+                );
+                for (undone, applied) in plan.moves[..done].iter().rev().enumerate() {
+                    let inverse = applied.inverse();
+                    snapshot = api
+                        .reorder_playlist_items(
+                            &playlist_id,
+                            inverse.range_start,
+                            inverse.range_length,
+                            inverse.insert_before,
+                            &snapshot,
+                        )
+                        .await
+                        .map_err(|rollback| {
+                            AppError::Conflict(format!(
+                                "Reorder failed ({}) and rollback failed ({}); restore the latest backup",
+                                e, rollback
+                            ))
+                        })?;
+                    progress(done - undone - 1, true);
+                }
+                return Err(e);
+            }
+        }
+    }
+
+    log::info!("Reordered playlist {} with {} moves", playlist_id, total);
+    Ok(ReorderPlan {
+        snapshot_id: snapshot,
+        moves: plan.moves,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(order: &[usize]) -> (Vec<usize>, Vec<ReorderMove>) {
+        let moves = plan_moves(order);
+        let mut items: Vec<usize> = (0..order.len()).collect();
+        for step in &moves {
+            step.apply(&mut items);
+        }
+        (items, moves)
+    }
+
+    #[test]
+    fn test_single_move_to_top() {
+        let (items, moves) = run(&[4, 0, 1, 2, 3]);
+        assert_eq!(items, vec![4, 0, 1, 2, 3]);
+        assert_eq!(
+            moves,
+            vec![ReorderMove {
+                range_start: 4,
+                range_length: 1,
+                insert_before: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_block_is_moved_as_range() {
+        let (items, moves) = run(&[3, 4, 0, 1, 2]);
+        assert_eq!(items, vec![3, 4, 0, 1, 2]);
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].range_length, 2);
+    }
+
+    #[test]
+    fn test_reverse_and_identity() {
+        let (items, moves) = run(&[5, 4, 3, 2, 1, 0]);
+        assert_eq!(items, vec![5, 4, 3, 2, 1, 0]);
+        assert_eq!(moves.len(), 5);
+
+        assert!(run(&[0, 1, 2]).1.is_empty());
+    }
+
+    #[test]
+    fn test_permutations_round_trip() {
+        // Deterministic pseudo-random permutations
+        let mut seed = 42u64;
+        for len in 1..40 {
+            let mut order: Vec<usize> = (0..len).collect();
+            for i in (1..len).rev() {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                order.swap(i, (seed >> 33) as usize % (i + 1));
+            }
+
+            let (items, moves) = run(&order);
+            assert_eq!(items, order);
+
+            let mut undone = items.clone();
+            for step in moves.iter().rev() {
+                step.inverse().apply(&mut undone);
+            }
+            assert_eq!(undone, (0..len).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_validate_order() {
+        assert!(validate_order(&[1, 0, 2], 3).is_ok());
+        assert!(validate_order(&[1, 1, 2], 3).is_err());
+        assert!(validate_order(&[0, 1], 3).is_err());
+        assert!(validate_order(&[0, 1, 3], 3).is_err());
+    }
+
+    #[test]
+    fn test_sort_descending_keeps_missing_values_last() {
+        let item = |id: &str| -> PlaylistItem {
+            serde_json::from_value(serde_json::json!({
+                "added_at": null,
+                "track": { "id": id, "uri": format!("spotify:track:{}", id), "name": id }
+            }))
+            .unwrap()
+        };
+        let items = vec![item("slow"), item("unknown"), item("fast")];
+        let tempos = HashMap::from([("slow".to_string(), 80.0), ("fast".to_string(), 140.0)]);
+
+        let sort = |descending| PlaylistSort {
+            key: ReorderKey::Tempo,
+            descending,
+        };
+        assert_eq!(sorted_order(&items, sort(false), &tempos), vec![0, 2, 1]);
+        assert_eq!(sorted_order(&items, sort(true), &tempos), vec![2, 0, 1]);
+    }
+}
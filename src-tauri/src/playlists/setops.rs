@@ -0,0 +1,525 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tauri::State;
+
+use super::backup;
+use crate::api::{SpotifyApi, Track};
+use crate::auth::{self, AppAuthState, AuthError};
+use crate::error::AppError;
+
+/// Where tracks are read from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PlaylistSource {
+    Playlist { id: String },
+    LikedSongs,
+}
+
+/// Set operation applied across the sources, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SetOperation {
+    /// Tracks in any source
+    Union,
+    /// Tracks in every source
+    Intersection,
+    /// Tracks in the first source and none of the others
+    Difference,
+    /// Tracks in exactly one source
+    SymmetricDifference,
+}
+
+/// How an existing playlist is written to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WriteMode {
+    Replace,
+    Append,
+}
+
+/// Where results are written
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PlaylistTarget {
+    New {
+        name: String,
+        description: Option<String>,
+        #[serde(default)]
+        public: bool,
+    },
+    Existing {
+        id: String,
+        mode: WriteMode,
+    },
+}
+
+/// Attribute a playlist can be split by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SplitAttribute {
+    Year,
+    Decade,
+    Artist,
+    AddedBy,
+    Genre,
+}
+
+/// A track read from a source with the item metadata used for splitting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceTrack {
+    pub track: Track,
+    pub added_at: Option<DateTime<Utc>>,
+    pub added_by: Option<String>,
+}
+
+/// One group of a split
+#[derive(Debug, Clone, Serialize)]
+pub struct SplitGroup {
+    pub key: String,
+    pub tracks: Vec<Track>,
+}
+
+/// A playlist written by a set operation or split
+#[derive(Debug, Clone, Serialize)]
+pub struct WriteResult {
+    pub playlist_id: String,
+    pub name: String,
+    pub snapshot_id: Option<String>,
+    pub track_count: usize,
+}
+
+/// Read the tracks of a source; local files and unavailable episodes are skipped
+pub async fn load_source(
+    state: &AppAuthState,
+    source: &PlaylistSource,
+) -> Result<Vec<SourceTrack>, AppError> {
+    let api = SpotifyApi::new(state);
+
+    match source {
+        PlaylistSource::Playlist { id } => Ok(api
+            .playlist_items(id)
+            .await?
+            .into_iter()
+            .filter(|i| !i.is_local)
+            .filter_map(|i| {
+                Some(SourceTrack {
+                    track: i.track?,
+                    added_at: i.added_at,
+                    added_by: i.added_by.map(|u| u.id),
+                })
+            })
+            .collect()),
+        PlaylistSource::LikedSongs => {
+            let user = auth::current_user(state).map(|u| u.id);
+            Ok(api
+                .saved_tracks()
+                .await?
+                .into_iter()
+                .map(|s| SourceTrack {
+                    track: s.track,
+                    added_at: Some(s.added_at),
+                    added_by: user.clone(),
+                })
+                .collect())
+        }
+    }
+}
+
+/// What makes two tracks the same; relinked tracks count as their original
+fn identity(track: &Track, match_by_isrc: bool) -> String {
+    match track.isrc() {
+        Some(isrc) if match_by_isrc => isrc.to_ascii_uppercase(),
+        _ => track.original_uri().to_string(),
+    }
+}
+
+/// Combine sources with a set operation
+///
+/// Results keep the order in which tracks first appear across the sources and
+/// contain each track once. With `match_by_isrc`, different releases of the same
+/// recording count as the same track.
+pub fn combine(
+    operation: SetOperation,
+    sources: &[Vec<SourceTrack>],
+    match_by_isrc: bool,
+) -> Vec<SourceTrack> {
+    // identity -> number of sources containing it
+    let mut membership: HashMap<String, usize> = HashMap::new();
+    for source in sources {
+        let unique: HashSet<String> = source
+            .iter()
+            .map(|t| identity(&t.track, match_by_isrc))
+            .collect();
+        for key in unique {
+            *membership.entry(key).or_insert(0) += 1;
+        }
+    }
+
+    let first: HashSet<String> = sources
+        .first()
+        .map(|s| {
+            s.iter()
+                .map(|t| identity(&t.track, match_by_isrc))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let keep = |key: &str| -> bool {
+        let count = membership.get(key).copied().unwrap_or(0);
+        match operation {
+            SetOperation::Union => true,
+            SetOperation::Intersection => count == sources.len(),
+            SetOperation::Difference => first.contains(key) && count == 1,
+            SetOperation::SymmetricDifference => count == 1,
+        }
+    };
+
+    let mut seen = HashSet::new();
+    sources
+        .iter()
+        .flatten()
+        .filter(|t| {
+            let key = identity(&t.track, match_by_isrc);
+            keep(&key) && seen.insert(key)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Group tracks by an attribute, keeping playlist order inside each group
+///
+/// `genres` maps artist ids to their genres and is only used for `Genre`, where a
+/// track is filed under the first genre of its primary artist.
+pub fn split(
+    tracks: &[SourceTrack],
+    attribute: SplitAttribute,
+    genres: &HashMap<String, Vec<String>>,
+) -> Vec<SplitGroup> {
+    const UNKNOWN: &str = "Unknown";
+    let mut groups: BTreeMap<String, Vec<Track>> = BTreeMap::new();
+
+    for item in tracks {
+        let track = &item.track;
+        let year = track
+            .album
+            .release_date
+            .as_deref()
+            .and_then(|d| d.get(..4))
+            .and_then(|y| y.parse::<i32>().ok());
+        let primary = track.artists.first();
+
+        let key = match attribute {
+            SplitAttribute::Year => year.map(|y| y.to_string()),
+            SplitAttribute::Decade => year.map(|y| format!("{}s", y - y.rem_euclid(10))),
+            SplitAttribute::Artist => primary.map(|a| a.name.clone()),
+            SplitAttribute::AddedBy => item.added_by.clone(),
+            SplitAttribute::Genre => primary
+                .and_then(|a| a.id.as_ref())
+                .and_then(|id| genres.get(id))
+                .and_then(|g| g.first())
+                .cloned(),
+        };
+
+        groups
+            .entry(key.unwrap_or_else(|| UNKNOWN.to_string()))
+            .or_default()
+            .push(track.clone());
+    }
+
+    groups
+        .into_iter()
+        .map(|(key, tracks)| SplitGroup { key, tracks })
+        .collect()
+}
+
+/// Write URIs to a new or existing playlist
+///
+/// Replacing an existing playlist takes a backup first.
+pub async fn write_to_target(
+    state: &AppAuthState,
+    target: &PlaylistTarget,
+    uris: &[String],
+) -> Result<WriteResult, AppError> {
+    let api = SpotifyApi::new(state);
+
+    match target {
+        PlaylistTarget::New {
+            name,
+            description,
+            public,
+        } => {
+            let user = auth::current_user(state).ok_or(AuthError::NotAuthenticated)?;
+            let playlist = api
+                .create_playlist(&user.id, name, description.as_deref(), *public)
+                .await?;
+            let snapshot_id = api.add_playlist_items(&playlist.id, uris).await?;
+            Ok(WriteResult {
+                playlist_id: playlist.id,
+                name: playlist.name,
+                snapshot_id: snapshot_id.or(Some(playlist.snapshot_id)),
+                track_count: uris.len(),
+            })
+        }
+        PlaylistTarget::Existing { id, mode } => {
+            let playlist = api.playlist(id).await?;
+            let snapshot_id = match mode {
+                WriteMode::Replace => {
+                    backup::backup_playlist(state, id, backup::max_versions(state)).await?;
+                    Some(api.replace_playlist_items(id, uris).await?)
+                }
+                WriteMode::Append => api.add_playlist_items(id, uris).await?,
+            };
+            Ok(WriteResult {
+                playlist_id: playlist.id,
+                name: playlist.name,
+                snapshot_id,
+                track_count: uris.len(),
+            })
+        }
+    }
+}
+
+async fn load_sources(
+    state: &AppAuthState,
+    sources: &[PlaylistSource],
+) -> Result<Vec<Vec<SourceTrack>>, AppError> {
+    if sources.is_empty() {
+        return Err(AppError::InvalidInput(
+            "At least one source is required".into(),
+        ));
+    }
+
+    let mut loaded = Vec::with_capacity(sources.len());
+    for source in sources {
+        loaded.push(load_source(state, source).await?);
+    }
+    Ok(loaded)
+}
+
+async fn split_source(
+    state: &AppAuthState,
+    source: &PlaylistSource,
+    attribute: SplitAttribute,
+) -> Result<Vec<SplitGroup>, AppError> {
+    let tracks = load_source(state, source).await?;
+
+    let genres = if attribute == SplitAttribute::Genre {
+        let ids: Vec<String> = tracks
+            .iter()
+            .filter_map(|t| t.track.artists.first()?.id.clone())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        SpotifyApi::new(state)
+            .artists(&ids)
+            .await?
+            .into_iter()
+            .map(|a| (a.id, a.genres))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    Ok(split(&tracks, attribute, &genres))
+}
+
+/// Compute a set operation without writing anything
+#[tauri::command]
+pub async fn preview_playlist_set_operation(
+    operation: SetOperation,
+    sources: Vec<PlaylistSource>,
+    match_by_isrc: Option<bool>,
+    state: State<'_, AppAuthState>,
+) -> Result<Vec<Track>, AppError> {
+    let loaded = load_sources(&state, &sources).await?;
+    Ok(combine(operation, &loaded, match_by_isrc.unwrap_or(false))
+        .into_iter()
+        .map(|t| t.track)
+        .collect())
+}
+
+/// Compute a set operation and write the result to a playlist
+#[tauri::command]
+pub async fn apply_playlist_set_operation(
+    operation: SetOperation,
+    sources: Vec<PlaylistSource>,
+    match_by_isrc: Option<bool>,
+    target: PlaylistTarget,
+    state: State<'_, AppAuthState>,
+) -> Result<WriteResult, AppError> {
+    let loaded = load_sources(&state, &sources).await?;
+    let uris: Vec<String> = combine(operation, &loaded, match_by_isrc.unwrap_or(false))
+        .into_iter()
+        .map(|t| t.track.original_uri().to_string())
+        .collect();
+
+    write_to_target(&state, &target, &uris).await
+}
+
+/// Group a playlist's tracks by an attribute without writing anything
+#[tauri::command]
+pub async fn preview_playlist_split(
+    source: PlaylistSource,
+    attribute: SplitAttribute,
+    state: State<'_, AppAuthState>,
+) -> Result<Vec<SplitGroup>, AppError> {
+    split_source(&state, &source, attribute).await
+}
+
+/// Split a playlist into one new playlist per group
+///
+/// `name_template` may contain `{group}`, replaced by the group key. Groups with
+/// fewer than `min_tracks` tracks are skipped.
+#[tauri::command]
+pub async fn apply_playlist_split(
+    source: PlaylistSource,
+    attribute: SplitAttribute,
+    name_template: String,
+    min_tracks: Option<usize>,
+    public: Option<bool>,
+    state: State<'_, AppAuthState>,
+) -> Result<Vec<WriteResult>, AppError> {
+    if name_template.trim().is_empty() {
+        return Err(AppError::InvalidInput("Playlist name is required".into()));
+    }
+
+    let groups = split_source(&state, &source, attribute).await?;
+    let mut results = Vec::new();
+
+    for group in groups
+        .into_iter()
+        .filter(|g| g.tracks.len() >= min_tracks.unwrap_or(1))
+    {
+        let name = if name_template.contains("{group}") {
+            name_template.replace("{group}", &group.key)
+        } else {
+            format!("{} - {}", name_template.trim(), group.key)
+        };
+        let uris: Vec<String> = group
+            .tracks
+            .iter()
+            .map(|t| t.original_uri().to_string())
+            .collect();
+        let target = PlaylistTarget::New {
+            name,
+            description: None,
+            public: public.unwrap_or(false),
+        };
+        results.push(write_to_target(&state, &target, &uris).await?);
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{ExternalIds, LinkedTrack, SimplifiedAlbum, SimplifiedArtist};
+
+    fn source(ids: &[&str]) -> Vec<SourceTrack> {
+        ids.iter()
+            .map(|id| SourceTrack {
+                track: Track {
+                    id: Some(id.to_string()),
+                    uri: format!("spotify:track:{}", id),
+                    name: id.to_string(),
+                    duration_ms: 0,
+                    explicit: false,
+                    artists: vec![SimplifiedArtist {
+                        id: Some(format!("artist-{}", id)),
+                        name: format!("Artist {}", id),
+                        uri: String::new(),
+                    }],
+                    album: SimplifiedAlbum {
+                        release_date: Some(if *id < "m" { "1994-05-01" } else { "2003" }.into()),
+                        ..Default::default()
+                    },
+                    external_ids: ExternalIds::default(),
+                    is_playable: None,
+                    is_local: false,
+                    popularity: None,
+                    linked_from: None,
+                },
+                added_at: None,
+                added_by: Some(if *id < "c" { "alice" } else { "bob" }.into()),
+            })
+            .collect()
+    }
+
+    fn ids(tracks: &[SourceTrack]) -> Vec<&str> {
+        tracks.iter().map(|t| t.track.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let sources = vec![
+            source(&["a", "b", "c", "a"]),
+            source(&["c", "d"]),
+            source(&["c", "b"]),
+        ];
+
+        assert_eq!(
+            ids(&combine(SetOperation::Union, &sources, false)),
+            vec!["a", "b", "c", "d"]
+        );
+        assert_eq!(
+            ids(&combine(SetOperation::Intersection, &sources, false)),
+            vec!["c"]
+        );
+        assert_eq!(
+            ids(&combine(SetOperation::Difference, &sources, false)),
+            vec!["a"]
+        );
+        assert_eq!(
+            ids(&combine(SetOperation::SymmetricDifference, &sources, false)),
+            vec!["a", "d"]
+        );
+    }
+
+    #[test]
+    fn test_match_by_isrc() {
+        let mut a = source(&["a"]);
+        let mut b = source(&["b"]);
+        a[0].track.external_ids.isrc = Some("usrc17607839".into());
+        b[0].track.external_ids.isrc = Some("USRC17607839".into());
+
+        assert_eq!(
+            combine(SetOperation::Difference, &[a.clone(), b.clone()], false).len(),
+            1
+        );
+        assert!(combine(SetOperation::Difference, &[a, b], true).is_empty());
+    }
+
+    #[test]
+    fn test_relinked_tracks_match_their_original() {
+        let a = source(&["a"]);
+        let mut relinked = source(&["x"]);
+        relinked[0].track.linked_from = Some(LinkedTrack {
+            id: Some("a".into()),
+            uri: "spotify:track:a".into(),
+        });
+
+        let union = combine(SetOperation::Union, &[a.clone(), relinked.clone()], false);
+        assert_eq!(union.len(), 1);
+        assert!(combine(SetOperation::Difference, &[relinked, a], false).is_empty());
+    }
+
+    #[test]
+    fn test_split_by_decade_and_added_by() {
+        let tracks = source(&["a", "b", "x"]);
+
+        let decades = split(&tracks, SplitAttribute::Decade, &HashMap::new());
+        assert_eq!(decades.len(), 2);
+        assert_eq!(decades[0].key, "1990s");
+        assert_eq!(decades[0].tracks.len(), 2);
+        assert_eq!(decades[1].key, "2000s");
+
+        let added_by = split(&tracks, SplitAttribute::AddedBy, &HashMap::new());
+        assert_eq!(added_by[0].key, "alice");
+        assert_eq!(added_by[1].key, "bob");
+
+        let genres = split(&tracks, SplitAttribute::Genre, &HashMap::new());
+        assert_eq!(genres[0].key, "Unknown");
+    }
+}
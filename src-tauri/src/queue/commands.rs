@@ -0,0 +1,262 @@
+use chrono::Utc;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::broadcast::error::RecvError;
+
+use super::model::{LocalQueue, QueueItem};
+use crate::api::{Playlist, SpotifyApi};
+use crate::auth::{self, AppAuthState, AuthError};
+use crate::error::AppError;
+use crate::playback::events::PlaybackEvent;
+use crate::playback::{PlaybackHub, PlaybackUpdate};
+use crate::store;
+
+const QUEUE_FILE: &str = "queue.json";
+
+/// How long before the end of the current track the next item is handed over
+const FEED_LEAD_MS: u64 = 15_000;
+
+/// Load the local queue of the signed-in account
+pub fn load_queue(state: &AppAuthState) -> Result<LocalQueue, AppError> {
+    Ok(store::load_json(&store::account_file(state, QUEUE_FILE)?)?.unwrap_or_default())
+}
+
+/// Load, modify and save the queue in one locked step
+fn update<T>(
+    state: &AppAuthState,
+    f: impl FnOnce(&mut LocalQueue) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    store::update_json(&store::account_file(state, QUEUE_FILE)?, f)
+}
+
+/// Apply a change and tell every window about the new queue
+fn change(
+    app: &AppHandle,
+    state: &AppAuthState,
+    f: impl FnOnce(&mut LocalQueue) -> Result<(), AppError>,
+) -> Result<LocalQueue, AppError> {
+    let queue = update(state, |queue| {
+        f(queue)?;
+        Ok(queue.clone())
+    })?;
+    let _ = app.emit("queue:changed", &queue);
+    Ok(queue)
+}
+
+/// Hand the next item to Spotify's queue when the current track is about to end
+async fn feed(
+    app: &AppHandle,
+    state: &AppAuthState,
+    update: &PlaybackUpdate,
+) -> Result<(), AppError> {
+    for event in &update.events {
+        if let PlaybackEvent::TrackChanged {
+            track: Some(track), ..
+        } = event
+        {
+            if load_queue(state)?.pending.is_some() {
+                change(app, state, |queue| {
+                    queue.track_started(track);
+                    Ok(())
+                })?;
+            }
+        }
+    }
+
+    let ending = update
+        .snapshot
+        .as_ref()
+        .and_then(|s| s.remaining_ms())
+        .map(|remaining| remaining <= FEED_LEAD_MS)
+        .unwrap_or(false);
+    if !ending {
+        return Ok(());
+    }
+
+    let now = Utc::now();
+    let queue = load_queue(state)?;
+    if queue.items.is_empty() || !queue.is_waiting(now) {
+        return Ok(());
+    }
+    let next = self::update(state, |queue| {
+        Ok(if queue.is_waiting(now) {
+            queue.take_next(now)
+        } else {
+            None
+        })
+    })?;
+    let Some(item) = next else {
+        return Ok(());
+    };
+
+    if let Err(e) = SpotifyApi::new(state).add_to_queue(&item.uri).await {
+        change(app, state, |queue| {
+            queue.restore(item);
+            Ok(())
+        })?;
+        return Err(e);
+    }
+
+    let _ = app.emit("queue:fed", &item);
+    let _ = app.emit("queue:changed", &load_queue(state)?);
+    Ok(())
+}
+
+/// Start the background job feeding the local queue to the active device
+pub fn spawn_queue_feeder(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut updates = app.state::<PlaybackHub>().subscribe();
+        loop {
+            let update = match updates.recv().await {
+                Ok(update) => update,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+
+            let state = app.state::<AppAuthState>();
+            if auth::current_user(&state).is_none() {
+                continue;
+            }
+            if let Err(e) = feed(&app, &state, &update).await {
+                log::warn!("Failed to feed the local queue: {}", e);
+            }
+        }
+    });
+}
+
+/// Insert URIs into the local queue at `index` (the end when omitted)
+pub fn enqueue(
+    app: &AppHandle,
+    state: &AppAuthState,
+    uris: &[String],
+    index: Option<usize>,
+) -> Result<LocalQueue, AppError> {
+    change(app, state, |queue| queue.insert(uris, index))
+}
+
+/// Get the local queue
+#[tauri::command]
+pub fn get_local_queue(state: State<AppAuthState>) -> Result<LocalQueue, AppError> {
+    load_queue(&state)
+}
+
+/// Insert URIs into the local queue at `index` (the end when omitted)
+#[tauri::command]
+pub fn add_to_local_queue(
+    app: AppHandle,
+    uris: Vec<String>,
+    index: Option<usize>,
+    state: State<AppAuthState>,
+) -> Result<LocalQueue, AppError> {
+    enqueue(&app, &state, &uris, index)
+}
+
+/// Remove items from the local queue
+#[tauri::command]
+pub fn remove_from_local_queue(
+    app: AppHandle,
+    ids: Vec<String>,
+    state: State<AppAuthState>,
+) -> Result<LocalQueue, AppError> {
+    change(&app, &state, |queue| {
+        queue.remove(&ids);
+        Ok(())
+    })
+}
+
+/// Move a local queue item to a new position
+#[tauri::command]
+pub fn move_local_queue_item(
+    app: AppHandle,
+    id: String,
+    index: usize,
+    state: State<AppAuthState>,
+) -> Result<LocalQueue, AppError> {
+    change(&app, &state, |queue| queue.move_item(&id, index))
+}
+
+/// Shuffle the local queue
+#[tauri::command]
+pub fn shuffle_local_queue(
+    app: AppHandle,
+    state: State<AppAuthState>,
+) -> Result<LocalQueue, AppError> {
+    change(&app, &state, |queue| {
+        queue.shuffle();
+        Ok(())
+    })
+}
+
+/// Remove every item from the local queue
+#[tauri::command]
+pub fn clear_local_queue(
+    app: AppHandle,
+    state: State<AppAuthState>,
+) -> Result<LocalQueue, AppError> {
+    change(&app, &state, |queue| {
+        queue.clear();
+        Ok(())
+    })
+}
+
+/// Start playing the local queue now, on the active device unless one is given
+#[tauri::command]
+pub async fn play_local_queue(
+    app: AppHandle,
+    device_id: Option<String>,
+    state: State<'_, AppAuthState>,
+) -> Result<Option<QueueItem>, AppError> {
+    let Some(item) = update(&state, |queue| Ok(queue.take_next(Utc::now())))? else {
+        return Ok(None);
+    };
+
+    if let Err(e) = SpotifyApi::new(&state)
+        .play_uris(std::slice::from_ref(&item.uri), device_id.as_deref())
+        .await
+    {
+        change(&app, &state, |queue| {
+            queue.restore(item);
+            Ok(())
+        })?;
+        return Err(e);
+    }
+
+    app.state::<PlaybackHub>().refresh();
+    let _ = app.emit("queue:changed", &load_queue(&state)?);
+    Ok(Some(item))
+}
+
+/// Save the local queue as a new Spotify playlist
+#[tauri::command]
+pub async fn save_local_queue_as_playlist(
+    name: String,
+    description: Option<String>,
+    public: Option<bool>,
+    state: State<'_, AppAuthState>,
+) -> Result<Playlist, AppError> {
+    if name.trim().is_empty() {
+        return Err(AppError::InvalidInput("Playlist name is required".into()));
+    }
+    let uris: Vec<String> = load_queue(&state)?
+        .items
+        .into_iter()
+        .map(|i| i.uri)
+        .collect();
+    if uris.is_empty() {
+        return Err(AppError::InvalidInput("The queue is empty".into()));
+    }
+
+    let user = auth::current_user(&state).ok_or(AuthError::NotAuthenticated)?;
+    let api = SpotifyApi::new(&state);
+    let mut playlist = api
+        .create_playlist(
+            &user.id,
+            name.trim(),
+            description.as_deref(),
+            public.unwrap_or(false),
+        )
+        .await?;
+    if let Some(snapshot) = api.add_playlist_items(&playlist.id, &uris).await? {
+        playlist.snapshot_id = snapshot;
+    }
+    Ok(playlist)
+}
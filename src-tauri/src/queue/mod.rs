@@ -0,0 +1,4 @@
+pub mod commands;
+pub mod model;
+
+pub use commands::*;
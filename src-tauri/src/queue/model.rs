@@ -0,0 +1,187 @@
+use chrono::{DateTime, Duration, Utc};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::api::Track;
+use crate::error::AppError;
+use crate::store;
+
+/// How long a track handed to Spotify may take to start before it is forgotten
+const PENDING_TIMEOUT_MINUTES: i64 = 30;
+
+/// An entry of the local queue; the id tells duplicates of one URI apart
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueueItem {
+    pub id: String,
+    pub uri: String,
+    pub added_at: DateTime<Utc>,
+}
+
+/// Queue kept by the backend and fed to Spotify one track at a time
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LocalQueue {
+    pub items: Vec<QueueItem>,
+    /// Item already handed to Spotify's queue and expected to play next
+    pub pending: Option<QueueItem>,
+    pub pending_since: Option<DateTime<Utc>>,
+}
+
+impl LocalQueue {
+    /// Insert URIs at `index` (the end when `None`)
+    pub fn insert(&mut self, uris: &[String], index: Option<usize>) -> Result<(), AppError> {
+        if let Some(invalid) = uris.iter().find(|u| !u.starts_with("spotify:")) {
+            return Err(AppError::InvalidInput(format!(
+                "Not a Spotify URI: {}",
+                invalid
+            )));
+        }
+
+        let now = Utc::now();
+        let index = index.unwrap_or(self.items.len()).min(self.items.len());
+        let items = uris.iter().map(|uri| QueueItem {
+            id: store::new_id(),
+            uri: uri.clone(),
+            added_at: now,
+        });
+        self.items.splice(index..index, items);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, ids: &[String]) {
+        self.items.retain(|item| !ids.contains(&item.id));
+    }
+
+    /// Move an item to a new position
+    pub fn move_item(&mut self, id: &str, index: usize) -> Result<(), AppError> {
+        let from = self
+            .items
+            .iter()
+            .position(|item| item.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("Queue item {}", id)))?;
+        let item = self.items.remove(from);
+        let index = index.min(self.items.len());
+        self.items.insert(index, item);
+        Ok(())
+    }
+
+    pub fn shuffle(&mut self) {
+        self.items.shuffle(&mut rand::thread_rng());
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// Whether the next item should be handed to Spotify now
+    pub fn is_waiting(&self, now: DateTime<Utc>) -> bool {
+        match self.pending_since {
+            Some(since) => now - since > Duration::minutes(PENDING_TIMEOUT_MINUTES),
+            None => true,
+        }
+    }
+
+    /// Take the next item and mark it as handed to Spotify
+    pub fn take_next(&mut self, now: DateTime<Utc>) -> Option<QueueItem> {
+        if self.items.is_empty() {
+            return None;
+        }
+        let item = self.items.remove(0);
+        self.pending = Some(item.clone());
+        self.pending_since = Some(now);
+        Some(item)
+    }
+
+    /// Put an item that could not be handed over back at the front
+    pub fn restore(&mut self, item: QueueItem) {
+        self.pending = None;
+        self.pending_since = None;
+        self.items.insert(0, item);
+    }
+
+    /// Settle the pending item once the next track starts; returns whether it
+    /// was the pending item
+    ///
+    /// The item is handed over near the end of a track, so the next track is the
+    /// one it was due as. When something else starts instead (the user skipped
+    /// past it), it is forgotten all the same so feeding carries on.
+    pub fn track_started(&mut self, track: &Track) -> bool {
+        let Some(pending) = self.pending.take() else {
+            return false;
+        };
+        self.pending_since = None;
+        // Relinked tracks report the substitute's URI
+        pending.uri == track.uri || pending.uri == track.original_uri()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uris(queue: &LocalQueue) -> Vec<&str> {
+        queue.items.iter().map(|i| i.uri.as_str()).collect()
+    }
+
+    fn track(id: &str) -> String {
+        format!("spotify:track:{}", id)
+    }
+
+    fn started(uri: &str, linked_from: Option<&str>) -> Track {
+        serde_json::from_value(serde_json::json!({
+            "id": null,
+            "uri": uri,
+            "name": "Song",
+            "linked_from": linked_from.map(|uri| serde_json::json!({ "id": null, "uri": uri })),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_insert_move_remove() {
+        let mut queue = LocalQueue::default();
+        queue.insert(&[track("a"), track("b")], None).unwrap();
+        queue.insert(&[track("c")], Some(1)).unwrap();
+        assert_eq!(uris(&queue), vec![track("a"), track("c"), track("b")]);
+
+        let first = queue.items[0].id.clone();
+        queue.move_item(&first, 10).unwrap();
+        assert_eq!(uris(&queue), vec![track("c"), track("b"), track("a")]);
+
+        queue.remove(&[first]);
+        assert_eq!(uris(&queue), vec![track("c"), track("b")]);
+        assert!(queue.insert(&["https://example.com".into()], None).is_err());
+    }
+
+    #[test]
+    fn test_feeding_one_at_a_time() {
+        let now = Utc::now();
+        let mut queue = LocalQueue::default();
+        queue.insert(&[track("a"), track("b")], None).unwrap();
+
+        assert!(queue.is_waiting(now));
+        let item = queue.take_next(now).unwrap();
+        assert_eq!(item.uri, track("a"));
+        assert!(!queue.is_waiting(now));
+
+        assert!(queue.track_started(&started(&track("a"), None)));
+        assert!(queue.is_waiting(now));
+        assert_eq!(uris(&queue), vec![track("b")]);
+    }
+
+    #[test]
+    fn test_pending_settled_by_any_next_track() {
+        let now = Utc::now();
+        let mut queue = LocalQueue::default();
+        queue.insert(&[track("a"), track("b")], None).unwrap();
+
+        // Relinked under another URI
+        queue.take_next(now);
+        assert!(queue.track_started(&started(&track("x"), Some(&track("a")))));
+
+        // Skipped past
+        queue.take_next(now);
+        assert!(!queue.track_started(&started(&track("y"), None)));
+        assert!(queue.is_waiting(now));
+        assert!(queue.pending.is_none());
+    }
+}
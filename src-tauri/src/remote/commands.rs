@@ -0,0 +1,110 @@
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+
+use super::server;
+use super::settings::{self, RemoteSettings};
+use crate::error::AppError;
+use crate::loopback::LoopbackServer;
+use crate::store;
+
+const REMOTE_FILE: &str = "remote.json";
+
+/// The remote-control server, when running
+#[derive(Default)]
+pub struct RemoteHub {
+    server: tokio::sync::Mutex<Option<LoopbackServer>>,
+}
+
+/// Settings of the server and whether it is listening
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteStatus {
+    #[serde(flatten)]
+    pub settings: RemoteSettings,
+    pub running: bool,
+}
+
+/// Load, modify and save the settings; a token is generated on first use
+fn update(f: impl FnOnce(&mut RemoteSettings)) -> Result<RemoteSettings, AppError> {
+    store::update_json(
+        &store::app_file(REMOTE_FILE)?,
+        |settings: &mut RemoteSettings| {
+            f(settings);
+            Ok(settings.clone())
+        },
+    )
+}
+
+/// Stop the running server, then start it again if the settings enable it
+async fn apply(
+    app: &AppHandle,
+    hub: &RemoteHub,
+    settings: RemoteSettings,
+) -> Result<RemoteStatus, AppError> {
+    let mut server = hub.server.lock().await;
+    if let Some(running) = server.take() {
+        running.stop().await;
+    }
+    if settings.enabled {
+        *server = Some(server::start(app.clone(), settings.port, settings.token.clone()).await?);
+    }
+
+    Ok(RemoteStatus {
+        running: server.is_some(),
+        settings,
+    })
+}
+
+/// Start the server at launch when enabled
+pub fn spawn_remote_server(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let settings = match update(|_| {}) {
+            Ok(settings) => settings,
+            Err(e) => {
+                log::warn!("Failed to load remote control settings: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = apply(&app, &app.state::<RemoteHub>(), settings).await {
+            log::warn!("Failed to start the remote control server: {}", e);
+        }
+    });
+}
+
+/// Get the remote-control settings, including the token clients must send
+#[tauri::command]
+pub async fn get_remote_settings(hub: State<'_, RemoteHub>) -> Result<RemoteStatus, AppError> {
+    Ok(RemoteStatus {
+        settings: update(|_| {})?,
+        running: hub.server.lock().await.is_some(),
+    })
+}
+
+/// Enable or disable the server and choose its port
+#[tauri::command]
+pub async fn set_remote_settings(
+    app: AppHandle,
+    enabled: bool,
+    port: Option<u16>,
+    hub: State<'_, RemoteHub>,
+) -> Result<RemoteStatus, AppError> {
+    if port.is_some_and(|p| p < 1024) {
+        return Err(AppError::InvalidInput(
+            "Port must be between 1024 and 65535".into(),
+        ));
+    }
+    let settings = update(|s| {
+        s.enabled = enabled;
+        s.port = port.unwrap_or(s.port);
+    })?;
+    apply(&app, &hub, settings).await
+}
+
+/// Replace the token, disconnecting every client using the old one
+#[tauri::command]
+pub async fn regenerate_remote_token(
+    app: AppHandle,
+    hub: State<'_, RemoteHub>,
+) -> Result<RemoteStatus, AppError> {
+    let settings = update(|s| s.token = settings::new_token())?;
+    apply(&app, &hub, settings).await
+}
@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod server;
+pub mod settings;
+
+pub use commands::*;
@@ -0,0 +1,302 @@
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::watch;
+
+use super::settings;
+use crate::api::Track;
+use crate::auth::AppAuthState;
+use crate::blocklist::{self, FilteredTracks};
+use crate::error::AppError;
+use crate::loopback::{self, LoopbackServer};
+use crate::playback::events::PlaybackSnapshot;
+use crate::playback::{self, PlaybackHub, PlayerCommand, VOLUME_STEP};
+use crate::queue::{self, model::LocalQueue};
+
+#[derive(Clone)]
+struct ServerState {
+    app: AppHandle,
+    /// Closed when the server stops, ending open event streams
+    stopped: watch::Receiver<()>,
+}
+
+/// What a request must match to be let through
+#[derive(Clone)]
+struct Access {
+    token: String,
+    port: u16,
+}
+
+/// Error body of every failed request
+struct ApiError(AppError);
+
+impl From<AppError> for ApiError {
+    fn from(error: AppError) -> Self {
+        Self(error)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match &self.0 {
+            AppError::Auth(_) => StatusCode::UNAUTHORIZED,
+            AppError::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            AppError::Api { .. } | AppError::Http(_) | AppError::Parse(_) => {
+                StatusCode::BAD_GATEWAY
+            }
+            AppError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(json!({ "error": self.0.to_string() }))).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+/// Reject requests for other hosts or without the token
+async fn authorize(State(access): State<Access>, request: Request, next: Next) -> Response {
+    let (host_allowed, token_valid) = {
+        let header = |name| request.headers().get(name).and_then(|v| v.to_str().ok());
+        let token = settings::request_token(header(header::AUTHORIZATION), request.uri().query());
+        (
+            loopback::allowed_host(header(header::HOST), access.port),
+            token.is_some_and(|t| settings::token_matches(&access.token, t)),
+        )
+    };
+
+    if !host_allowed {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "error": "Forbidden host" })),
+        )
+            .into_response();
+    }
+    if !token_valid {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": "Invalid token" })),
+        )
+            .into_response();
+    }
+    next.run(request).await
+}
+
+async fn get_playback(State(server): State<ServerState>) -> Json<Option<PlaybackSnapshot>> {
+    Json(server.app.state::<PlaybackHub>().current())
+}
+
+async fn run_command(server: &ServerState, command: PlayerCommand) -> ApiResult<Value> {
+    let state = server.app.state::<AppAuthState>();
+    let hub = server.app.state::<PlaybackHub>();
+    playback::execute(&state, &hub, command).await?;
+    Ok(Json(json!({ "ok": true })))
+}
+
+async fn post_command(
+    State(server): State<ServerState>,
+    Json(command): Json<PlayerCommand>,
+) -> ApiResult<Value> {
+    run_command(&server, command).await
+}
+
+/// Body-less shortcuts for buttons that can only fire a plain request
+async fn post_action(
+    State(server): State<ServerState>,
+    Path(action): Path<String>,
+) -> ApiResult<Value> {
+    let command = match action.as_str() {
+        "play" => PlayerCommand::Play,
+        "pause" => PlayerCommand::Pause,
+        "toggle" => PlayerCommand::TogglePlay,
+        "next" => PlayerCommand::Next,
+        "previous" => PlayerCommand::Previous,
+        "volume-up" => PlayerCommand::ChangeVolume { delta: VOLUME_STEP },
+        "volume-down" => PlayerCommand::ChangeVolume {
+            delta: -VOLUME_STEP,
+        },
+        _ => return Err(AppError::NotFound(format!("Action {}", action)).into()),
+    };
+    run_command(&server, command).await
+}
+
+async fn get_queue(State(server): State<ServerState>) -> ApiResult<LocalQueue> {
+    Ok(Json(queue::load_queue(
+        &server.app.state::<AppAuthState>(),
+    )?))
+}
+
+#[derive(Deserialize)]
+struct EnqueueBody {
+    uris: Vec<String>,
+    index: Option<usize>,
+}
+
+async fn post_queue(
+    State(server): State<ServerState>,
+    Json(body): Json<EnqueueBody>,
+) -> ApiResult<LocalQueue> {
+    let state = server.app.state::<AppAuthState>();
+    Ok(Json(queue::enqueue(
+        &server.app,
+        &state,
+        &body.uris,
+        body.index,
+    )?))
+}
+
+#[derive(Deserialize)]
+struct SearchQuery {
+    q: String,
+    limit: Option<u32>,
+}
+
+async fn get_search(
+    State(server): State<ServerState>,
+    Query(query): Query<SearchQuery>,
+) -> ApiResult<FilteredTracks> {
+    let state = server.app.state::<AppAuthState>();
+    Ok(Json(
+        blocklist::search(&state, &query.q, query.limit).await?,
+    ))
+}
+
+async fn post_like(State(server): State<ServerState>) -> ApiResult<Track> {
+    let state = server.app.state::<AppAuthState>();
+    let hub = server.app.state::<PlaybackHub>();
+    let track = playback::like_current_track(&state, &hub).await?;
+    let _ = server.app.emit("library:track-saved", &track);
+    Ok(Json(track))
+}
+
+/// Message of the event stream, named like the matching Tauri event
+#[derive(Serialize)]
+struct StreamMessage<'a, T: Serialize> {
+    event: &'a str,
+    payload: T,
+}
+
+fn text<T: Serialize>(event: &str, payload: T) -> Option<Message> {
+    serde_json::to_string(&StreamMessage { event, payload })
+        .ok()
+        .map(|json| Message::Text(json.into()))
+}
+
+async fn get_events(State(server): State<ServerState>, upgrade: WebSocketUpgrade) -> Response {
+    upgrade.on_upgrade(move |socket| stream_events(server, socket))
+}
+
+/// Send the current state, then every playback event, until the client leaves
+async fn stream_events(server: ServerState, mut socket: WebSocket) {
+    let mut stopped = server.stopped;
+    let hub = server.app.state::<PlaybackHub>();
+    let mut updates = hub.subscribe();
+    if let Some(message) = text("playback:state", hub.current()) {
+        if socket.send(message).await.is_err() {
+            return;
+        }
+    }
+
+    loop {
+        tokio::select! {
+            _ = stopped.changed() => break,
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            update = updates.recv() => {
+                let update = match update {
+                    Ok(update) => update,
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                };
+                for event in &update.events {
+                    let Some(message) = text(event.name(), event) else {
+                        continue;
+                    };
+                    if socket.send(message).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn router(state: ServerState, access: Access) -> Router {
+    Router::new()
+        .route("/v1/playback", get(get_playback).post(post_command))
+        .route("/v1/playback/{action}", post(post_action))
+        .route("/v1/queue", get(get_queue).post(post_queue))
+        .route("/v1/search", get(get_search))
+        .route("/v1/like", post(post_like))
+        .route("/v1/events", get(get_events))
+        .layer(middleware::from_fn_with_state(access, authorize))
+        .with_state(state)
+}
+
+/// Bind to loopback and serve the API until stopped
+pub async fn start(app: AppHandle, port: u16, token: String) -> Result<LoopbackServer, AppError> {
+    loopback::serve("Remote control server", port, |stopped| {
+        router(ServerState { app, stopped }, Access { token, port })
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use tower::ServiceExt;
+
+    async fn status(host: &str, authorization: Option<&str>, query: &str) -> StatusCode {
+        let access = Access {
+            token: "secret".into(),
+            port: 8974,
+        };
+        let router = Router::new()
+            .route("/v1/playback", get(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(access, authorize));
+
+        let mut request = Request::builder()
+            .uri(format!("/v1/playback{}", query))
+            .header(header::HOST, host);
+        if let Some(value) = authorization {
+            request = request.header(header::AUTHORIZATION, value);
+        }
+        router
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_authorize_requires_loopback_host_and_token() {
+        let host = "127.0.0.1:8974";
+        assert_eq!(
+            status(host, Some("Bearer secret"), "").await,
+            StatusCode::OK
+        );
+        assert_eq!(status(host, None, "?token=secret").await, StatusCode::OK);
+
+        assert_eq!(status(host, None, "").await, StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            status(host, Some("Bearer secreT"), "").await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status("evil.example:8974", Some("Bearer secret"), "").await,
+            StatusCode::FORBIDDEN
+        );
+    }
+}
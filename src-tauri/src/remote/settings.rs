@@ -0,0 +1,92 @@
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_PORT: u16 = 8974;
+
+/// Configuration of the local remote-control server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteSettings {
+    pub enabled: bool,
+    pub port: u16,
+    /// Bearer token every request must carry
+    pub token: String,
+}
+
+impl Default for RemoteSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+            token: new_token(),
+        }
+    }
+}
+
+/// Generate a random 256-bit token, hex encoded
+pub fn new_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Compare a provided token without leaking where it differs through timing
+pub fn token_matches(expected: &str, provided: &str) -> bool {
+    if expected.is_empty() || expected.len() != provided.len() {
+        return false;
+    }
+    expected
+        .bytes()
+        .zip(provided.bytes())
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+        == 0
+}
+
+/// Token of a request, from `Authorization: Bearer` or a `token` query parameter
+///
+/// The query form exists for WebSocket clients that cannot set headers.
+pub fn request_token<'a>(
+    authorization: Option<&'a str>,
+    query: Option<&'a str>,
+) -> Option<&'a str> {
+    let bearer = authorization.and_then(|value| {
+        let (scheme, token) = value.split_once(' ')?;
+        scheme.eq_ignore_ascii_case("bearer").then(|| token.trim())
+    });
+    bearer.or_else(|| {
+        query?
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(name, _)| *name == "token")
+            .map(|(_, value)| value)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_are_random_and_compared_exactly() {
+        let token = new_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, new_token());
+
+        assert!(token_matches(&token, &token.clone()));
+        assert!(!token_matches(&token, &token[..63]));
+        assert!(!token_matches(&token, &token.to_uppercase()));
+        assert!(!token_matches("", ""));
+    }
+
+    #[test]
+    fn test_request_token_reads_header_then_query() {
+        assert_eq!(request_token(Some("Bearer abc"), None), Some("abc"));
+        assert_eq!(request_token(Some("bearer  abc "), None), Some("abc"));
+        assert_eq!(
+            request_token(Some("Basic abc"), Some("token=def")),
+            Some("def")
+        );
+        assert_eq!(request_token(None, Some("x=1&token=def")), Some("def"));
+        assert_eq!(request_token(None, Some("tokens=def")), None);
+        assert_eq!(request_token(None, None), None);
+    }
+}
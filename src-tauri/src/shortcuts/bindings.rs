@@ -0,0 +1,271 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::error::AppError;
+
+/// Something a global shortcut can trigger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortcutAction {
+    PlayPause,
+    Next,
+    Previous,
+    LikeCurrentTrack,
+    VolumeUp,
+    VolumeDown,
+    ShowLyrics,
+}
+
+impl ShortcutAction {
+    pub const ALL: [ShortcutAction; 7] = [
+        ShortcutAction::PlayPause,
+        ShortcutAction::Next,
+        ShortcutAction::Previous,
+        ShortcutAction::LikeCurrentTrack,
+        ShortcutAction::VolumeUp,
+        ShortcutAction::VolumeDown,
+        ShortcutAction::ShowLyrics,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ShortcutAction::PlayPause => "Play/pause",
+            ShortcutAction::Next => "Next track",
+            ShortcutAction::Previous => "Previous track",
+            ShortcutAction::LikeCurrentTrack => "Like current track",
+            ShortcutAction::VolumeUp => "Volume up",
+            ShortcutAction::VolumeDown => "Volume down",
+            ShortcutAction::ShowLyrics => "Show lyrics",
+        }
+    }
+
+    fn default_accelerator(self) -> &'static str {
+        match self {
+            ShortcutAction::PlayPause => "MediaPlayPause",
+            ShortcutAction::Next => "MediaTrackNext",
+            ShortcutAction::Previous => "MediaTrackPrevious",
+            ShortcutAction::LikeCurrentTrack => "CommandOrControl+Alt+L",
+            ShortcutAction::VolumeUp => "CommandOrControl+Alt+Up",
+            ShortcutAction::VolumeDown => "CommandOrControl+Alt+Down",
+            ShortcutAction::ShowLyrics => "CommandOrControl+Alt+Y",
+        }
+    }
+}
+
+/// Accelerator bound to each action; `None` means the action is unbound
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bindings(BTreeMap<ShortcutAction, Option<String>>);
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self(
+            ShortcutAction::ALL
+                .into_iter()
+                .map(|a| (a, Some(a.default_accelerator().to_string())))
+                .collect(),
+        )
+    }
+}
+
+impl Bindings {
+    /// Accelerator of an action; actions missing from a stored file get their default
+    pub fn get(&self, action: ShortcutAction) -> Option<&str> {
+        match self.0.get(&action) {
+            Some(accelerator) => accelerator.as_deref(),
+            None => Some(action.default_accelerator()),
+        }
+    }
+
+    /// Every bound action with its accelerator
+    pub fn bound(&self) -> impl Iterator<Item = (ShortcutAction, &str)> {
+        ShortcutAction::ALL
+            .into_iter()
+            .filter_map(|a| self.get(a).map(|accel| (a, accel)))
+    }
+
+    /// Bind or unbind an action, rejecting accelerators another action already uses
+    pub fn set(
+        &mut self,
+        action: ShortcutAction,
+        accelerator: Option<&str>,
+    ) -> Result<(), AppError> {
+        let accelerator = match accelerator.map(str::trim).filter(|a| !a.is_empty()) {
+            Some(accelerator) => {
+                let normalized = normalize(accelerator)?;
+                if let Some(other) = self.conflict(action, &normalized) {
+                    return Err(AppError::Conflict(format!(
+                        "{} is already used by \"{}\"",
+                        accelerator,
+                        other.label()
+                    )));
+                }
+                Some(normalized)
+            }
+            None => None,
+        };
+
+        self.0.insert(action, accelerator);
+        Ok(())
+    }
+
+    /// Action other than `action` bound to the same key combination
+    fn conflict(&self, action: ShortcutAction, normalized: &str) -> Option<ShortcutAction> {
+        self.bound()
+            .filter(|(other, _)| *other != action)
+            .find(|(_, accel)| normalize(accel).is_ok_and(|n| n.eq_ignore_ascii_case(normalized)))
+            .map(|(other, _)| other)
+    }
+}
+
+/// Canonical modifier names, in display order
+const MODIFIERS: [&str; 5] = ["CommandOrControl", "Control", "Alt", "Shift", "Super"];
+
+fn modifier(token: &str) -> Option<&'static str> {
+    match token.to_ascii_lowercase().as_str() {
+        "commandorcontrol" | "commandorctrl" | "cmdorctrl" | "cmdorcontrol" => Some(MODIFIERS[0]),
+        "control" | "ctrl" => Some(MODIFIERS[1]),
+        "alt" | "option" => Some(MODIFIERS[2]),
+        "shift" => Some(MODIFIERS[3]),
+        "super" | "command" | "cmd" | "meta" => Some(MODIFIERS[4]),
+        _ => None,
+    }
+}
+
+/// Keys that are safe to grab globally without a modifier
+fn standalone(key: &str) -> bool {
+    let lower = key.to_ascii_lowercase();
+    lower.starts_with("media")
+        || lower.starts_with("audiovolume")
+        || lower
+            .strip_prefix('f')
+            .is_some_and(|n| n.parse::<u8>().is_ok_and(|n| (1..=24).contains(&n)))
+}
+
+/// Validate an accelerator and rewrite it with canonical, ordered modifiers
+///
+/// `CommandOrControl` is resolved to the platform modifier so that it conflicts
+/// with an explicit `Control` (or `Super` on macOS).
+pub fn normalize(accelerator: &str) -> Result<String, AppError> {
+    let invalid = |reason: &str| {
+        AppError::InvalidInput(format!("Invalid shortcut \"{}\": {}", accelerator, reason))
+    };
+
+    let mut modifiers = Vec::new();
+    let mut key = None;
+    for token in accelerator.split('+').map(str::trim) {
+        if token.is_empty() {
+            return Err(invalid("empty key"));
+        }
+        if key.is_some() {
+            return Err(invalid("the key must come last"));
+        }
+        match modifier(token) {
+            Some(m) => {
+                let m = match m {
+                    "CommandOrControl" if cfg!(target_os = "macos") => "Super",
+                    "CommandOrControl" => "Control",
+                    m => m,
+                };
+                if !modifiers.contains(&m) {
+                    modifiers.push(m);
+                }
+            }
+            None => key = Some(token),
+        }
+    }
+
+    let key = key.ok_or_else(|| invalid("missing key"))?;
+    if modifiers.is_empty() && !standalone(key) {
+        return Err(invalid("needs at least one modifier"));
+    }
+
+    modifiers.sort_by_key(|m| MODIFIERS.iter().position(|x| x == m));
+    let key = if key.chars().count() == 1 {
+        key.to_ascii_uppercase()
+    } else {
+        key.to_string()
+    };
+    Ok(modifiers
+        .into_iter()
+        .chain(std::iter::once(key.as_str()))
+        .collect::<Vec<_>>()
+        .join("+"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_orders_and_aliases_modifiers() {
+        assert_eq!(normalize("shift+ctrl+k").unwrap(), "Control+Shift+K");
+        assert_eq!(normalize("Option + Cmd + F5").unwrap(), "Alt+Super+F5");
+        assert_eq!(normalize("MediaPlayPause").unwrap(), "MediaPlayPause");
+        assert_eq!(normalize("F13").unwrap(), "F13");
+    }
+
+    #[test]
+    fn test_normalize_rejects_malformed_accelerators() {
+        assert!(normalize("K").is_err());
+        assert!(normalize("Ctrl+").is_err());
+        assert!(normalize("Ctrl+Shift").is_err());
+        assert!(normalize("Ctrl+K+Shift").is_err());
+    }
+
+    #[test]
+    fn test_defaults_bind_every_action_without_conflicts() {
+        let bindings = Bindings::default();
+        assert_eq!(bindings.bound().count(), ShortcutAction::ALL.len());
+        for (action, accel) in bindings.bound() {
+            let normalized = normalize(accel).unwrap();
+            assert_eq!(bindings.conflict(action, &normalized), None);
+        }
+    }
+
+    #[test]
+    fn test_set_detects_conflicts_across_aliases() {
+        let mut bindings = Bindings::default();
+        let platform = if cfg!(target_os = "macos") {
+            "Cmd"
+        } else {
+            "Ctrl"
+        };
+
+        let err = bindings
+            .set(
+                ShortcutAction::ShowLyrics,
+                Some(&format!("alt+{}+l", platform)),
+            )
+            .unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+
+        bindings
+            .set(ShortcutAction::LikeCurrentTrack, Some("Ctrl+Shift+L"))
+            .unwrap();
+        bindings
+            .set(ShortcutAction::ShowLyrics, Some("CmdOrCtrl+Alt+L"))
+            .unwrap();
+        assert_eq!(
+            bindings.get(ShortcutAction::ShowLyrics),
+            Some(if cfg!(target_os = "macos") {
+                "Alt+Super+L"
+            } else {
+                "Control+Alt+L"
+            })
+        );
+    }
+
+    #[test]
+    fn test_unbinding_and_missing_entries() {
+        let mut bindings: Bindings = serde_json::from_str(r#"{"next": null}"#).unwrap();
+        assert_eq!(bindings.get(ShortcutAction::Next), None);
+        assert_eq!(
+            bindings.get(ShortcutAction::PlayPause),
+            Some("MediaPlayPause")
+        );
+
+        bindings.set(ShortcutAction::PlayPause, Some("  ")).unwrap();
+        assert_eq!(bindings.get(ShortcutAction::PlayPause), None);
+        assert_eq!(bindings.bound().count(), ShortcutAction::ALL.len() - 2);
+    }
+}
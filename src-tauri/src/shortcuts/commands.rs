@@ -0,0 +1,200 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+
+use super::bindings::{Bindings, ShortcutAction};
+use crate::auth::AppAuthState;
+use crate::error::AppError;
+use crate::playback::{self, PlaybackHub, PlayerCommand, VOLUME_STEP};
+use crate::store;
+use crate::window;
+
+const SHORTCUTS_FILE: &str = "shortcuts.json";
+
+/// Hotkeys currently registered with the OS
+#[derive(Default)]
+pub struct ShortcutHub {
+    registration: Mutex<Registration>,
+}
+
+#[derive(Default)]
+struct Registration {
+    actions: HashMap<u32, ShortcutAction>,
+    errors: HashMap<ShortcutAction, String>,
+}
+
+/// A binding and whether the OS accepted it
+#[derive(Debug, Clone, Serialize)]
+pub struct ShortcutStatus {
+    pub action: ShortcutAction,
+    pub label: &'static str,
+    pub accelerator: Option<String>,
+    pub registered: bool,
+    /// Why registration failed, e.g. another application owns the hotkey
+    pub error: Option<String>,
+}
+
+fn load() -> Result<Bindings, AppError> {
+    Ok(store::load_json(&store::app_file(SHORTCUTS_FILE)?)?.unwrap_or_default())
+}
+
+fn update(f: impl FnOnce(&mut Bindings) -> Result<(), AppError>) -> Result<Bindings, AppError> {
+    store::update_json(
+        &store::app_file(SHORTCUTS_FILE)?,
+        |bindings: &mut Bindings| {
+            f(bindings)?;
+            Ok(bindings.clone())
+        },
+    )
+}
+
+/// Replace every registered hotkey with the given bindings
+fn apply(app: &AppHandle, bindings: &Bindings) {
+    let shortcuts = app.global_shortcut();
+    if let Err(e) = shortcuts.unregister_all() {
+        log::warn!("Failed to unregister global shortcuts: {}", e);
+    }
+
+    let mut registration = Registration::default();
+    for (action, accelerator) in bindings.bound() {
+        let result = Shortcut::from_str(accelerator)
+            .map_err(|e| e.to_string())
+            .and_then(|shortcut| match registration.actions.get(&shortcut.id()) {
+                // Different spellings of the same keys (e.g. "Up" and "ArrowUp")
+                Some(other) => Err(format!("Same keys as \"{}\"", other.label())),
+                None => shortcuts
+                    .register(shortcut)
+                    .map(|_| shortcut.id())
+                    .map_err(|e| e.to_string()),
+            });
+
+        match result {
+            Ok(id) => {
+                registration.actions.insert(id, action);
+            }
+            Err(e) => {
+                log::warn!("Failed to register shortcut {}: {}", accelerator, e);
+                registration.errors.insert(action, e);
+            }
+        }
+    }
+
+    *app.state::<ShortcutHub>().registration.lock().unwrap() = registration;
+}
+
+fn statuses(hub: &ShortcutHub, bindings: &Bindings) -> Vec<ShortcutStatus> {
+    let registration = hub.registration.lock().unwrap();
+    ShortcutAction::ALL
+        .into_iter()
+        .map(|action| ShortcutStatus {
+            action,
+            label: action.label(),
+            accelerator: bindings.get(action).map(str::to_string),
+            registered: registration.actions.values().any(|a| *a == action),
+            error: registration.errors.get(&action).cloned(),
+        })
+        .collect()
+}
+
+/// Register the stored bindings at startup
+pub fn register_shortcuts(app: &AppHandle) {
+    let bindings = load().unwrap_or_else(|e| {
+        log::warn!("Failed to load shortcut bindings, using defaults: {}", e);
+        Bindings::default()
+    });
+    apply(app, &bindings);
+}
+
+/// Global shortcut handler, installed with the plugin
+pub fn handle_shortcut(app: &AppHandle, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state() != ShortcutState::Pressed {
+        return;
+    }
+    let action = {
+        let hub = app.state::<ShortcutHub>();
+        let registration = hub.registration.lock().unwrap();
+        registration.actions.get(&shortcut.id()).copied()
+    };
+    let Some(action) = action else {
+        return;
+    };
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = run(&app, action).await {
+            log::warn!("Shortcut {:?} failed: {}", action, e);
+            let _ = app.emit("shortcut:failed", e.to_string());
+        }
+    });
+}
+
+async fn run(app: &AppHandle, action: ShortcutAction) -> Result<(), AppError> {
+    let state = app.state::<AppAuthState>();
+    let hub = app.state::<PlaybackHub>();
+
+    let command = match action {
+        ShortcutAction::PlayPause => PlayerCommand::TogglePlay,
+        ShortcutAction::Next => PlayerCommand::Next,
+        ShortcutAction::Previous => PlayerCommand::Previous,
+        ShortcutAction::VolumeUp => PlayerCommand::ChangeVolume { delta: VOLUME_STEP },
+        ShortcutAction::VolumeDown => PlayerCommand::ChangeVolume {
+            delta: -VOLUME_STEP,
+        },
+        ShortcutAction::LikeCurrentTrack => {
+            let track = playback::like_current_track(&state, &hub).await?;
+            let _ = app.emit("library:track-saved", &track);
+            return Ok(());
+        }
+        ShortcutAction::ShowLyrics => {
+            window::show_main_window(app);
+            let _ = app.emit("shortcut:show-lyrics", ());
+            return Ok(());
+        }
+    };
+
+    playback::execute(&state, &hub, command).await
+}
+
+/// List every action with its binding and registration status
+#[tauri::command]
+pub fn get_shortcuts(hub: State<'_, ShortcutHub>) -> Result<Vec<ShortcutStatus>, AppError> {
+    Ok(statuses(&hub, &load()?))
+}
+
+/// Bind an action to an accelerator, or unbind it with `None`
+///
+/// Fails with a conflict when another action already uses the keys.
+#[tauri::command]
+pub async fn set_shortcut(
+    app: AppHandle,
+    action: ShortcutAction,
+    accelerator: Option<String>,
+    hub: State<'_, ShortcutHub>,
+) -> Result<Vec<ShortcutStatus>, AppError> {
+    let bindings = update(|bindings| bindings.set(action, accelerator.as_deref()))?;
+    apply(&app, &bindings);
+
+    let statuses = statuses(&hub, &bindings);
+    let _ = app.emit("shortcuts:changed", &statuses);
+    Ok(statuses)
+}
+
+/// Restore the default bindings
+#[tauri::command]
+pub async fn reset_shortcuts(
+    app: AppHandle,
+    hub: State<'_, ShortcutHub>,
+) -> Result<Vec<ShortcutStatus>, AppError> {
+    let bindings = update(|bindings| {
+        *bindings = Bindings::default();
+        Ok(())
+    })?;
+    apply(&app, &bindings);
+
+    let statuses = statuses(&hub, &bindings);
+    let _ = app.emit("shortcuts:changed", &statuses);
+    Ok(statuses)
+}
@@ -0,0 +1,4 @@
+pub mod bindings;
+pub mod commands;
+
+pub use commands::*;
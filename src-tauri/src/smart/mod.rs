@@ -0,0 +1,4 @@
+pub mod playlists;
+pub mod rules;
+
+pub use playlists::*;
@@ -0,0 +1,259 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::rules::{self, RuleContext, RuleSet};
+use crate::annotations;
+use crate::api::SpotifyApi;
+use crate::auth::{self, AppAuthState, AuthError};
+use crate::blocklist;
+use crate::error::AppError;
+use crate::library::{history, mirror, LibraryTrack};
+use crate::store;
+
+const SMART_FILE: &str = "smart_playlists.json";
+
+/// How often the scheduler checks for smart playlists due for a refresh
+const JOB_TICK: Duration = Duration::from_secs(60);
+
+/// The library mirror is resynced before evaluating when older than this
+const MIRROR_MAX_AGE_MINUTES: i64 = 30;
+
+/// A smart playlist definition and the Spotify playlist it is materialised into
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmartPlaylist {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub rules: RuleSet,
+    /// Spotify playlist holding the results, created on first refresh
+    pub playlist_id: Option<String>,
+    /// Refresh automatically every N minutes
+    pub refresh_minutes: Option<u32>,
+    pub last_refreshed: Option<DateTime<Utc>>,
+    pub last_track_count: Option<usize>,
+}
+
+/// Fields the frontend sends when creating or editing a smart playlist
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmartPlaylistInput {
+    pub id: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+    pub rules: RuleSet,
+    pub refresh_minutes: Option<u32>,
+}
+
+fn load_all(state: &AppAuthState) -> Result<Vec<SmartPlaylist>, AppError> {
+    Ok(store::load_json(&store::account_file(state, SMART_FILE)?)?.unwrap_or_default())
+}
+
+fn update<T>(
+    state: &AppAuthState,
+    f: impl FnOnce(&mut Vec<SmartPlaylist>) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    store::update_json(&store::account_file(state, SMART_FILE)?, f)
+}
+
+/// Evaluate a rule set against a fresh library mirror and the play history,
+/// leaving out blocked artists and tracks
+pub async fn evaluate(
+    state: &AppAuthState,
+    rules: &RuleSet,
+) -> Result<Vec<LibraryTrack>, AppError> {
+    let mirror =
+        mirror::ensure_fresh(state, chrono::Duration::minutes(MIRROR_MAX_AGE_MINUTES)).await?;
+    let plays = history::load_history(state)?.stats();
+    let annotations = annotations::load_annotations(state)?;
+
+    let blocklist = blocklist::load_blocklist(state)?;
+
+    let ctx = RuleContext {
+        now: Utc::now(),
+        mirror: &mirror,
+        plays: &plays,
+        annotations: &annotations,
+        blocklist: &blocklist,
+    };
+    Ok(rules::evaluate(rules, &ctx).into_iter().cloned().collect())
+}
+
+/// Re-evaluate a smart playlist and rewrite its Spotify playlist
+pub async fn refresh(state: &AppAuthState, id: &str) -> Result<SmartPlaylist, AppError> {
+    let mut smart = load_all(state)?
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| AppError::NotFound(format!("Smart playlist {}", id)))?;
+
+    let uris: Vec<String> = evaluate(state, &smart.rules)
+        .await?
+        .into_iter()
+        .map(|t| t.uri)
+        .collect();
+
+    let api = SpotifyApi::new(state);
+    let existing = match smart.playlist_id.as_deref() {
+        Some(playlist_id) => match api.replace_playlist_items(playlist_id, &uris).await {
+            Ok(_) => true,
+            // The playlist was deleted on Spotify's side; create a new one
+            Err(AppError::Api { status: 404, .. }) => false,
+            Err(e) => return Err(e),
+        },
+        None => false,
+    };
+
+    if !existing {
+        let user = auth::current_user(state).ok_or(AuthError::NotAuthenticated)?;
+        let playlist = api
+            .create_playlist(&user.id, &smart.name, smart.description.as_deref(), false)
+            .await?;
+        api.add_playlist_items(&playlist.id, &uris).await?;
+        smart.playlist_id = Some(playlist.id);
+    }
+
+    smart.last_refreshed = Some(Utc::now());
+    smart.last_track_count = Some(uris.len());
+
+    // Reload so edits made while the refresh was running are not lost
+    update(state, |all| {
+        let stored = all
+            .iter_mut()
+            .find(|p| p.id == smart.id)
+            .ok_or_else(|| AppError::NotFound(format!("Smart playlist {}", id)))?;
+        stored.playlist_id = smart.playlist_id.clone();
+        stored.last_refreshed = smart.last_refreshed;
+        stored.last_track_count = smart.last_track_count;
+        Ok(())
+    })?;
+
+    log::info!(
+        "Refreshed smart playlist {} ({} tracks)",
+        smart.name,
+        uris.len()
+    );
+    Ok(smart)
+}
+
+fn is_due(smart: &SmartPlaylist, now: DateTime<Utc>) -> bool {
+    let Some(minutes) = smart.refresh_minutes.filter(|m| *m > 0) else {
+        return false;
+    };
+    smart
+        .last_refreshed
+        .map(|t| now - t >= chrono::Duration::minutes(minutes as i64))
+        .unwrap_or(true)
+}
+
+/// Start the scheduler that keeps smart playlists up to date
+pub fn spawn_smart_playlist_job(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(JOB_TICK).await;
+
+            let state = app.state::<AppAuthState>();
+            if auth::current_user(&state).is_none() {
+                continue;
+            }
+
+            let due: Vec<String> = match load_all(&state) {
+                Ok(all) => all
+                    .iter()
+                    .filter(|p| is_due(p, Utc::now()))
+                    .map(|p| p.id.clone())
+                    .collect(),
+                Err(e) => {
+                    log::error!("Failed to load smart playlists: {}", e);
+                    continue;
+                }
+            };
+
+            for id in due {
+                match refresh(&state, &id).await {
+                    Ok(smart) => {
+                        let _ = app.emit("smart-playlist:refreshed", smart);
+                    }
+                    Err(e) => log::warn!("Failed to refresh smart playlist {}: {}", id, e),
+                }
+            }
+        }
+    });
+}
+
+/// List the smart playlists of the current account
+#[tauri::command]
+pub fn list_smart_playlists(state: State<AppAuthState>) -> Result<Vec<SmartPlaylist>, AppError> {
+    load_all(&state)
+}
+
+/// Create or update a smart playlist definition
+#[tauri::command]
+pub fn save_smart_playlist(
+    input: SmartPlaylistInput,
+    state: State<AppAuthState>,
+) -> Result<SmartPlaylist, AppError> {
+    if input.name.trim().is_empty() {
+        return Err(AppError::InvalidInput(
+            "Smart playlist name is required".into(),
+        ));
+    }
+
+    update(&state, |all| match input.id.as_deref() {
+        Some(id) => {
+            let existing = all
+                .iter_mut()
+                .find(|p| p.id == id)
+                .ok_or_else(|| AppError::NotFound(format!("Smart playlist {}", id)))?;
+            existing.name = input.name.trim().to_string();
+            existing.description = input.description;
+            existing.rules = input.rules;
+            existing.refresh_minutes = input.refresh_minutes;
+            Ok(existing.clone())
+        }
+        None => {
+            let smart = SmartPlaylist {
+                id: store::new_id(),
+                name: input.name.trim().to_string(),
+                description: input.description,
+                rules: input.rules,
+                playlist_id: None,
+                refresh_minutes: input.refresh_minutes,
+                last_refreshed: None,
+                last_track_count: None,
+            };
+            all.push(smart.clone());
+            Ok(smart)
+        }
+    })
+}
+
+/// Delete a smart playlist definition (the Spotify playlist is kept)
+#[tauri::command]
+pub fn delete_smart_playlist(id: String, state: State<AppAuthState>) -> Result<(), AppError> {
+    update(&state, |all| {
+        let before = all.len();
+        all.retain(|p| p.id != id);
+        if all.len() == before {
+            return Err(AppError::NotFound(format!("Smart playlist {}", id)));
+        }
+        Ok(())
+    })
+}
+
+/// Evaluate rules without touching any playlist
+#[tauri::command]
+pub async fn preview_smart_playlist(
+    rules: RuleSet,
+    state: State<'_, AppAuthState>,
+) -> Result<Vec<LibraryTrack>, AppError> {
+    evaluate(&state, &rules).await
+}
+
+/// Re-evaluate a smart playlist now and write the results to Spotify
+#[tauri::command]
+pub async fn refresh_smart_playlist(
+    id: String,
+    state: State<'_, AppAuthState>,
+) -> Result<SmartPlaylist, AppError> {
+    refresh(&state, &id).await
+}
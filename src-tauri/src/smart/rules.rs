@@ -0,0 +1,512 @@
+use chrono::{DateTime, Duration, Utc};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::annotations::{Annotation, Annotations};
+use crate::blocklist::model::Blocklist;
+use crate::library::{LibraryMirror, LibraryTrack, PlayStats};
+
+/// A condition a library track must satisfy
+///
+/// Rules nest through `all`, `any` and `not`, so
+/// "saved in last 30 days AND genre contains jazz AND not played in 90 days" is
+/// an `all` of `saved_within_days`, `genre_contains` and `not_played_within_days`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Rule {
+    All {
+        rules: Vec<Rule>,
+    },
+    Any {
+        rules: Vec<Rule>,
+    },
+    Not {
+        rule: Box<Rule>,
+    },
+    SavedWithinDays {
+        days: u32,
+    },
+    SavedBeforeDays {
+        days: u32,
+    },
+    PlayedWithinDays {
+        days: u32,
+    },
+    NotPlayedWithinDays {
+        days: u32,
+    },
+    PlayCountAtLeast {
+        count: u32,
+    },
+    PlayCountAtMost {
+        count: u32,
+    },
+    TitleContains {
+        value: String,
+    },
+    ArtistContains {
+        value: String,
+    },
+    AlbumContains {
+        value: String,
+    },
+    GenreContains {
+        value: String,
+    },
+    ReleaseYearBetween {
+        from: Option<i32>,
+        to: Option<i32>,
+    },
+    DurationBetween {
+        min_seconds: Option<u64>,
+        max_seconds: Option<u64>,
+    },
+    PopularityBetween {
+        min: Option<u32>,
+        max: Option<u32>,
+    },
+    Explicit {
+        value: bool,
+    },
+    RatingBetween {
+        min: Option<u8>,
+        max: Option<u8>,
+    },
+    /// The track, its album or one of its artists carries the tag
+    HasTag {
+        tag: String,
+    },
+    NoteContains {
+        value: String,
+    },
+}
+
+/// Fields results can be sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    AddedAt,
+    Title,
+    Artist,
+    Album,
+    ReleaseDate,
+    Duration,
+    Popularity,
+    PlayCount,
+    LastPlayed,
+    Rating,
+    Random,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SortSpec {
+    pub field: SortField,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// Complete definition of what a smart playlist contains
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleSet {
+    pub rule: Rule,
+    pub sort: Option<SortSpec>,
+    pub limit: Option<usize>,
+}
+
+/// Data rules are evaluated against
+pub struct RuleContext<'a> {
+    pub now: DateTime<Utc>,
+    pub mirror: &'a LibraryMirror,
+    pub plays: &'a HashMap<String, PlayStats>,
+    pub annotations: &'a Annotations,
+    /// Blocked tracks never match, so they do not count towards the limit
+    pub blocklist: &'a Blocklist,
+}
+
+impl RuleContext<'_> {
+    fn plays(&self, track: &LibraryTrack) -> Option<&PlayStats> {
+        self.plays.get(&track.uri)
+    }
+
+    fn annotation(&self, track: &LibraryTrack) -> Option<&Annotation> {
+        self.annotations.get(&track.uri)
+    }
+
+    /// Annotations of the track itself, its album and its artists
+    fn related_annotations<'b>(
+        &'b self,
+        track: &'b LibraryTrack,
+    ) -> impl Iterator<Item = &'b Annotation> + 'b {
+        std::iter::once(track.uri.as_str())
+            .chain(std::iter::once(track.album_uri.as_str()))
+            .chain(track.artists.iter().map(|a| a.uri.as_str()))
+            .filter_map(|uri| self.annotations.get(uri))
+    }
+
+    fn days_ago(&self, days: u32) -> DateTime<Utc> {
+        self.now - Duration::days(days as i64)
+    }
+}
+
+fn contains(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+fn within<T: PartialOrd>(value: T, min: Option<T>, max: Option<T>) -> bool {
+    min.map(|m| value >= m).unwrap_or(true) && max.map(|m| value <= m).unwrap_or(true)
+}
+
+impl Rule {
+    /// Check whether a track satisfies the rule
+    pub fn matches(&self, track: &LibraryTrack, ctx: &RuleContext) -> bool {
+        match self {
+            Rule::All { rules } => rules.iter().all(|r| r.matches(track, ctx)),
+            Rule::Any { rules } => rules.iter().any(|r| r.matches(track, ctx)),
+            Rule::Not { rule } => !rule.matches(track, ctx),
+            Rule::SavedWithinDays { days } => track.added_at >= ctx.days_ago(*days),
+            Rule::SavedBeforeDays { days } => track.added_at < ctx.days_ago(*days),
+            Rule::PlayedWithinDays { days } => ctx
+                .plays(track)
+                .map(|p| p.last_played >= ctx.days_ago(*days))
+                .unwrap_or(false),
+            Rule::NotPlayedWithinDays { days } => ctx
+                .plays(track)
+                .map(|p| p.last_played < ctx.days_ago(*days))
+                .unwrap_or(true),
+            Rule::PlayCountAtLeast { count } => {
+                ctx.plays(track).map(|p| p.count).unwrap_or(0) >= *count
+            }
+            Rule::PlayCountAtMost { count } => {
+                ctx.plays(track).map(|p| p.count).unwrap_or(0) <= *count
+            }
+            Rule::TitleContains { value } => contains(&track.name, value),
+            Rule::ArtistContains { value } => {
+                track.artists.iter().any(|a| contains(&a.name, value))
+            }
+            Rule::AlbumContains { value } => contains(&track.album, value),
+            Rule::GenreContains { value } => {
+                ctx.mirror.track_genres(track).any(|g| contains(g, value))
+            }
+            Rule::ReleaseYearBetween { from, to } => track
+                .release_year()
+                .map(|year| within(year, *from, *to))
+                .unwrap_or(false),
+            Rule::DurationBetween {
+                min_seconds,
+                max_seconds,
+            } => within(track.duration_ms / 1000, *min_seconds, *max_seconds),
+            Rule::PopularityBetween { min, max } => track
+                .popularity
+                .map(|p| within(p, *min, *max))
+                .unwrap_or(false),
+            Rule::Explicit { value } => track.explicit == *value,
+            Rule::RatingBetween { min, max } => ctx
+                .annotation(track)
+                .and_then(|a| a.rating)
+                .map(|r| within(r, *min, *max))
+                .unwrap_or(false),
+            Rule::HasTag { tag } => ctx.related_annotations(track).any(|a| a.has_tag(tag)),
+            Rule::NoteContains { value } => ctx
+                .annotation(track)
+                .and_then(|a| a.note.as_deref())
+                .map(|n| contains(n, value))
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// Order two optional values; missing ones go last in either direction
+fn compare_values<T: Ord>(a: Option<T>, b: Option<T>, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if descending => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+fn compare(
+    a: &LibraryTrack,
+    b: &LibraryTrack,
+    SortSpec { field, descending }: SortSpec,
+    ctx: &RuleContext,
+) -> Ordering {
+    let text = |s: &str| s.to_lowercase();
+    let artist = |t: &LibraryTrack| t.artists.first().map(|a| text(&a.name)).unwrap_or_default();
+
+    match field {
+        SortField::AddedAt => compare_values(Some(a.added_at), Some(b.added_at), descending),
+        SortField::Title => compare_values(Some(text(&a.name)), Some(text(&b.name)), descending),
+        SortField::Artist => compare_values(Some(artist(a)), Some(artist(b)), descending),
+        SortField::Album => compare_values(Some(text(&a.album)), Some(text(&b.album)), descending),
+        SortField::ReleaseDate => {
+            compare_values(a.release_date.as_ref(), b.release_date.as_ref(), descending)
+        }
+        SortField::Duration => compare_values(Some(a.duration_ms), Some(b.duration_ms), descending),
+        SortField::Popularity => compare_values(a.popularity, b.popularity, descending),
+        SortField::PlayCount => compare_values(
+            ctx.plays(a).map(|p| p.count),
+            ctx.plays(b).map(|p| p.count),
+            descending,
+        ),
+        SortField::LastPlayed => compare_values(
+            ctx.plays(a).map(|p| p.last_played),
+            ctx.plays(b).map(|p| p.last_played),
+            descending,
+        ),
+        SortField::Rating => compare_values(
+            ctx.annotation(a).and_then(|n| n.rating),
+            ctx.annotation(b).and_then(|n| n.rating),
+            descending,
+        ),
+        SortField::Random => Ordering::Equal,
+    }
+}
+
+/// Select, sort and limit the library tracks matching a rule set
+pub fn evaluate<'a>(set: &RuleSet, ctx: &RuleContext<'a>) -> Vec<&'a LibraryTrack> {
+    let mut tracks: Vec<&LibraryTrack> = ctx
+        .mirror
+        .tracks
+        .iter()
+        .filter(|t| !ctx.blocklist.blocks_library_track(t))
+        .filter(|t| set.rule.matches(t, ctx))
+        .collect();
+
+    match set.sort {
+        Some(SortSpec {
+            field: SortField::Random,
+            ..
+        }) => tracks.shuffle(&mut rand::thread_rng()),
+        Some(spec) => tracks.sort_by(|a, b| compare(a, b, spec, ctx)),
+        None => {}
+    }
+
+    if let Some(limit) = set.limit {
+        tracks.truncate(limit);
+    }
+    tracks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::SimplifiedArtist;
+
+    fn track(uri: &str, artist_id: &str, days_saved: i64, year: &str) -> LibraryTrack {
+        LibraryTrack {
+            uri: uri.into(),
+            name: format!("Song {}", uri),
+            artists: vec![SimplifiedArtist {
+                id: Some(artist_id.into()),
+                name: format!("Artist {}", artist_id),
+                uri: String::new(),
+            }],
+            album: "Album".into(),
+            album_uri: String::new(),
+            release_date: Some(format!("{}-01-01", year)),
+            duration_ms: 180_000,
+            explicit: false,
+            popularity: Some(50),
+            isrc: None,
+            added_at: Utc::now() - Duration::days(days_saved),
+        }
+    }
+
+    fn mirror() -> LibraryMirror {
+        LibraryMirror {
+            synced_at: Some(Utc::now()),
+            tracks: vec![
+                track("a", "jazz1", 5, "1959"),
+                track("b", "jazz1", 60, "1961"),
+                track("c", "rock1", 3, "1971"),
+                track("d", "jazz2", 10, "2004"),
+            ],
+            genres: HashMap::from([
+                ("jazz1".to_string(), vec!["Cool Jazz".to_string()]),
+                ("jazz2".to_string(), vec!["nu jazz".to_string()]),
+                ("rock1".to_string(), vec!["rock".to_string()]),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_recent_unplayed_jazz() {
+        let mirror = mirror();
+        let plays = HashMap::from([(
+            "d".to_string(),
+            PlayStats {
+                count: 3,
+                last_played: Utc::now() - Duration::days(2),
+            },
+        )]);
+        let ctx = RuleContext {
+            now: Utc::now(),
+            mirror: &mirror,
+            plays: &plays,
+            annotations: &Annotations::default(),
+            blocklist: &Blocklist::default(),
+        };
+        let set = RuleSet {
+            rule: Rule::All {
+                rules: vec![
+                    Rule::SavedWithinDays { days: 30 },
+                    Rule::GenreContains {
+                        value: "jazz".into(),
+                    },
+                    Rule::NotPlayedWithinDays { days: 90 },
+                ],
+            },
+            sort: None,
+            limit: None,
+        };
+
+        let uris: Vec<&str> = evaluate(&set, &ctx)
+            .iter()
+            .map(|t| t.uri.as_str())
+            .collect();
+        assert_eq!(uris, vec!["a"]);
+    }
+
+    #[test]
+    fn test_sort_and_limit() {
+        let mirror = mirror();
+        let plays = HashMap::new();
+        let ctx = RuleContext {
+            now: Utc::now(),
+            mirror: &mirror,
+            plays: &plays,
+            annotations: &Annotations::default(),
+            blocklist: &Blocklist::default(),
+        };
+        let set = RuleSet {
+            rule: Rule::Not {
+                rule: Box::new(Rule::ReleaseYearBetween {
+                    from: Some(2000),
+                    to: None,
+                }),
+            },
+            sort: Some(SortSpec {
+                field: SortField::ReleaseDate,
+                descending: true,
+            }),
+            limit: Some(2),
+        };
+
+        let uris: Vec<&str> = evaluate(&set, &ctx)
+            .iter()
+            .map(|t| t.uri.as_str())
+            .collect();
+        assert_eq!(uris, vec!["c", "b"]);
+
+        let mut blocklist = Blocklist::default();
+        Blocklist::add(&mut blocklist.tracks, "c", "Song c");
+        let ctx = RuleContext {
+            blocklist: &blocklist,
+            ..ctx
+        };
+        let uris: Vec<&str> = evaluate(&set, &ctx)
+            .iter()
+            .map(|t| t.uri.as_str())
+            .collect();
+        assert_eq!(uris, vec!["b", "a"]);
+    }
+
+    #[test]
+    fn test_tag_and_rating_rules() {
+        let mirror = mirror();
+        let plays = HashMap::new();
+        let mut annotations = Annotations::default();
+        annotations.set_rating("b", Some(5)).unwrap();
+        annotations.set_rating("c", Some(2)).unwrap();
+        annotations.edit_tags(&["c".to_string()], &["warmup".into()], &[]);
+        let ctx = RuleContext {
+            now: Utc::now(),
+            mirror: &mirror,
+            plays: &plays,
+            annotations: &annotations,
+            blocklist: &Blocklist::default(),
+        };
+        let set = RuleSet {
+            rule: Rule::Any {
+                rules: vec![
+                    Rule::RatingBetween {
+                        min: Some(4),
+                        max: None,
+                    },
+                    Rule::HasTag {
+                        tag: "Warmup".into(),
+                    },
+                ],
+            },
+            sort: Some(SortSpec {
+                field: SortField::Rating,
+                descending: true,
+            }),
+            limit: None,
+        };
+
+        let uris: Vec<&str> = evaluate(&set, &ctx)
+            .iter()
+            .map(|t| t.uri.as_str())
+            .collect();
+        assert_eq!(uris, vec!["b", "c"]);
+    }
+
+    #[test]
+    fn test_sort_keeps_missing_values_last() {
+        let mirror = mirror();
+        let plays = HashMap::new();
+        let mut annotations = Annotations::default();
+        annotations.set_rating("b", Some(5)).unwrap();
+        annotations.set_rating("c", Some(2)).unwrap();
+        let ctx = RuleContext {
+            now: Utc::now(),
+            mirror: &mirror,
+            plays: &plays,
+            annotations: &annotations,
+            blocklist: &Blocklist::default(),
+        };
+
+        for (descending, expected) in [(false, ["c", "b", "a", "d"]), (true, ["b", "c", "a", "d"])]
+        {
+            let set = RuleSet {
+                rule: Rule::ReleaseYearBetween {
+                    from: None,
+                    to: None,
+                },
+                sort: Some(SortSpec {
+                    field: SortField::Rating,
+                    descending,
+                }),
+                limit: None,
+            };
+            let uris: Vec<&str> = evaluate(&set, &ctx)
+                .iter()
+                .map(|t| t.uri.as_str())
+                .collect();
+            assert_eq!(uris, expected);
+        }
+    }
+
+    #[test]
+    fn test_rule_json_shape() {
+        let rule: Rule = serde_json::from_str(
+            r#"{"type":"any","rules":[{"type":"explicit","value":true},{"type":"play_count_at_least","count":5}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            rule,
+            Rule::Any {
+                rules: vec![
+                    Rule::Explicit { value: true },
+                    Rule::PlayCountAtLeast { count: 5 }
+                ]
+            }
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use rand::RngCore;
+use serde::{de::DeserializeOwned, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::auth::{self, AppAuthState, AuthError};
+use crate::error::AppError;
+
+/// Serializes JSON loads, saves and updates, so no write can land between the
+/// load and the save of an update
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Get the application data directory
+pub fn data_dir() -> Result<PathBuf, AppError> {
+    Ok(auth::storage::get_data_dir()?)
+}
+
+/// Get a file path inside the application data directory
+///
+/// For app-wide settings that do not depend on the signed-in account.
+pub fn app_file(name: &str) -> Result<PathBuf, AppError> {
+    let mut path = data_dir()?;
+    path.push(name);
+    Ok(path)
+}
+
+/// Get the data directory of the signed-in account
+///
+/// Everything the backend keeps per user (backups, folders, queue...) lives here,
+/// so switching accounts never mixes data.
+pub fn account_dir(state: &AppAuthState) -> Result<PathBuf, AppError> {
+    let user = auth::current_user(state).ok_or(AuthError::NotAuthenticated)?;
+    let mut path = data_dir()?;
+    path.push("accounts");
+    path.push(sanitize_file_name(&user.id));
+    Ok(path)
+}
+
+/// Get a file path inside the signed-in account's directory
+pub fn account_file(state: &AppAuthState, name: &str) -> Result<PathBuf, AppError> {
+    let mut path = account_dir(state)?;
+    path.push(name);
+    Ok(path)
+}
+
+/// Replace characters that are not safe in file names
+pub fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Generate a random identifier for locally created records
+pub fn new_id() -> String {
+    let mut bytes = [0u8; 8];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Load a JSON document, returning `None` when the file does not exist
+pub fn load_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, AppError> {
+    let _guard = LOCK.lock().unwrap();
+    read_json(path)
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, AppError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let json = fs::read_to_string(path)
+        .map_err(|e| AppError::Storage(format!("Failed to read {:?}: {}", path, e)))?;
+
+    serde_json::from_str(&json)
+        .map(Some)
+        .map_err(|e| AppError::Storage(format!("Failed to deserialize {:?}: {}", path, e)))
+}
+
+/// Save a JSON document, writing to a temporary file first so a crash never
+/// leaves a truncated file behind
+pub fn save_json<T: Serialize>(path: &Path, value: &T) -> Result<(), AppError> {
+    let _guard = LOCK.lock().unwrap();
+    write_json(path, value)
+}
+
+/// Load a JSON document, change it and save it back in one step
+///
+/// A missing file starts from the default value. Nothing is saved when `f`
+/// fails; `f` must not load or save documents itself.
+pub fn update_json<T, R>(
+    path: &Path,
+    f: impl FnOnce(&mut T) -> Result<R, AppError>,
+) -> Result<R, AppError>
+where
+    T: Serialize + DeserializeOwned + Default,
+{
+    let _guard = LOCK.lock().unwrap();
+    let mut value = read_json(path)?.unwrap_or_default();
+    let result = f(&mut value)?;
+    write_json(path, &value)?;
+    Ok(result)
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), AppError> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| AppError::Storage(format!("Failed to serialize: {}", e)))?;
+
+    write_file(path, json.as_bytes())
+}
+
+/// Replace a file through a temporary file, so readers never see it half written
+pub fn write_file(path: &Path, contents: &[u8]) -> Result<(), AppError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| AppError::Storage(format!("Failed to create directory: {}", e)))?;
+    }
+
+    // Suffixed to the full name, so `a.json` and `a.bin` never share one
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    fs::write(&tmp, contents)
+        .map_err(|e| AppError::Storage(format!("Failed to write {:?}: {}", tmp, e)))?;
+    fs::rename(&tmp, path)
+        .map_err(|e| AppError::Storage(format!("Failed to replace {:?}: {}", path, e)))?;
+
+    Ok(())
+}
@@ -0,0 +1,492 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::fade;
+use crate::api::SpotifyApi;
+use crate::auth::{self, AppAuthState};
+use crate::devices::preferences::DeviceRef;
+use crate::error::AppError;
+use crate::playback::events::PlaybackSnapshot;
+use crate::playback::PlaybackHub;
+use crate::store;
+
+const ALARMS_FILE: &str = "alarms.json";
+
+/// How often the scheduler checks for due alarms
+const JOB_TICK: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Alarms missed by more than this (app closed, machine asleep) are skipped
+const MISSED_GRACE_MINUTES: i64 = 5;
+
+/// Volume a ramp-up starts from
+const RAMP_START_VOLUME: u32 = 5;
+
+/// Time between two volume steps of a ramp-up
+const RAMP_STEP: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// How long to wait for a poll showing the alarm playing
+const START_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// When an alarm repeats
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Recurrence {
+    Once { date: NaiveDate },
+    Daily,
+    Weekly { days: Vec<Weekday> },
+}
+
+/// A scheduled playback
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alarm {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    /// Local wall-clock time
+    pub time: NaiveTime,
+    pub recurrence: Recurrence,
+    pub context_uri: String,
+    /// Device to play on; the active device when `None`
+    pub device: Option<DeviceRef>,
+    pub volume: u32,
+    /// Seconds to ramp from a low volume up to `volume`
+    pub ramp_seconds: u32,
+    pub created_at: DateTime<Utc>,
+    pub last_fired: Option<DateTime<Utc>>,
+}
+
+/// Fields the frontend sends when creating or editing an alarm
+#[derive(Debug, Clone, Deserialize)]
+pub struct AlarmInput {
+    pub id: Option<String>,
+    pub name: String,
+    pub enabled: Option<bool>,
+    pub time: NaiveTime,
+    pub recurrence: Recurrence,
+    pub context_uri: String,
+    pub device_id: Option<String>,
+    pub volume: u32,
+    pub ramp_seconds: Option<u32>,
+}
+
+/// An alarm with its next occurrence
+#[derive(Debug, Clone, Serialize)]
+pub struct AlarmStatus {
+    #[serde(flatten)]
+    pub alarm: Alarm,
+    pub next_fire: Option<DateTime<Local>>,
+}
+
+impl Recurrence {
+    fn matches(&self, date: NaiveDate) -> bool {
+        match self {
+            Recurrence::Once { date: once } => *once == date,
+            Recurrence::Daily => true,
+            Recurrence::Weekly { days } => days.contains(&date.weekday()),
+        }
+    }
+}
+
+impl Alarm {
+    /// First occurrence strictly after `after`, in the time zone of `after`
+    pub fn next_occurrence<Tz: TimeZone>(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let start = after.date_naive();
+        (0..=366)
+            .filter_map(|offset| start.checked_add_signed(Duration::days(offset)))
+            .filter(|date| self.recurrence.matches(*date))
+            // Times skipped by a DST change have no local equivalent
+            .filter_map(|date| {
+                after
+                    .timezone()
+                    .from_local_datetime(&date.and_time(self.time))
+                    .earliest()
+            })
+            .find(|at| at > after)
+    }
+
+    /// The occurrence that should fire at `now`, if one is due and not yet fired
+    fn due<Tz: TimeZone>(&self, now: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        if !self.enabled {
+            return None;
+        }
+        let fired = self
+            .last_fired
+            .unwrap_or(self.created_at)
+            .with_timezone(&now.timezone());
+        let window_start = now.clone() - Duration::minutes(MISSED_GRACE_MINUTES);
+        let since = if fired > window_start {
+            fired
+        } else {
+            window_start
+        };
+        self.next_occurrence(&since).filter(|next| next <= now)
+    }
+
+    fn status(self) -> AlarmStatus {
+        let next_fire = if self.enabled {
+            self.next_occurrence(&Local::now())
+        } else {
+            None
+        };
+        AlarmStatus {
+            alarm: self,
+            next_fire,
+        }
+    }
+}
+
+fn load(state: &AppAuthState) -> Result<Vec<Alarm>, AppError> {
+    Ok(store::load_json(&store::account_file(state, ALARMS_FILE)?)?.unwrap_or_default())
+}
+
+fn update<T>(
+    state: &AppAuthState,
+    f: impl FnOnce(&mut Vec<Alarm>) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    store::update_json(&store::account_file(state, ALARMS_FILE)?, f)
+}
+
+/// Whether a snapshot taken after `requested` shows playback running
+fn started_playing(snapshot: Option<&PlaybackSnapshot>, requested: DateTime<Utc>) -> bool {
+    snapshot.is_some_and(|s| s.is_playing && s.observed_at >= requested)
+}
+
+/// Start playback for an alarm and ramp the volume up
+async fn fire(app: &AppHandle, state: &AppAuthState, alarm: &Alarm) -> Result<(), AppError> {
+    let api = SpotifyApi::new(state);
+
+    let device_id = match &alarm.device {
+        Some(device) => {
+            let devices = api.devices().await?;
+            let found = device
+                .resolve(&devices)
+                .and_then(|d| d.id.clone())
+                .ok_or_else(|| AppError::NotFound(format!("Device {}", device.name)))?;
+            Some(found)
+        }
+        None => None,
+    };
+    let device_id = device_id.as_deref();
+
+    let ramping = alarm.ramp_seconds > 0 && alarm.volume > RAMP_START_VOLUME;
+    let first_volume = if ramping {
+        RAMP_START_VOLUME
+    } else {
+        alarm.volume
+    };
+    // Inactive devices reject volume changes; sent again once playback runs
+    let preset = api.set_volume(first_volume, device_id).await.is_ok();
+    let requested = Utc::now();
+    api.play_context(&alarm.context_uri, device_id).await?;
+    let playback = app.state::<PlaybackHub>();
+    playback.refresh();
+    let _ = app.emit("alarm:fired", alarm);
+
+    if preset && !ramping {
+        return Ok(());
+    }
+
+    let waiting = std::time::Instant::now();
+    while !started_playing(playback.current().as_ref(), requested) {
+        if waiting.elapsed() >= START_TIMEOUT {
+            log::warn!("Alarm {} did not start playing", alarm.name);
+            return Ok(());
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+    if !preset {
+        api.set_volume(first_volume, device_id).await?;
+    }
+    if !ramping {
+        return Ok(());
+    }
+
+    let started = std::time::Instant::now();
+    let ramp = std::time::Duration::from_secs(alarm.ramp_seconds as u64);
+    let mut last = Some(first_volume);
+    while started.elapsed() < ramp {
+        tokio::time::sleep(RAMP_STEP).await;
+
+        // Pausing the alarm dismisses it
+        if playback.current().map(|s| !s.is_playing).unwrap_or(false) {
+            return Ok(());
+        }
+
+        let progress = started.elapsed().as_secs_f64() / ramp.as_secs_f64();
+        let volume = fade::ramp(RAMP_START_VOLUME, alarm.volume, progress);
+        if fade::should_send(last, volume) || progress >= 1.0 {
+            api.set_volume(volume, device_id).await?;
+            last = Some(volume);
+        }
+    }
+    Ok(())
+}
+
+/// Mark due alarms as fired and start them
+async fn run_due(app: &AppHandle, state: &AppAuthState) -> Result<(), AppError> {
+    let now = Local::now();
+    if load(state)?.iter().all(|a| a.due(&now).is_none()) {
+        return Ok(());
+    }
+
+    let due = update(state, |alarms| {
+        let mut due = Vec::new();
+        for alarm in alarms.iter_mut().filter(|a| a.due(&now).is_some()) {
+            alarm.last_fired = Some(Utc::now());
+            if matches!(alarm.recurrence, Recurrence::Once { .. }) {
+                alarm.enabled = false;
+            }
+            due.push(alarm.clone());
+        }
+        Ok(due)
+    })?;
+
+    for alarm in due {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let state = app.state::<AppAuthState>();
+            if let Err(e) = fire(&app, &state, &alarm).await {
+                log::warn!("Alarm {} failed: {}", alarm.name, e);
+                let _ = app.emit("alarm:failed", (&alarm.id, e.to_string()));
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Start the background job that fires alarms
+pub fn spawn_alarm_job(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let state = app.state::<AppAuthState>();
+            if auth::current_user(&state).is_some() {
+                if let Err(e) = run_due(&app, &state).await {
+                    log::warn!("Failed to check alarms: {}", e);
+                }
+            }
+            tokio::time::sleep(JOB_TICK).await;
+        }
+    });
+}
+
+/// List alarms with their next occurrence
+#[tauri::command]
+pub fn list_alarms(state: State<AppAuthState>) -> Result<Vec<AlarmStatus>, AppError> {
+    Ok(load(&state)?.into_iter().map(Alarm::status).collect())
+}
+
+/// Create an alarm, or update it when `id` is set
+#[tauri::command]
+pub async fn save_alarm(
+    input: AlarmInput,
+    state: State<'_, AppAuthState>,
+) -> Result<AlarmStatus, AppError> {
+    if input.name.trim().is_empty() {
+        return Err(AppError::InvalidInput("Alarm name is required".into()));
+    }
+    if !input.context_uri.starts_with("spotify:") {
+        return Err(AppError::InvalidInput(format!(
+            "Not a Spotify URI: {}",
+            input.context_uri
+        )));
+    }
+    if let Recurrence::Weekly { days } = &input.recurrence {
+        if days.is_empty() {
+            return Err(AppError::InvalidInput("Pick at least one day".into()));
+        }
+    }
+
+    let device = match input.device_id.as_deref() {
+        Some(id) => {
+            let devices = SpotifyApi::new(&state).devices().await?;
+            let device = devices
+                .iter()
+                .find(|d| d.id.as_deref() == Some(id))
+                .ok_or_else(|| AppError::NotFound(format!("Device {}", id)))?;
+            Some(DeviceRef::from(device))
+        }
+        None => None,
+    };
+
+    let alarm = update(&state, |alarms| {
+        let now = Utc::now();
+        let existing = match input.id.as_deref() {
+            Some(id) => Some(
+                alarms
+                    .iter()
+                    .position(|a| a.id == id)
+                    .ok_or_else(|| AppError::NotFound(format!("Alarm {}", id)))?,
+            ),
+            None => None,
+        };
+
+        let alarm = Alarm {
+            id: input.id.clone().unwrap_or_else(store::new_id),
+            name: input.name.trim().to_string(),
+            enabled: input.enabled.unwrap_or(true),
+            time: input.time,
+            recurrence: input.recurrence,
+            context_uri: input.context_uri,
+            device,
+            volume: input.volume.min(100),
+            ramp_seconds: input.ramp_seconds.unwrap_or(0),
+            // Editing restarts the schedule so a changed time cannot fire retroactively
+            created_at: now,
+            last_fired: existing.and_then(|i| alarms[i].last_fired),
+        };
+        match existing {
+            Some(index) => alarms[index] = alarm.clone(),
+            None => alarms.push(alarm.clone()),
+        }
+        Ok(alarm)
+    })?;
+    Ok(alarm.status())
+}
+
+/// Delete an alarm
+#[tauri::command]
+pub fn delete_alarm(id: String, state: State<AppAuthState>) -> Result<(), AppError> {
+    update(&state, |alarms| {
+        let before = alarms.len();
+        alarms.retain(|a| a.id != id);
+        if alarms.len() == before {
+            return Err(AppError::NotFound(format!("Alarm {}", id)));
+        }
+        Ok(())
+    })
+}
+
+/// Enable or disable an alarm
+#[tauri::command]
+pub fn set_alarm_enabled(
+    id: String,
+    enabled: bool,
+    state: State<AppAuthState>,
+) -> Result<AlarmStatus, AppError> {
+    let alarm = update(&state, |alarms| {
+        let alarm = alarms
+            .iter_mut()
+            .find(|a| a.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("Alarm {}", id)))?;
+        alarm.enabled = enabled;
+        if enabled {
+            alarm.created_at = Utc::now();
+            alarm.last_fired = None;
+        }
+        Ok(alarm.clone())
+    })?;
+    Ok(alarm.status())
+}
+
+/// Fire an alarm immediately, to try out the device and volume ramp
+#[tauri::command]
+pub async fn test_alarm(
+    app: AppHandle,
+    id: String,
+    state: State<'_, AppAuthState>,
+) -> Result<(), AppError> {
+    let alarm = load(&state)?
+        .into_iter()
+        .find(|a| a.id == id)
+        .ok_or_else(|| AppError::NotFound(format!("Alarm {}", id)))?;
+    fire(&app, &state, &alarm).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alarm(time: &str, recurrence: Recurrence, created_at: &str) -> Alarm {
+        Alarm {
+            id: "a".into(),
+            name: "Wake up".into(),
+            enabled: true,
+            time: time.parse().unwrap(),
+            recurrence,
+            context_uri: "spotify:playlist:x".into(),
+            device: None,
+            volume: 60,
+            ramp_seconds: 120,
+            created_at: created_at.parse().unwrap(),
+            last_fired: None,
+        }
+    }
+
+    fn at(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_weekly_next_occurrence() {
+        // 2026-10-16 is a Friday
+        let alarm = alarm(
+            "07:30:00",
+            Recurrence::Weekly {
+                days: vec![Weekday::Mon, Weekday::Fri],
+            },
+            "2026-10-01T00:00:00Z",
+        );
+
+        assert_eq!(
+            alarm.next_occurrence(&at("2026-10-16T07:00:00Z")),
+            Some(at("2026-10-16T07:30:00Z"))
+        );
+        assert_eq!(
+            alarm.next_occurrence(&at("2026-10-16T07:30:00Z")),
+            Some(at("2026-10-19T07:30:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_due_and_missed() {
+        let mut alarm = alarm("07:30:00", Recurrence::Daily, "2026-10-16T06:00:00Z");
+
+        assert!(alarm.due(&at("2026-10-16T07:29:00Z")).is_none());
+        assert!(alarm.due(&at("2026-10-16T07:31:00Z")).is_some());
+        // Too late: the app was not running at 07:30
+        assert!(alarm.due(&at("2026-10-16T09:00:00Z")).is_none());
+
+        // A missed occurrence does not block the following ones
+        assert!(alarm.due(&at("2026-10-17T07:30:05Z")).is_some());
+
+        alarm.last_fired = Some(at("2026-10-16T07:30:10Z"));
+        assert!(alarm.due(&at("2026-10-16T07:31:00Z")).is_none());
+        assert!(alarm.due(&at("2026-10-17T07:30:05Z")).is_some());
+    }
+
+    #[test]
+    fn test_once_only_matches_its_date() {
+        let alarm = alarm(
+            "06:00:00",
+            Recurrence::Once {
+                date: "2026-10-20".parse().unwrap(),
+            },
+            "2026-10-18T12:00:00Z",
+        );
+        assert_eq!(
+            alarm.next_occurrence(&at("2026-10-18T12:00:00Z")),
+            Some(at("2026-10-20T06:00:00Z"))
+        );
+        assert_eq!(alarm.next_occurrence(&at("2026-10-20T06:00:00Z")), None);
+    }
+
+    #[test]
+    fn test_started_playing_ignores_older_snapshots() {
+        let requested = Utc::now();
+        let playing = |observed_at, is_playing| PlaybackSnapshot {
+            observed_at,
+            is_playing,
+            ..PlaybackSnapshot::for_test(serde_json::json!({
+                "id": null, "uri": "a", "name": "a", "duration_ms": 200_000
+            }))
+        };
+
+        let before = requested - Duration::seconds(2);
+        let after = requested + Duration::seconds(2);
+        assert!(!started_playing(None, requested));
+        assert!(!started_playing(Some(&playing(before, true)), requested));
+        assert!(!started_playing(Some(&playing(after, false)), requested));
+        assert!(started_playing(Some(&playing(after, true)), requested));
+    }
+}
@@ -0,0 +1,38 @@
+/// Smallest volume change worth sending to the device
+pub const MIN_VOLUME_STEP: u32 = 3;
+
+/// Volume at `progress` (0.0 to 1.0) of a linear ramp from `from` to `to`
+pub fn ramp(from: u32, to: u32, progress: f64) -> u32 {
+    let progress = progress.clamp(0.0, 1.0);
+    let volume = from as f64 + (to as f64 - from as f64) * progress;
+    volume.round().clamp(0.0, 100.0) as u32
+}
+
+/// Whether moving from the last sent volume to `target` deserves a request
+pub fn should_send(last: Option<u32>, target: u32) -> bool {
+    match last {
+        Some(last) => last.abs_diff(target) >= MIN_VOLUME_STEP || (target == 0 && last != 0),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ramp() {
+        assert_eq!(ramp(80, 0, 0.0), 80);
+        assert_eq!(ramp(80, 0, 0.25), 60);
+        assert_eq!(ramp(80, 0, 2.0), 0);
+        assert_eq!(ramp(10, 50, 0.5), 30);
+    }
+
+    #[test]
+    fn test_should_send() {
+        assert!(should_send(None, 40));
+        assert!(!should_send(Some(40), 42));
+        assert!(should_send(Some(40), 37));
+        assert!(should_send(Some(1), 0));
+    }
+}
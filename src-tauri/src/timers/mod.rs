@@ -0,0 +1,6 @@
+pub mod alarm;
+pub mod fade;
+pub mod sleep;
+
+pub use alarm::*;
+pub use sleep::*;
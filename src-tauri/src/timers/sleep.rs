@@ -0,0 +1,400 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::broadcast::error::RecvError;
+
+use super::fade;
+use crate::api::SpotifyApi;
+use crate::auth::AppAuthState;
+use crate::error::AppError;
+use crate::playback::events::PlaybackSnapshot;
+use crate::playback::PlaybackHub;
+
+/// Playback is paused this close to the end of a track rather than after it
+const TRACK_END_MARGIN_MS: u64 = 1500;
+
+/// When the sleep timer stops playback
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SleepMode {
+    AfterMinutes {
+        minutes: u32,
+    },
+    EndOfTrack,
+    /// Stops once playback leaves the current album or playlist; no fade-out,
+    /// since the remaining time of a context is unknown
+    EndOfContext,
+}
+
+/// A running sleep timer
+#[derive(Debug, Clone, Serialize)]
+pub struct SleepTimer {
+    pub mode: SleepMode,
+    pub started_at: DateTime<Utc>,
+    pub deadline: Option<DateTime<Utc>>,
+    /// Track or context URI the timer is bound to
+    pub target_uri: Option<String>,
+    pub fade_seconds: Option<u32>,
+    /// Volume before the fade-out started, restored after stopping
+    pub original_volume: Option<u32>,
+    pub last_volume: Option<u32>,
+}
+
+/// What the timer wants done after looking at a poll
+#[derive(Debug, PartialEq)]
+enum Step {
+    Wait,
+    Fade(u32),
+    Stop,
+}
+
+impl SleepTimer {
+    fn new(
+        mode: SleepMode,
+        fade_seconds: Option<u32>,
+        snapshot: Option<&PlaybackSnapshot>,
+        now: DateTime<Utc>,
+    ) -> Result<Self, AppError> {
+        let playing = || {
+            snapshot
+                .filter(|s| s.track.is_some())
+                .ok_or_else(|| AppError::InvalidInput("Nothing is playing".into()))
+        };
+
+        let (deadline, target_uri) = match &mode {
+            SleepMode::AfterMinutes { minutes } => {
+                if *minutes == 0 {
+                    return Err(AppError::InvalidInput(
+                        "Duration must be at least a minute".into(),
+                    ));
+                }
+                (Some(now + Duration::minutes(*minutes as i64)), None)
+            }
+            SleepMode::EndOfTrack => (None, playing()?.track_uri().map(str::to_string)),
+            SleepMode::EndOfContext => {
+                let context = playing()?.context_uri.clone().ok_or_else(|| {
+                    AppError::InvalidInput("Playback has no album or playlist".into())
+                })?;
+                (None, Some(context))
+            }
+        };
+
+        Ok(Self {
+            fade_seconds: fade_seconds
+                .filter(|_| mode != SleepMode::EndOfContext && fade_seconds != Some(0)),
+            mode,
+            started_at: now,
+            deadline,
+            target_uri,
+            original_volume: None,
+            last_volume: None,
+        })
+    }
+
+    /// Time left before playback stops, when it can be known
+    pub fn remaining_ms(
+        &self,
+        snapshot: Option<&PlaybackSnapshot>,
+        now: DateTime<Utc>,
+    ) -> Option<u64> {
+        match self.mode {
+            SleepMode::AfterMinutes { .. } => self
+                .deadline
+                .map(|d| (d - now).num_milliseconds().max(0) as u64),
+            SleepMode::EndOfTrack => snapshot
+                .filter(|s| s.track_uri() == self.target_uri.as_deref())
+                .and_then(|s| s.remaining_ms())
+                .map(|r| r.saturating_sub(TRACK_END_MARGIN_MS)),
+            SleepMode::EndOfContext => None,
+        }
+    }
+
+    fn step(&self, snapshot: Option<&PlaybackSnapshot>, now: DateTime<Utc>) -> Step {
+        let playing = snapshot.map(|s| s.is_playing).unwrap_or(false);
+
+        let finished = match self.mode {
+            SleepMode::AfterMinutes { .. } => self.deadline.map(|d| now >= d).unwrap_or(true),
+            SleepMode::EndOfTrack => {
+                snapshot.and_then(|s| s.track_uri()) != self.target_uri.as_deref()
+            }
+            SleepMode::EndOfContext => {
+                snapshot.and_then(|s| s.context_uri.as_deref()) != self.target_uri.as_deref()
+                    || snapshot.is_some_and(ran_out)
+            }
+        };
+        let remaining = self.remaining_ms(snapshot, now);
+        if finished || remaining == Some(0) {
+            return Step::Stop;
+        }
+        if !playing {
+            return Step::Wait;
+        }
+
+        let (Some(fade_seconds), Some(remaining)) = (self.fade_seconds, remaining) else {
+            return Step::Wait;
+        };
+        let fade_ms = fade_seconds as u64 * 1000;
+        if remaining > fade_ms {
+            return Step::Wait;
+        }
+
+        let from = self
+            .original_volume
+            .or_else(|| snapshot.and_then(|s| s.device.volume_percent))
+            .unwrap_or(100);
+        let target = fade::ramp(from, 0, 1.0 - remaining as f64 / fade_ms as f64);
+        if fade::should_send(self.last_volume, target) {
+            Step::Fade(target)
+        } else {
+            Step::Wait
+        }
+    }
+}
+
+/// Whether playback stopped by itself at the end of the last track; Spotify
+/// keeps the context when it runs out, rewound to the start or left at the end
+fn ran_out(snapshot: &PlaybackSnapshot) -> bool {
+    let Some(track) = snapshot.track.as_ref().filter(|_| !snapshot.is_playing) else {
+        return false;
+    };
+    snapshot.progress_ms == 0 || snapshot.progress_ms + TRACK_END_MARGIN_MS >= track.duration_ms
+}
+
+/// The sleep timer, kept by the backend so it survives window reloads
+#[derive(Default)]
+pub struct SleepTimerHub {
+    timer: Mutex<Option<SleepTimer>>,
+}
+
+impl SleepTimerHub {
+    fn get(&self) -> Option<SleepTimer> {
+        self.timer.lock().unwrap().clone()
+    }
+
+    fn set(&self, timer: Option<SleepTimer>) -> Option<SleepTimer> {
+        std::mem::replace(&mut *self.timer.lock().unwrap(), timer)
+    }
+}
+
+/// Timer state reported to the UI
+#[derive(Debug, Clone, Serialize)]
+pub struct SleepTimerStatus {
+    pub timer: SleepTimer,
+    pub remaining_ms: Option<u64>,
+}
+
+fn status(timer: SleepTimer, playback: &PlaybackHub) -> SleepTimerStatus {
+    SleepTimerStatus {
+        remaining_ms: timer.remaining_ms(playback.current().as_ref(), Utc::now()),
+        timer,
+    }
+}
+
+/// Put the volume back to where it was before fading out
+async fn restore_volume(state: &AppAuthState, timer: &SleepTimer, device_id: Option<&str>) {
+    if let Some(volume) = timer.original_volume {
+        if let Err(e) = SpotifyApi::new(state).set_volume(volume, device_id).await {
+            log::warn!("Failed to restore volume after sleep timer: {}", e);
+        }
+    }
+}
+
+async fn tick(
+    app: &AppHandle,
+    state: &AppAuthState,
+    hub: &SleepTimerHub,
+    snapshot: Option<&PlaybackSnapshot>,
+) -> Result<(), AppError> {
+    let Some(timer) = hub.get() else {
+        return Ok(());
+    };
+    let device_id = snapshot.and_then(|s| s.device.id.as_deref());
+    let api = SpotifyApi::new(state);
+
+    match timer.step(snapshot, Utc::now()) {
+        Step::Wait => {}
+        Step::Fade(volume) => {
+            api.set_volume(volume, device_id).await?;
+            let mut timer = timer;
+            timer.original_volume = timer
+                .original_volume
+                .or_else(|| snapshot.and_then(|s| s.device.volume_percent));
+            timer.last_volume = Some(volume);
+            hub.set(Some(timer));
+        }
+        Step::Stop => {
+            hub.set(None);
+            if snapshot.map(|s| s.is_playing).unwrap_or(false) {
+                api.pause(device_id).await?;
+            }
+            restore_volume(state, &timer, device_id).await;
+            app.state::<PlaybackHub>().refresh();
+            let _ = app.emit("sleep-timer:finished", &timer);
+            let _ = app.emit("sleep-timer:changed", None::<SleepTimerStatus>);
+        }
+    }
+    Ok(())
+}
+
+/// Start the background job driving the sleep timer from playback polls
+pub fn spawn_sleep_timer_job(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut updates = app.state::<PlaybackHub>().subscribe();
+        loop {
+            let update = match updates.recv().await {
+                Ok(update) => update,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+
+            let state = app.state::<AppAuthState>();
+            let hub = app.state::<SleepTimerHub>();
+            if let Err(e) = tick(&app, &state, &hub, update.snapshot.as_ref()).await {
+                log::warn!("Sleep timer step failed: {}", e);
+            }
+        }
+    });
+}
+
+/// Start (or replace) the sleep timer
+#[tauri::command]
+pub async fn start_sleep_timer(
+    app: AppHandle,
+    mode: SleepMode,
+    fade_seconds: Option<u32>,
+    state: State<'_, AppAuthState>,
+    hub: State<'_, SleepTimerHub>,
+    playback: State<'_, PlaybackHub>,
+) -> Result<SleepTimerStatus, AppError> {
+    let snapshot = playback.current();
+    let timer = SleepTimer::new(mode, fade_seconds, snapshot.as_ref(), Utc::now())?;
+
+    if let Some(previous) = hub.set(Some(timer.clone())) {
+        restore_volume(
+            &state,
+            &previous,
+            snapshot.as_ref().and_then(|s| s.device.id.as_deref()),
+        )
+        .await;
+    }
+
+    let status = status(timer, &playback);
+    let _ = app.emit("sleep-timer:changed", Some(&status));
+    Ok(status)
+}
+
+/// Cancel the sleep timer, restoring the volume if it was fading out
+#[tauri::command]
+pub async fn cancel_sleep_timer(
+    app: AppHandle,
+    state: State<'_, AppAuthState>,
+    hub: State<'_, SleepTimerHub>,
+    playback: State<'_, PlaybackHub>,
+) -> Result<(), AppError> {
+    if let Some(timer) = hub.set(None) {
+        let snapshot = playback.current();
+        restore_volume(
+            &state,
+            &timer,
+            snapshot.as_ref().and_then(|s| s.device.id.as_deref()),
+        )
+        .await;
+        let _ = app.emit("sleep-timer:changed", None::<SleepTimerStatus>);
+    }
+    Ok(())
+}
+
+/// Get the running sleep timer, if any
+#[tauri::command]
+pub fn get_sleep_timer(
+    hub: State<SleepTimerHub>,
+    playback: State<PlaybackHub>,
+) -> Option<SleepTimerStatus> {
+    hub.get().map(|timer| status(timer, &playback))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(track: &str, context: &str, progress_ms: u64) -> PlaybackSnapshot {
+        let mut snapshot = PlaybackSnapshot {
+            context_uri: Some(context.into()),
+            progress_ms,
+            ..PlaybackSnapshot::for_test(serde_json::json!({
+                "id": null, "uri": track, "name": track, "duration_ms": 200_000
+            }))
+        };
+        snapshot.device.volume_percent = Some(80);
+        snapshot
+    }
+
+    #[test]
+    fn test_after_minutes_fades_then_stops() {
+        let now = Utc::now();
+        let playing = snapshot("a", "album", 0);
+        let timer = SleepTimer::new(
+            SleepMode::AfterMinutes { minutes: 10 },
+            Some(60),
+            Some(&playing),
+            now,
+        )
+        .unwrap();
+
+        assert_eq!(timer.step(Some(&playing), now), Step::Wait);
+        assert_eq!(
+            timer.step(Some(&playing), now + Duration::seconds(570)),
+            Step::Fade(40)
+        );
+        assert_eq!(
+            timer.step(Some(&playing), now + Duration::minutes(10)),
+            Step::Stop
+        );
+    }
+
+    #[test]
+    fn test_end_of_track_and_context() {
+        let now = Utc::now();
+        let playing = snapshot("a", "album", 100_000);
+        let track = SleepTimer::new(SleepMode::EndOfTrack, None, Some(&playing), now).unwrap();
+        let context =
+            SleepTimer::new(SleepMode::EndOfContext, Some(30), Some(&playing), now).unwrap();
+        assert_eq!(context.fade_seconds, None);
+
+        assert_eq!(track.step(Some(&playing), now), Step::Wait);
+        assert_eq!(
+            track.step(Some(&snapshot("a", "album", 199_000)), now),
+            Step::Stop
+        );
+        assert_eq!(
+            track.step(Some(&snapshot("b", "album", 0)), now),
+            Step::Stop
+        );
+
+        assert_eq!(
+            context.step(Some(&snapshot("b", "album", 0)), now),
+            Step::Wait
+        );
+        assert_eq!(
+            context.step(Some(&snapshot("c", "radio", 0)), now),
+            Step::Stop
+        );
+        assert!(SleepTimer::new(SleepMode::EndOfTrack, None, None, now).is_err());
+    }
+
+    #[test]
+    fn test_end_of_context_when_last_track_ends() {
+        let now = Utc::now();
+        let playing = snapshot("a", "album", 100_000);
+        let timer = SleepTimer::new(SleepMode::EndOfContext, None, Some(&playing), now).unwrap();
+        let paused = |progress_ms| PlaybackSnapshot {
+            is_playing: false,
+            ..snapshot("c", "album", progress_ms)
+        };
+
+        assert_eq!(timer.step(Some(&paused(100_000)), now), Step::Wait);
+        assert_eq!(timer.step(Some(&paused(200_000)), now), Step::Stop);
+        assert_eq!(timer.step(Some(&paused(0)), now), Step::Stop);
+    }
+}
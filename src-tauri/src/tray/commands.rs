@@ -0,0 +1,269 @@
+use serde::{Deserialize, Serialize};
+use tauri::menu::{
+    CheckMenuItem, IsMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu,
+};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Listener, Manager, WebviewWindow, WindowEvent, Wry};
+use tokio::sync::broadcast::error::RecvError;
+
+use super::menu::{self, TrayAction};
+use crate::auth::AppAuthState;
+use crate::devices::{self, DeviceHub};
+use crate::error::AppError;
+use crate::playback::events::PlaybackEvent;
+use crate::playback::{self, PlaybackHub, PlayerCommand};
+use crate::store;
+use crate::window;
+
+const TRAY_ID: &str = "main";
+const TRAY_FILE: &str = "tray.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TraySettings {
+    /// Closing the main window hides it to the tray instead of quitting
+    pub minimize_to_tray: bool,
+}
+
+fn load() -> Result<TraySettings, AppError> {
+    Ok(store::load_json(&store::app_file(TRAY_FILE)?)?.unwrap_or_default())
+}
+
+fn update(f: impl FnOnce(&mut TraySettings)) -> Result<TraySettings, AppError> {
+    store::update_json(
+        &store::app_file(TRAY_FILE)?,
+        |settings: &mut TraySettings| {
+            f(settings);
+            Ok(settings.clone())
+        },
+    )
+}
+
+fn build_menu(app: &AppHandle) -> tauri::Result<Menu<Wry>> {
+    let snapshot = app.state::<PlaybackHub>().current();
+    let snapshot = snapshot.as_ref();
+    let playing = snapshot.is_some_and(|s| s.track.is_some());
+    let item = |action: TrayAction, text: &str, enabled: bool| {
+        MenuItem::with_id(app, action.id(), text, enabled, None::<&str>)
+    };
+
+    let now_playing = MenuItem::with_id(
+        app,
+        "now_playing",
+        menu::now_playing_label(snapshot),
+        false,
+        None::<&str>,
+    )?;
+    let play_pause = item(
+        TrayAction::PlayPause,
+        menu::play_pause_label(snapshot),
+        true,
+    )?;
+    let next = item(TrayAction::Next, "Next", playing)?;
+    let previous = item(TrayAction::Previous, "Previous", playing)?;
+    let like = item(TrayAction::Like, "Save to Liked Songs", playing)?;
+
+    let active = snapshot.and_then(|s| s.device.id.clone());
+    let devices = app
+        .state::<DeviceHub>()
+        .known()
+        .into_iter()
+        .filter_map(|device| {
+            let id = device.id?;
+            let checked = active.as_deref() == Some(id.as_str());
+            CheckMenuItem::with_id(
+                app,
+                TrayAction::Transfer(id).id(),
+                device.name.replace('&', "&&"),
+                !device.is_restricted,
+                checked,
+                None::<&str>,
+            )
+            .ok()
+        })
+        .collect::<Vec<_>>();
+    let device_items: Vec<&dyn IsMenuItem<Wry>> =
+        devices.iter().map(|d| d as &dyn IsMenuItem<Wry>).collect();
+    let device_menu = Submenu::with_items(app, "Devices", !devices.is_empty(), &device_items)?;
+
+    let minimize = CheckMenuItem::with_id(
+        app,
+        TrayAction::ToggleMinimizeToTray.id(),
+        "Minimize to Tray on Close",
+        true,
+        load().map(|s| s.minimize_to_tray).unwrap_or(false),
+        None::<&str>,
+    )?;
+    let show = item(TrayAction::Show, "Show Window", true)?;
+    let quit = item(TrayAction::Quit, "Quit", true)?;
+
+    Menu::with_items(
+        app,
+        &[
+            &now_playing,
+            &PredefinedMenuItem::separator(app)?,
+            &play_pause,
+            &next,
+            &previous,
+            &like,
+            &PredefinedMenuItem::separator(app)?,
+            &device_menu,
+            &PredefinedMenuItem::separator(app)?,
+            &show,
+            &minimize,
+            &quit,
+        ],
+    )
+}
+
+/// Rebuild the menu and tooltip from the latest playback state
+fn refresh(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let snapshot = app.state::<PlaybackHub>().current();
+    let name = app.package_info().name.clone();
+
+    if let Err(e) = build_menu(app).and_then(|menu| tray.set_menu(Some(menu))) {
+        log::warn!("Failed to update the tray menu: {}", e);
+    }
+    let _ = tray.set_tooltip(Some(menu::tooltip(&name, snapshot.as_ref())));
+}
+
+fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+    let Some(action) = TrayAction::parse(event.id().as_ref()) else {
+        return;
+    };
+
+    match action {
+        TrayAction::Show => window::show_main_window(app),
+        TrayAction::Quit => app.exit(0),
+        TrayAction::ToggleMinimizeToTray => {
+            match update(|s| s.minimize_to_tray = !s.minimize_to_tray) {
+                Ok(settings) => {
+                    let _ = app.emit("tray:settings-changed", &settings);
+                }
+                Err(e) => log::warn!("Failed to save tray settings: {}", e),
+            }
+            refresh(app);
+        }
+        action => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = run(&app, action).await {
+                    log::warn!("Tray action failed: {}", e);
+                    let _ = app.emit("tray:failed", e.to_string());
+                }
+            });
+        }
+    }
+}
+
+async fn run(app: &AppHandle, action: TrayAction) -> Result<(), AppError> {
+    let state = app.state::<AppAuthState>();
+    let hub = app.state::<PlaybackHub>();
+
+    let command = match action {
+        TrayAction::PlayPause => PlayerCommand::TogglePlay,
+        TrayAction::Next => PlayerCommand::Next,
+        TrayAction::Previous => PlayerCommand::Previous,
+        TrayAction::Like => {
+            let track = playback::like_current_track(&state, &hub).await?;
+            let _ = app.emit("library:track-saved", &track);
+            return Ok(());
+        }
+        TrayAction::Transfer(device_id) => {
+            let devices = app.state::<DeviceHub>();
+            return devices::transfer_to_device(app, &state, &devices, &device_id, true).await;
+        }
+        TrayAction::Show | TrayAction::Quit | TrayAction::ToggleMinimizeToTray => return Ok(()),
+    };
+
+    playback::execute(&state, &hub, command).await
+}
+
+fn handle_tray_event(tray: &TrayIcon, event: TrayIconEvent) {
+    if let TrayIconEvent::Click {
+        button: MouseButton::Left,
+        button_state: MouseButtonState::Up,
+        ..
+    } = event
+    {
+        window::show_main_window(tray.app_handle());
+    }
+}
+
+/// Hide the main window instead of closing it when minimize to tray is on
+fn hide_on_close(window: &WebviewWindow, event: &WindowEvent) {
+    if let WindowEvent::CloseRequested { api, .. } = event {
+        if load().map(|s| s.minimize_to_tray).unwrap_or(false) {
+            api.prevent_close();
+            let _ = window.hide();
+        }
+    }
+}
+
+/// Create the tray icon and keep its menu in sync with playback and devices
+pub fn spawn_tray(app: &AppHandle) -> tauri::Result<()> {
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(&app.package_info().name)
+        .menu(&build_menu(app)?)
+        .show_menu_on_left_click(false)
+        .on_menu_event(handle_menu_event)
+        .on_tray_icon_event(handle_tray_event);
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+
+    if let Some(window) = app.get_webview_window("main") {
+        let main = window.clone();
+        window.on_window_event(move |event| hide_on_close(&main, event));
+    }
+
+    // The device job lists devices at startup and whenever they change; refresh
+    // once more in case its first listing landed before this listener
+    let handle = app.clone();
+    app.listen_any("devices:changed", move |_| refresh(&handle));
+    refresh(app);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut updates = app.state::<PlaybackHub>().subscribe();
+        loop {
+            let update = match updates.recv().await {
+                Ok(update) => update,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+            // Seeks, volume, shuffle and repeat don't change the menu
+            let relevant = update.events.iter().any(|e| {
+                matches!(
+                    e,
+                    PlaybackEvent::TrackChanged { .. }
+                        | PlaybackEvent::Paused { .. }
+                        | PlaybackEvent::Resumed { .. }
+                        | PlaybackEvent::DeviceChanged { .. }
+                )
+            });
+            if relevant {
+                refresh(&app);
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Get the tray settings
+#[tauri::command]
+pub fn get_tray_settings() -> Result<TraySettings, AppError> {
+    load()
+}
+
+/// Hide to the tray instead of quitting when the main window is closed
+#[tauri::command]
+pub fn set_minimize_to_tray(app: AppHandle, enabled: bool) -> Result<TraySettings, AppError> {
+    let settings = update(|s| s.minimize_to_tray = enabled)?;
+    refresh(&app);
+    Ok(settings)
+}
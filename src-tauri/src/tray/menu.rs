@@ -0,0 +1,153 @@
+use crate::playback::events::PlaybackSnapshot;
+
+/// Longest track label shown in the menu before it is shortened
+const MAX_LABEL_CHARS: usize = 48;
+
+const DEVICE_PREFIX: &str = "device:";
+
+/// A clickable tray menu entry
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrayAction {
+    PlayPause,
+    Next,
+    Previous,
+    Like,
+    Show,
+    Transfer(String),
+    ToggleMinimizeToTray,
+    Quit,
+}
+
+impl TrayAction {
+    pub fn id(&self) -> String {
+        match self {
+            TrayAction::PlayPause => "play_pause".into(),
+            TrayAction::Next => "next".into(),
+            TrayAction::Previous => "previous".into(),
+            TrayAction::Like => "like".into(),
+            TrayAction::Show => "show".into(),
+            TrayAction::Transfer(device_id) => format!("{}{}", DEVICE_PREFIX, device_id),
+            TrayAction::ToggleMinimizeToTray => "minimize_to_tray".into(),
+            TrayAction::Quit => "quit".into(),
+        }
+    }
+
+    pub fn parse(id: &str) -> Option<Self> {
+        if let Some(device_id) = id.strip_prefix(DEVICE_PREFIX) {
+            return Some(TrayAction::Transfer(device_id.to_string()));
+        }
+        Some(match id {
+            "play_pause" => TrayAction::PlayPause,
+            "next" => TrayAction::Next,
+            "previous" => TrayAction::Previous,
+            "like" => TrayAction::Like,
+            "show" => TrayAction::Show,
+            "minimize_to_tray" => TrayAction::ToggleMinimizeToTray,
+            "quit" => TrayAction::Quit,
+            _ => return None,
+        })
+    }
+}
+
+/// Shorten a label to `max` characters, ending with an ellipsis when cut
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut short: String = text.chars().take(max - 1).collect();
+    short.push('…');
+    short
+}
+
+/// Menu labels treat `&` as a mnemonic marker on Windows
+fn escape(text: &str) -> String {
+    text.replace('&', "&&")
+}
+
+/// "Title — Artists" of the current track, unescaped
+fn track_line(snapshot: Option<&PlaybackSnapshot>) -> Option<String> {
+    let track = snapshot?.track.as_ref()?;
+    let artists = track.artist_names();
+    Some(if artists.is_empty() {
+        track.name.clone()
+    } else {
+        format!("{} — {}", track.name, artists)
+    })
+}
+
+/// Disabled first entry of the menu describing what is playing
+pub fn now_playing_label(snapshot: Option<&PlaybackSnapshot>) -> String {
+    match track_line(snapshot) {
+        Some(line) => escape(&truncate(&line, MAX_LABEL_CHARS)),
+        None => "Nothing playing".into(),
+    }
+}
+
+pub fn play_pause_label(snapshot: Option<&PlaybackSnapshot>) -> &'static str {
+    if snapshot.is_some_and(|s| s.is_playing) {
+        "Pause"
+    } else {
+        "Play"
+    }
+}
+
+/// Hover text of the tray icon
+pub fn tooltip(app_name: &str, snapshot: Option<&PlaybackSnapshot>) -> String {
+    match track_line(snapshot) {
+        Some(line) if snapshot.is_some_and(|s| !s.is_playing) => {
+            format!("{}\n{} (paused)", app_name, line)
+        }
+        Some(line) => format!("{}\n{}", app_name, line),
+        None => app_name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(name: &str, playing: bool) -> PlaybackSnapshot {
+        PlaybackSnapshot {
+            is_playing: playing,
+            ..PlaybackSnapshot::for_test(serde_json::json!({
+                "id": "1", "uri": "spotify:track:1", "name": name,
+                "artists": [{"id": null, "name": "Simon & Garfunkel", "uri": ""}]
+            }))
+        }
+    }
+
+    #[test]
+    fn test_action_ids_round_trip() {
+        for action in [
+            TrayAction::PlayPause,
+            TrayAction::Like,
+            TrayAction::Transfer("abc:def".into()),
+            TrayAction::ToggleMinimizeToTray,
+            TrayAction::Quit,
+        ] {
+            assert_eq!(TrayAction::parse(&action.id()), Some(action));
+        }
+        assert_eq!(TrayAction::parse("now_playing"), None);
+    }
+
+    #[test]
+    fn test_labels_describe_the_current_track() {
+        let playing = snapshot("The Boxer", true);
+        assert_eq!(
+            now_playing_label(Some(&playing)),
+            "The Boxer — Simon && Garfunkel"
+        );
+        assert_eq!(play_pause_label(Some(&playing)), "Pause");
+        assert_eq!(now_playing_label(None), "Nothing playing");
+        assert_eq!(play_pause_label(None), "Play");
+
+        let long = snapshot(&"la".repeat(40), false);
+        let label = now_playing_label(Some(&long));
+        assert_eq!(label.chars().count(), MAX_LABEL_CHARS);
+        assert!(label.ends_with('…'));
+        assert_eq!(
+            tooltip("App", Some(&long)),
+            format!("App\n{} — Simon & Garfunkel (paused)", "la".repeat(40))
+        );
+    }
+}
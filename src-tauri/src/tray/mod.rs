@@ -0,0 +1,4 @@
+pub mod commands;
+pub mod menu;
+
+pub use commands::*;
@@ -25,3 +25,12 @@ pub async fn toggle_fullscreen(window: Window) -> Result<bool, String> {
         .map_err(|e| e.to_string())?;
     Ok(new_state)
 }
+
+/// Bring the main window to the front, restoring it if hidden or minimized
+pub fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}